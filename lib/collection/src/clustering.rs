@@ -0,0 +1,105 @@
+use std::mem;
+
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+
+use segment::spaces::metric::Metric;
+use segment::types::VectorElementType;
+
+/// Mini-batch k-means (Sculley, 2010).
+/// Each batch moves centroids towards assigned points with decreasing learning rate,
+/// so the whole data set is never required in memory.
+pub struct MiniBatchKMeans {
+    metric: Box<dyn Metric>,
+    centroids: Vec<Vec<VectorElementType>>,
+    /// Number of points assigned to each centroid during all updates
+    counts: Vec<usize>,
+}
+
+impl MiniBatchKMeans {
+    pub fn new(metric: Box<dyn Metric>, initial_centroids: Vec<Vec<VectorElementType>>) -> Self {
+        let counts = vec![0; initial_centroids.len()];
+        MiniBatchKMeans {
+            metric,
+            centroids: initial_centroids,
+            counts,
+        }
+    }
+
+    /// Index of the most similar centroid
+    pub fn nearest(&self, vector: &[VectorElementType]) -> usize {
+        self.centroids
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, centroid)| OrderedFloat(self.metric.similarity(vector, centroid)))
+            .map(|(cluster, _)| cluster)
+            .unwrap_or(0)
+    }
+
+    pub fn update(&mut self, batch: &[Vec<VectorElementType>]) {
+        let assignments = batch.iter().map(|vector| self.nearest(vector)).collect_vec();
+
+        for (vector, &cluster) in batch.iter().zip(assignments.iter()) {
+            self.counts[cluster] += 1;
+            let learning_rate = 1.0 / self.counts[cluster] as VectorElementType;
+            for (centroid_value, value) in self.centroids[cluster].iter_mut().zip(vector) {
+                *centroid_value = (1.0 - learning_rate) * *centroid_value + learning_rate * value;
+            }
+        }
+
+        // Centroids should stay in the same space as stored vectors, e.g. normalized for cosine
+        for cluster in assignments.into_iter().unique() {
+            let centroid = mem::take(&mut self.centroids[cluster]);
+            self.centroids[cluster] = self.metric.preprocess(centroid);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.centroids.len()
+    }
+
+    pub fn centroids(&self) -> &[Vec<VectorElementType>] {
+        &self.centroids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use segment::spaces::tools::mertic_object;
+    use segment::types::Distance;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_mini_batch_kmeans() {
+        let mut rnd = StdRng::seed_from_u64(42);
+        let mut blob_point = |center: VectorElementType| vec![
+            center + rnd.gen_range(-0.1, 0.1),
+            center + rnd.gen_range(-0.1, 0.1),
+        ];
+
+        let mut kmeans = MiniBatchKMeans::new(
+            mertic_object(&Distance::Euclid),
+            vec![vec![-0.2, -0.3], vec![0.3, 0.2]],
+        );
+
+        for _ in 0..20 {
+            let batch = (0..10)
+                .map(|idx| if idx % 2 == 0 { blob_point(-1.0) } else { blob_point(1.0) })
+                .collect_vec();
+            kmeans.update(&batch);
+        }
+
+        let low_cluster = kmeans.nearest(&[-1.0, -1.0]);
+        let high_cluster = kmeans.nearest(&[1.0, 1.0]);
+        assert_ne!(low_cluster, high_cluster);
+
+        for value in &kmeans.centroids()[low_cluster] {
+            assert!((value + 1.0).abs() < 0.1, "{:?}", kmeans.centroids());
+        }
+        for value in &kmeans.centroids()[high_cluster] {
+            assert!((value - 1.0).abs() < 0.1, "{:?}", kmeans.centroids());
+        }
+    }
+}
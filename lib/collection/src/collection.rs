@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use crossbeam_channel::{Sender};
 use itertools::Itertools;
 use parking_lot::{Mutex, RwLock};
+use rand::seq::SliceRandom;
 use tokio::runtime::Runtime;
 
-use segment::types::{HasIdCondition, PointIdType, ScoredPoint, VectorElementType, SegmentType};
+use segment::types::{HasIdCondition, PointIdType, PayloadKeyType, PayloadType, PayloadInterface, PayloadVariant, TheMap, ScoredPoint, ScoreType, SimilarPair, VectorElementType, SegmentType, SeqNumberType, ExampleQuery, FacetValue, FloatPayloadType};
+use segment::spaces::metric::Metric;
+use segment::spaces::tools::{mertic_object, peek_top_scores_iterable};
 use segment::types::Condition;
 use segment::types::Filter;
 
@@ -15,12 +19,48 @@ use crate::collection_builder::optimizers_builder::build_optimizers;
 use crate::config::CollectionConfig;
 use crate::operations::CollectionUpdateOperations;
 use crate::operations::config_diff::{DiffConfig, OptimizersConfigDiff};
-use crate::operations::types::{CollectionError, CollectionInfo, CollectionResult, RecommendRequest, Record, SearchRequest, UpdateResult, UpdateStatus, CollectionStatus};
+use crate::operations::score_formula::ScoreFormula;
+use crate::operations::fusion::fuse;
+use crate::operations::payload_ops::PayloadOps;
+use crate::clustering::MiniBatchKMeans;
+use crate::sampling::merge_weighted_samples;
+use crate::similar_pairs::cross_segment_pairs;
+use crate::operations::types::{CollectionError, CollectionInfo, CollectionResult, RecommendRequest, RecommendExample, RecommendStrategy, DiscoverRequest, ExampleSearchRequest, Record, SearchRequest, SearchRequestBatch, SearchExplainResult, FusionSearchRequest, SearchGroupsRequest, SearchGroupsResult, UpdateResult, UpdateStatus, CollectionStatus, ScrollRequest, ScrollResult, OrderBy, SampleRequest, CountRequest, CountResult, SimilarPairsRequest, ClusterRequest, ClusterResult, DistanceMatrixRequest, DistanceMatrixResult, FacetRequest, FacetResult, FacetHit, HistogramRequest, HistogramResult, HistogramBucket};
 use crate::segment_manager::holders::segment_holder::SegmentHolder;
 use crate::segment_manager::segment_managers::{SegmentSearcher, SegmentUpdater};
 use crate::update_handler::update_handler::{UpdateHandler, UpdateSignal};
 use crate::wal::{SerdeWal};
 
+/// Number of candidates per requested result, re-ranked by diversified search
+const DIVERSITY_CANDIDATES_FACTOR: usize = 4;
+
+/// Max number of candidates fetched for re-ranking, unless more results are requested
+const MAX_RERANK_CANDIDATES: usize = 10_000;
+
+/// Number of candidates per requested result, re-scored by score formula if not specified in request
+const DEFAULT_FORMULA_OVERSAMPLING: usize = 4;
+
+const MAX_FORMULA_OVERSAMPLING: usize = 100;
+
+const DEFAULT_DISTANCE_MATRIX_LIMIT: usize = 3;
+
+const MAX_DISTANCE_MATRIX_SAMPLE: usize = 10_000;
+
+/// Number of sampled points, which neighbours are searched together
+const DISTANCE_MATRIX_SEARCH_BATCH: usize = 64;
+
+const DEFAULT_CLUSTER_BATCH_SIZE: usize = 100;
+
+const MAX_CLUSTER_BATCH_SIZE: usize = 10_000;
+
+const DEFAULT_CLUSTER_ITERATIONS: usize = 100;
+
+const MAX_CLUSTER_ITERATIONS: usize = 10_000;
+
+const MAX_SIMILAR_PAIRS: usize = 100_000;
+
+const MAX_SAMPLE_LIMIT: usize = 10_000;
+
 pub struct Collection {
     pub segments: Arc<RwLock<SegmentHolder>>,
     pub config: Arc<RwLock<CollectionConfig>>,
@@ -88,7 +128,59 @@ impl Collection {
     }
 
     pub fn search(&self, request: Arc<SearchRequest>) -> CollectionResult<Vec<ScoredPoint>> {
-        return self.searcher.search(request);
+        if !Self::requires_rerank(&request) {
+            return self.searcher.search(request);
+        }
+
+        Self::check_rerank_params(&request)?;
+        let candidates = self.searcher.search(Arc::new(Self::rerank_candidates_request(&request)))?;
+        self.rerank(&request, candidates)
+    }
+
+    /// Search and return details of the query processing in each segment.
+    /// Profiles of re-ranked searches describe the selection of candidates
+    pub fn search_explain(&self, request: Arc<SearchRequest>) -> CollectionResult<SearchExplainResult> {
+        if !Self::requires_rerank(&request) {
+            return self.searcher.search_explain(request);
+        }
+
+        Self::check_rerank_params(&request)?;
+        let candidates = self.searcher.search_explain(Arc::new(Self::rerank_candidates_request(&request)))?;
+        Ok(SearchExplainResult {
+            result: self.rerank(&request, candidates.result)?,
+            segments: candidates.segments,
+        })
+    }
+
+    pub fn search_batch(&self, request: Arc<SearchRequestBatch>) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        if !request.searches.iter().any(Self::requires_rerank) {
+            return self.searcher.search_batch(request);
+        }
+
+        for search in request.searches.iter() {
+            Self::check_rerank_params(search)?;
+        }
+
+        let candidates_request = SearchRequestBatch {
+            searches: request.searches.iter().map(Self::rerank_candidates_request).collect()
+        };
+        let candidates = self.searcher.search_batch(Arc::new(candidates_request))?;
+
+        request.searches.iter()
+            .zip(candidates)
+            .map(|(search, search_candidates)| self.rerank(search, search_candidates))
+            .collect()
+    }
+
+    /// Run all searches of the request and combine their results into a single ranked list
+    pub fn search_fusion(&self, request: Arc<FusionSearchRequest>) -> CollectionResult<Vec<ScoredPoint>> {
+        let batch = SearchRequestBatch { searches: request.searches.clone() };
+        let results = self.search_batch(Arc::new(batch))?;
+        Ok(fuse(results, request.fusion.unwrap_or_default(), request.top))
+    }
+
+    pub fn search_groups(&self, request: Arc<SearchGroupsRequest>) -> CollectionResult<SearchGroupsResult> {
+        self.searcher.search_groups(request)
     }
 
     pub fn retrieve(
@@ -100,6 +192,514 @@ impl Collection {
         return self.searcher.retrieve(points, with_payload, with_vector);
     }
 
+    /// Retrieve points ordered by numeric payload field in the same way as `scroll` does.
+    /// Points without the field and points before `start_from` value are skipped.
+    pub fn retrieve_ordered(
+        &self,
+        points: &Vec<PointIdType>,
+        order_by: &OrderBy,
+        with_payload: bool,
+        with_vector: bool,
+    ) -> CollectionResult<Vec<Record>> {
+        let direction = order_by.direction.unwrap_or_default();
+        let records = self.retrieve(points, true, with_vector)?
+            .into_iter()
+            .filter_map(|record| {
+                let value = record.payload
+                    .as_ref()
+                    .and_then(|payload| payload.get(&order_by.key))
+                    .and_then(|payload| direction.first_value(payload))?;
+                let is_before_start = order_by.start_from
+                    .map_or(false, |start_from| direction.compare(value, start_from) == Ordering::Less);
+                if is_before_start { None } else { Some((value, record)) }
+            })
+            .sorted_by(|(value_a, record_a), (value_b, record_b)| direction.compare(*value_a, *value_b).then(record_a.id.cmp(&record_b.id)))
+            .map(|(_, mut record)| {
+                if !with_payload {
+                    record.payload = None;
+                }
+                record
+            })
+            .collect();
+        Ok(records)
+    }
+
+    /// Paginate over all stored points in ascending order of their ids.
+    /// Only points which satisfy the given filter are returned.
+    pub fn scroll(&self, request: Arc<ScrollRequest>) -> CollectionResult<ScrollResult> {
+        let default_request = ScrollRequest::default();
+
+        let offset = request.offset.unwrap_or(default_request.offset.unwrap());
+        let limit = request.limit.unwrap_or(default_request.limit.unwrap());
+        let with_payload = request.with_payload.unwrap_or(default_request.with_payload.unwrap());
+        let with_vector = request.with_vector.unwrap_or(default_request.with_vector.unwrap());
+
+        if limit == 0 {
+            return Err(CollectionError::BadRequest {
+                description: "Limit of the scroll request should be greater than 0".to_string()
+            });
+        }
+
+        // Read one extra point to find out the offset of the next page
+        let limit_with_next = limit.saturating_add(1);
+
+        if let Some(order_by) = &request.order_by {
+            return self.scroll_ordered(order_by, offset, limit, request.filter.as_ref(), with_payload, with_vector);
+        }
+
+        let point_ids = {
+            let segments = self.segments.read();
+            segments
+                .iter()
+                .flat_map(|(_, segment)| segment.get().read().read_filtered(offset, limit_with_next, request.filter.as_ref()))
+                .sorted()
+                .dedup()
+                .take(limit_with_next)
+                .collect_vec()
+        };
+
+        let next_page_offset = if point_ids.len() < limit_with_next {
+            None
+        } else {
+            point_ids.last().cloned()
+        };
+
+        let page_ids = point_ids.into_iter().take(limit).collect_vec();
+
+        let mut points = self.retrieve(&page_ids, with_payload, with_vector)?;
+        points.sort_by_key(|point| point.id);
+
+        Ok(ScrollResult {
+            points,
+            next_page_offset,
+            next_page_start_from: None,
+        })
+    }
+
+    /// Select random points, each point which satisfies the filter has the same chance to be selected.
+    /// Number of points to take from each segment is randomly chosen according to the segment size.
+    pub fn sample(&self, request: Arc<SampleRequest>) -> CollectionResult<Vec<Record>> {
+        if request.limit > MAX_SAMPLE_LIMIT {
+            return Err(CollectionError::BadRequest {
+                description: format!("Number of sampled points should not exceed {}, got {}", MAX_SAMPLE_LIMIT, request.limit)
+            });
+        }
+
+        let filter = request.filter.as_ref();
+        let with_payload = request.with_payload.unwrap_or(true);
+        let with_vector = request.with_vector.unwrap_or(false);
+
+        let point_ids = {
+            let segments = self.segments.read();
+            let samples = segments
+                .iter()
+                .map(|(_, segment)| {
+                    let read_segment = segment.get();
+                    let read_segment = read_segment.read();
+                    (
+                        read_segment.estimate_points_count(filter).exp,
+                        read_segment.sample_filtered(request.limit, filter),
+                    )
+                })
+                .collect_vec();
+            merge_weighted_samples(samples, request.limit)
+        };
+
+        let mut records: HashMap<PointIdType, Record> = self.retrieve(&point_ids, with_payload, with_vector)?
+            .into_iter()
+            .map(|record| (record.id, record))
+            .collect();
+        Ok(point_ids.iter().filter_map(|point_id| records.remove(point_id)).collect())
+    }
+
+    fn scroll_ordered(
+        &self,
+        order_by: &OrderBy,
+        offset: PointIdType,
+        limit: usize,
+        filter: Option<&Filter>,
+        with_payload: bool,
+        with_vector: bool,
+    ) -> CollectionResult<ScrollResult> {
+        let direction = order_by.direction.unwrap_or_default();
+        let from = order_by.start_from.map(|value| (value, offset));
+        let limit_with_next = limit.saturating_add(1);
+
+        let ordered_points = {
+            let segments = self.segments.read();
+            segments
+                .iter()
+                .flat_map(|(_, segment)| segment.get().read().read_ordered(&order_by.key, direction, from, limit_with_next, filter))
+                .sorted_by(|(value_a, id_a), (value_b, id_b)| direction.compare(*value_a, *value_b).then(id_a.cmp(id_b)))
+                .unique_by(|(_, point_id)| *point_id)
+                .take(limit_with_next)
+                .collect_vec()
+        };
+
+        let (next_page_start_from, next_page_offset) = if ordered_points.len() < limit_with_next {
+            (None, None)
+        } else {
+            let (value, point_id) = ordered_points[limit];
+            (Some(value), Some(point_id))
+        };
+
+        let page_ids = ordered_points.iter().take(limit).map(|(_, point_id)| *point_id).collect_vec();
+        let mut records: HashMap<PointIdType, Record> = self.retrieve(&page_ids, with_payload, with_vector)?
+            .into_iter()
+            .map(|record| (record.id, record))
+            .collect();
+        let points = page_ids.iter().filter_map(|point_id| records.remove(point_id)).collect();
+
+        Ok(ScrollResult {
+            points,
+            next_page_offset,
+            next_page_start_from,
+        })
+    }
+
+    /// Find pairs of similar points and pass each of them to `on_pair`.
+    /// Pairs within a segment are verified among its index neighbours,
+    /// pairs from different segments - among search results of each point.
+    /// Pairs are collected segment by segment, and segment locks are released before `on_pair` is called.
+    /// Search is stopped if `on_pair` returns `false` or `limit` pairs are found, limit is capped by `MAX_SIMILAR_PAIRS`.
+    pub fn similar_pairs<F>(&self, request: Arc<SimilarPairsRequest>, mut on_pair: F) -> CollectionResult<()>
+        where F: FnMut(SimilarPair) -> bool
+    {
+        let filter = request.filter.as_ref();
+        let limit = request.limit.unwrap_or(MAX_SIMILAR_PAIRS).min(MAX_SIMILAR_PAIRS);
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let segments = self.segments.read()
+            .iter()
+            .map(|(_idx, segment)| segment.get())
+            .collect_vec();
+
+        // Same pair might be found from both points, if they are duplicated in several segments
+        let mut found_pairs: HashSet<(PointIdType, PointIdType)> = HashSet::new();
+
+        for (position, segment) in segments.iter().enumerate() {
+            let mut buffer = vec![];
+            for pair in segment.read().similar_pairs(request.threshold, filter, limit - found_pairs.len())? {
+                if found_pairs.insert((pair.a, pair.b)) {
+                    buffer.push(pair);
+                }
+            }
+            if !buffer.into_iter().all(&mut on_pair) || found_pairs.len() >= limit {
+                return Ok(());
+            }
+
+            for other_segment in &segments[position + 1..] {
+                let mut buffer = vec![];
+                cross_segment_pairs(&*segment.read(), filter, &*other_segment.read(), filter, request.threshold, &mut |pair| {
+                    if found_pairs.insert((pair.a, pair.b)) {
+                        buffer.push(pair);
+                    }
+                    found_pairs.len() < limit
+                })?;
+                if !buffer.into_iter().all(&mut on_pair) || found_pairs.len() >= limit {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sparse nearest neighbours matrix of randomly sampled points.
+    /// Neighbours are searched only among the sampled points.
+    pub fn distance_matrix(&self, request: Arc<DistanceMatrixRequest>) -> CollectionResult<DistanceMatrixResult> {
+        let limit = request.limit.unwrap_or(DEFAULT_DISTANCE_MATRIX_LIMIT);
+
+        if request.sample > MAX_DISTANCE_MATRIX_SAMPLE {
+            return Err(CollectionError::BadRequest {
+                description: format!("Sample size should not exceed {}, got {}", MAX_DISTANCE_MATRIX_SAMPLE, request.sample)
+            });
+        }
+
+        let point_ids = self.filtered_point_ids(request.filter.as_ref());
+        let sampled_ids = point_ids
+            .choose_multiple(&mut rand::thread_rng(), request.sample)
+            .cloned()
+            .collect_vec();
+
+        // Points, deleted after sampling, are excluded from the sample
+        let sample = self.fetch_vectors(&sampled_ids)?
+            .into_iter()
+            .sorted_by_key(|(point_id, _vector)| *point_id)
+            .collect_vec();
+        let sample_ids = sample.iter().map(|(point_id, _vector)| *point_id).collect_vec();
+
+        let sample_filter = Filter {
+            should: None,
+            must: Some(vec![Condition::HasId(sample_ids.iter().cloned().collect::<HashSet<_>>().into())]),
+            must_not: None,
+        };
+
+        let mut offsets = vec![0];
+        let mut ids = vec![];
+        let mut scores = vec![];
+        for chunk in sample.chunks(DISTANCE_MATRIX_SEARCH_BATCH) {
+            let batch = SearchRequestBatch {
+                searches: chunk.iter().map(|(_point_id, vector)| SearchRequest {
+                    vector: RecommendExample::Vector(vector.clone()),
+                    filter: Some(sample_filter.clone()),
+                    params: None,
+                    // Point itself is also found by its vector
+                    top: limit.saturating_add(1),
+                    diversity: None,
+                    score_formula: None,
+                    explain: None,
+                }).collect()
+            };
+            for ((point_id, _vector), result) in chunk.iter().zip(self.searcher.search_batch(Arc::new(batch))?) {
+                for scored in result.into_iter().filter(|scored| scored.id != *point_id).take(limit) {
+                    ids.push(scored.id);
+                    scores.push(scored.score);
+                }
+                offsets.push(ids.len());
+            }
+        }
+
+        Ok(DistanceMatrixResult { sample_ids, offsets, ids, scores })
+    }
+
+    /// Find centroids of the points vectors with mini-batch k-means
+    pub fn cluster(&self, request: Arc<ClusterRequest>) -> CollectionResult<ClusterResult> {
+        let batch_size = request.batch_size.unwrap_or(DEFAULT_CLUSTER_BATCH_SIZE);
+        let iterations = request.iterations.unwrap_or(DEFAULT_CLUSTER_ITERATIONS);
+
+        if request.clusters == 0 {
+            return Err(CollectionError::BadRequest {
+                description: "Number of clusters should be positive".to_string()
+            });
+        }
+        if batch_size == 0 || batch_size > MAX_CLUSTER_BATCH_SIZE {
+            return Err(CollectionError::BadRequest {
+                description: format!("Batch size should be in range [1, {}], got {}", MAX_CLUSTER_BATCH_SIZE, batch_size)
+            });
+        }
+        if iterations > MAX_CLUSTER_ITERATIONS {
+            return Err(CollectionError::BadRequest {
+                description: format!("Number of iterations should not exceed {}, got {}", MAX_CLUSTER_ITERATIONS, iterations)
+            });
+        }
+
+        let point_ids = self.filtered_point_ids(request.filter.as_ref());
+
+        if point_ids.len() < request.clusters {
+            return Err(CollectionError::BadRequest {
+                description: format!("Not enough points for {} clusters: {}", request.clusters, point_ids.len())
+            });
+        }
+
+        let mut rng = rand::thread_rng();
+        let metric = mertic_object(&self.config.read().params.distance);
+
+        let initial_ids = point_ids.choose_multiple(&mut rng, request.clusters).cloned().collect_vec();
+        let initial_centroids = self.fetch_vectors(&initial_ids)?.into_iter().map(|(_point_id, vector)| vector).collect_vec();
+        // Points, deleted after listing, are skipped
+        if initial_centroids.len() < request.clusters {
+            return Err(CollectionError::BadRequest {
+                description: format!("Not enough points for {} clusters: {}", request.clusters, initial_centroids.len())
+            });
+        }
+        let mut kmeans = MiniBatchKMeans::new(metric, initial_centroids);
+
+        for _ in 0..iterations {
+            let batch_ids = point_ids.choose_multiple(&mut rng, batch_size).cloned().collect_vec();
+            let batch = self.fetch_vectors(&batch_ids)?.into_iter().map(|(_point_id, vector)| vector).collect_vec();
+            kmeans.update(&batch);
+        }
+
+        let sizes = match &request.payload_key {
+            None => None,
+            Some(payload_key) => Some(self.assign_clusters(&kmeans, request.filter.as_ref(), payload_key)?),
+        };
+
+        Ok(ClusterResult {
+            centroids: kmeans.centroids().to_vec(),
+            sizes,
+        })
+    }
+
+    /// Sorted ids of all points, which satisfy the filter
+    fn filtered_point_ids(&self, filter: Option<&Filter>) -> Vec<PointIdType> {
+        let segments = self.segments.read();
+        let mut point_ids: HashSet<PointIdType> = Default::default();
+        for (_idx, segment) in segments.iter() {
+            point_ids.extend(segment.get().read().read_filtered(0.into(), usize::MAX, filter));
+        }
+        point_ids.into_iter().sorted().collect_vec()
+    }
+
+    /// Read vectors of the points from vector storages of the segments.
+    /// Each point is returned once, in order of the requested ids, using its latest version. Missing points are skipped
+    fn fetch_vectors(&self, point_ids: &[PointIdType]) -> CollectionResult<Vec<(PointIdType, Vec<VectorElementType>)>> {
+        let mut point_version: HashMap<PointIdType, SeqNumberType> = Default::default();
+        let mut point_vectors: HashMap<PointIdType, Vec<VectorElementType>> = Default::default();
+
+        self.segments.read().read_points(&point_ids.to_vec(), |id, segment| {
+            // If this point was not found yet or this segment have later version
+            if !point_version.contains_key(&id) || point_version[&id] < segment.version() {
+                point_vectors.insert(id, segment.vector(id)?);
+                point_version.insert(id, segment.version());
+            }
+            Ok(true)
+        })?;
+
+        Ok(point_ids
+            .iter()
+            .filter_map(|point_id| point_vectors.remove(point_id).map(|vector| (*point_id, vector)))
+            .collect())
+    }
+
+    /// Write index of the nearest centroid into payload of each point. Returns size of each cluster
+    fn assign_clusters(&self, kmeans: &MiniBatchKMeans, filter: Option<&Filter>, payload_key: &PayloadKeyType) -> CollectionResult<Vec<usize>> {
+        // Point might be duplicated in several segments, so only its latest version is assigned
+        let mut point_clusters: HashMap<PointIdType, (SeqNumberType, usize)> = HashMap::new();
+        for (_idx, segment) in self.segments.read().iter() {
+            let segment = segment.get();
+            let read_segment = segment.read();
+            let version = read_segment.version();
+            read_segment.read_vectors(filter, &mut |point_id, vector| {
+                let is_newer = point_clusters
+                    .get(&point_id)
+                    .map_or(true, |(point_version, _cluster)| *point_version < version);
+                if is_newer {
+                    point_clusters.insert(point_id, (version, kmeans.nearest(&vector)));
+                }
+                true
+            });
+        }
+
+        let mut cluster_points: Vec<Vec<PointIdType>> = vec![vec![]; kmeans.len()];
+        for (point_id, (_version, cluster)) in point_clusters {
+            cluster_points[cluster].push(point_id);
+        }
+
+        let sizes = cluster_points.iter().map(|points| points.len()).collect();
+
+        for (cluster, points) in cluster_points.into_iter().enumerate() {
+            if points.is_empty() {
+                continue;
+            }
+            let mut payload: HashMap<PayloadKeyType, PayloadInterface> = HashMap::new();
+            payload.insert(
+                payload_key.clone(),
+                PayloadInterface::IntShortcut(PayloadVariant::Value(cluster as i64)),
+            );
+            self.update(
+                CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload { payload, points }),
+                true,
+            )?;
+        }
+
+        Ok(sizes)
+    }
+
+    /// Count points which satisfy the given filter.
+    /// Exact count deduplicates point ids across segments, approximate count sums cardinality estimations of the segments.
+    pub fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
+        let exact = request.exact.unwrap_or(true);
+        let segments = self.segments.read();
+
+        let result = if exact {
+            let mut point_ids: HashSet<PointIdType> = Default::default();
+            for (_idx, segment) in segments.iter() {
+                let segment_points = segment.get().read().read_filtered(0.into(), usize::MAX, request.filter.as_ref());
+                point_ids.extend(segment_points);
+            }
+            CountResult {
+                count: point_ids.len(),
+                min: None,
+                max: None,
+            }
+        } else {
+            let mut count = 0;
+            let mut min = 0;
+            let mut max = 0;
+            for (_idx, segment) in segments.iter() {
+                let estimation = segment.get().read().estimate_points_count(request.filter.as_ref());
+                count += estimation.exp;
+                min += estimation.min;
+                max += estimation.max;
+            }
+            CountResult {
+                count,
+                min: Some(min),
+                max: Some(max),
+            }
+        };
+
+        Ok(result)
+    }
+
+    pub fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResult> {
+        let limit = request.limit.unwrap_or(10);
+        let segments = self.segments.read();
+
+        let mut counts: HashMap<FacetValue, usize> = Default::default();
+        for (_idx, segment) in segments.iter() {
+            let segment_counts = segment.get().read().facet_counts(&request.key, request.filter.as_ref());
+            for (value, count) in segment_counts {
+                *counts.entry(value).or_insert(0) += count;
+            }
+        }
+
+        let hits = counts
+            .into_iter()
+            .sorted_by(|(value_a, count_a), (value_b, count_b)| count_b.cmp(count_a).then(value_a.cmp(value_b)))
+            .take(limit)
+            .map(|(value, count)| FacetHit { value, count })
+            .collect();
+
+        Ok(FacetResult { hits })
+    }
+
+    pub fn histogram(&self, request: Arc<HistogramRequest>) -> CollectionResult<HistogramResult> {
+        let buckets = request.buckets.unwrap_or(10);
+        if buckets == 0 {
+            return Err(CollectionError::BadRequest {
+                description: "Number of buckets should be positive".to_string()
+            });
+        }
+
+        let segments = self.segments.read();
+
+        // Buckets should be the same for all segments, so global range is required first
+        let range = segments.iter()
+            .filter_map(|(_idx, segment)| segment.get().read().numeric_range(&request.key, request.filter.as_ref()))
+            .fold1(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)));
+
+        let (min, max) = match range {
+            None => return Ok(HistogramResult { min: None, max: None, buckets: vec![] }),
+            Some(range) => range,
+        };
+
+        let mut counts = vec![0; buckets];
+        for (_idx, segment) in segments.iter() {
+            let segment_counts = segment.get().read().numeric_histogram(&request.key, request.filter.as_ref(), min, max, buckets);
+            for (count, segment_count) in counts.iter_mut().zip(segment_counts) {
+                *count += segment_count;
+            }
+        }
+
+        let bucket_width = (max - min) / buckets as FloatPayloadType;
+        let histogram_buckets = counts
+            .into_iter()
+            .enumerate()
+            .map(|(bucket, count)| HistogramBucket {
+                from: min + bucket_width * bucket as FloatPayloadType,
+                to: if bucket + 1 == buckets { max } else { min + bucket_width * (bucket + 1) as FloatPayloadType },
+                count,
+            })
+            .collect();
+
+        Ok(HistogramResult { min: Some(min), max: Some(max), buckets: histogram_buckets })
+    }
+
     pub fn stop(&self) -> CollectionResult<()> {
         self.update_sender.send(UpdateSignal::Stop)?;
         Ok(())
@@ -110,6 +710,158 @@ impl Collection {
         Ok(())
     }
 
+    fn requires_rerank(search: &SearchRequest) -> bool {
+        search.diversity.is_some() || search.score_formula.is_some()
+    }
+
+    fn check_rerank_params(search: &SearchRequest) -> CollectionResult<()> {
+        if let Some(diversity) = search.diversity {
+            if !(0.0..=1.0).contains(&diversity) {
+                return Err(CollectionError::BadRequest {
+                    description: format!("Diversity should be in range [0, 1], got {}", diversity)
+                });
+            }
+        }
+        if let Some(formula) = &search.score_formula {
+            if let Some(oversampling) = formula.oversampling {
+                if oversampling == 0 || oversampling > MAX_FORMULA_OVERSAMPLING {
+                    return Err(CollectionError::BadRequest {
+                        description: format!("Formula oversampling should be in range [1, {}], got {}", MAX_FORMULA_OVERSAMPLING, oversampling)
+                    });
+                }
+            }
+            let has_invalid_scale = formula.terms
+                .iter()
+                .filter_map(|term| term.expression.scale())
+                .any(|scale| scale.partial_cmp(&0.0) != Some(Ordering::Greater));
+            if has_invalid_scale {
+                return Err(CollectionError::BadRequest {
+                    description: "Decay scale should be positive".to_string()
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy of the search request, which fetches enough candidates for re-scoring and diversity re-ranking
+    fn rerank_candidates_request(search: &SearchRequest) -> SearchRequest {
+        let formula_factor = search.score_formula
+            .as_ref()
+            .map(|formula| formula.oversampling.unwrap_or(DEFAULT_FORMULA_OVERSAMPLING))
+            .unwrap_or(1);
+        let diversity_factor = if search.diversity.is_some() { DIVERSITY_CANDIDATES_FACTOR } else { 1 };
+
+        SearchRequest {
+            vector: search.vector.clone(),
+            filter: search.filter.clone(),
+            params: search.params,
+            top: search.top
+                .saturating_mul(formula_factor)
+                .saturating_mul(diversity_factor)
+                .min(MAX_RERANK_CANDIDATES.max(search.top)),
+            diversity: None,
+            score_formula: None,
+            explain: None,
+        }
+    }
+
+    /// Apply score formula and diversity re-ranking to the search candidates
+    fn rerank(&self, search: &SearchRequest, candidates: Vec<ScoredPoint>) -> CollectionResult<Vec<ScoredPoint>> {
+        let candidates = match &search.score_formula {
+            None => candidates,
+            Some(formula) => {
+                // Diversity re-ranking requires all re-scored candidates
+                let top = if search.diversity.is_some() { candidates.len() } else { search.top };
+                self.rescore(candidates, formula, top)?
+            }
+        };
+
+        match search.diversity {
+            None => Ok(candidates),
+            Some(diversity) => self.rerank_diverse(candidates, diversity, search.top),
+        }
+    }
+
+    /// Fetch payloads of the candidates and select `top` of them by the formula score
+    fn rescore(&self, candidates: Vec<ScoredPoint>, formula: &ScoreFormula, top: usize) -> CollectionResult<Vec<ScoredPoint>> {
+        let ids = candidates.iter().map(|candidate| candidate.id).collect_vec();
+        let payloads: HashMap<PointIdType, TheMap<PayloadKeyType, PayloadType>> = self.retrieve(&ids, true, false)?
+            .into_iter()
+            .filter_map(|record| {
+                let id = record.id;
+                record.payload.map(|payload| (id, payload))
+            })
+            .collect();
+
+        let empty_payload = TheMap::new();
+        let rescored = candidates
+            .into_iter()
+            .map(|candidate| ScoredPoint {
+                id: candidate.id,
+                score: formula.score(candidate.score, payloads.get(&candidate.id).unwrap_or(&empty_payload)),
+            });
+
+        Ok(peek_top_scores_iterable(rescored, top))
+    }
+
+    /// Fetch vectors of the candidates and select `top` of them with maximal marginal relevance
+    fn rerank_diverse(&self, candidates: Vec<ScoredPoint>, diversity: f32, top: usize) -> CollectionResult<Vec<ScoredPoint>> {
+        let ids = candidates.iter().map(|candidate| candidate.id).collect_vec();
+        let vectors: HashMap<PointIdType, Vec<VectorElementType>> = self.retrieve(&ids, false, true)?
+            .into_iter()
+            .filter_map(|record| {
+                let id = record.id;
+                record.vector.map(|vector| (id, vector))
+            })
+            .collect();
+
+        let metric = mertic_object(&self.config.read().params.distance);
+
+        Ok(Self::maximal_marginal_relevance(candidates, &vectors, metric.as_ref(), diversity, top))
+    }
+
+    /// Greedily select points, which are relevant to the query, but not similar to already selected points.
+    /// Each step selects candidate with max `(1 - diversity) * score - diversity * max_similarity_to_selected`.
+    fn maximal_marginal_relevance(
+        candidates: Vec<ScoredPoint>,
+        vectors: &HashMap<PointIdType, Vec<VectorElementType>>,
+        metric: &dyn Metric,
+        diversity: f32,
+        top: usize,
+    ) -> Vec<ScoredPoint> {
+        // Candidates in order of relevance with their max similarity to the selected points
+        let mut remaining = candidates
+            .into_iter()
+            .filter(|candidate| vectors.contains_key(&candidate.id))
+            .map(|candidate| (candidate, ScoreType::NEG_INFINITY))
+            .collect_vec();
+
+        let mut selected: Vec<ScoredPoint> = Vec::with_capacity(top);
+
+        while selected.len() < top && !remaining.is_empty() {
+            let mut best_position = 0;
+            let mut best_score = ScoreType::NEG_INFINITY;
+            for (position, (candidate, max_similarity)) in remaining.iter().enumerate() {
+                let penalty = if selected.is_empty() { 0.0 } else { *max_similarity };
+                let mmr_score = (1.0 - diversity) * candidate.score - diversity * penalty;
+                if mmr_score > best_score {
+                    best_position = position;
+                    best_score = mmr_score;
+                }
+            }
+
+            let (point, _) = remaining.remove(best_position);
+            let point_vector = &vectors[&point.id];
+            for (candidate, max_similarity) in remaining.iter_mut() {
+                let similarity = metric.similarity(point_vector, &vectors[&candidate.id]);
+                *max_similarity = max_similarity.max(similarity);
+            }
+            selected.push(point);
+        }
+
+        selected
+    }
+
     fn avg_vectors<'a>(vectors: impl Iterator<Item=&'a Vec<VectorElementType>>) -> Vec<VectorElementType> {
         let mut count: usize = 0;
         let mut avg_vector: Vec<VectorElementType> = vec![];
@@ -131,68 +883,180 @@ impl Collection {
         avg_vector
     }
 
-    pub fn recommend(&self, request: Arc<RecommendRequest>) -> CollectionResult<Vec<ScoredPoint>> {
-        if request.positive.is_empty() {
-            return Err(CollectionError::BadRequest {
-                description: format!("At least one positive vector ID required")
-            });
-        }
+    /// Ids of the examples, which refer to existing points
+    fn example_point_ids<'a>(examples: impl Iterator<Item=&'a RecommendExample>) -> Vec<PointIdType> {
+        examples
+            .filter_map(|example| example.point_id())
+            .collect_vec()
+    }
 
-        let reference_vectors_ids = request.positive
-            .iter()
-            .chain(request.negative.iter())
-            .cloned()
-            .collect_vec();
+    /// Check that raw vectors of the examples have the same dimension as vectors of the collection
+    fn check_example_vectors<'a>(&self, examples: impl Iterator<Item=&'a RecommendExample>) -> CollectionResult<()> {
+        let vector_size = self.config.read().params.vector_size;
+        for example in examples {
+            if let RecommendExample::Vector(vector) = example {
+                if vector.len() != vector_size {
+                    return Err(CollectionError::BadRequest {
+                        description: format!("Wrong dimension of example vector: expected {}, got {}", vector_size, vector.len())
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 
-        let vectors = self.retrieve(&reference_vectors_ids, false, true)?;
+    /// Retrieve vectors of the example points from `lookup_collection`
+    fn fetch_example_vectors(lookup_collection: &Collection, point_ids: &Vec<PointIdType>) -> CollectionResult<HashMap<PointIdType, Vec<VectorElementType>>> {
+        let vectors = lookup_collection.retrieve(point_ids, false, true)?;
         let vectors_map: HashMap<PointIdType, Vec<VectorElementType>> = vectors
             .into_iter()
             .map(|rec| (rec.id, rec.vector.unwrap()))
             .collect();
 
-        for point_id in reference_vectors_ids.iter().cloned() {
+        for point_id in point_ids.iter().cloned() {
             if !vectors_map.contains_key(&point_id) {
                 return Err(CollectionError::NotFound {
                     missed_point_id: point_id
                 });
             }
         }
+        Ok(vectors_map)
+    }
 
-        let avg_positive = Collection::avg_vectors(request.positive
-            .iter()
-            .map(|vid| vectors_map.get(vid).unwrap()));
+    fn example_vector(example: &RecommendExample, vectors_map: &HashMap<PointIdType, Vec<VectorElementType>>) -> Vec<VectorElementType> {
+        match example {
+            RecommendExample::PointId(point_id) => vectors_map[point_id].clone(),
+            RecommendExample::Vector(vector) => vector.clone(),
+        }
+    }
 
-        let search_vector = if request.negative.is_empty() {
-            avg_positive
+    /// Combine user filter with condition, which excludes example points from the result
+    fn exclude_examples_filter(filter: Option<Filter>, exclude_ids: &[PointIdType]) -> Filter {
+        Filter {
+            should: None,
+            must: filter.map(|filter| vec![Condition::Filter(filter)]),
+            must_not: if exclude_ids.is_empty() {
+                None
+            } else {
+                Some(vec![Condition::HasId(HasIdCondition { has_id: exclude_ids.iter().cloned().collect() })])
+            },
+        }
+    }
+
+    pub fn recommend(&self, request: Arc<RecommendRequest>) -> CollectionResult<Vec<ScoredPoint>> {
+        self.recommend_by(request, self)
+    }
+
+    /// Same as `recommend`, but ids of the examples are resolved in `lookup_collection`.
+    /// Examples are excluded from the result only if they are looked up in this collection.
+    pub fn recommend_by(&self, request: Arc<RecommendRequest>, lookup_collection: &Collection) -> CollectionResult<Vec<ScoredPoint>> {
+        if request.positive.is_empty() {
+            return Err(CollectionError::BadRequest {
+                description: "At least one positive example required".to_string()
+            });
+        }
+
+        self.check_example_vectors(request.positive.iter().chain(request.negative.iter()))?;
+
+        let reference_vectors_ids = Self::example_point_ids(request.positive.iter().chain(request.negative.iter()));
+        let vectors_map = Self::fetch_example_vectors(lookup_collection, &reference_vectors_ids)?;
+
+        let positive_vectors = request.positive.iter()
+            .map(|example| Self::example_vector(example, &vectors_map))
+            .collect_vec();
+        let negative_vectors = request.negative.iter()
+            .map(|example| Self::example_vector(example, &vectors_map))
+            .collect_vec();
+
+        let search_filter = if std::ptr::eq(self, lookup_collection) {
+            Self::exclude_examples_filter(request.filter.clone(), &reference_vectors_ids)
         } else {
-            let avg_negative = Collection::avg_vectors(request.negative
-                .iter()
-                .map(|vid| vectors_map.get(vid).unwrap()));
+            Self::exclude_examples_filter(request.filter.clone(), &[])
+        };
 
-            avg_positive
-                .iter()
-                .cloned()
-                .zip(avg_negative.iter().cloned())
-                .map(|(pos, neg)| pos + pos - neg)
-                .collect()
-        };
-
-
-        let search_request = SearchRequest {
-            vector: search_vector,
-            filter: Some(Filter {
-                should: None,
-                must: match request.filter.clone() {
-                    None => None,
-                    Some(filter) => Some(vec![Condition::Filter(filter)])
-                },
-                must_not: Some(vec![Condition::HasId(HasIdCondition { has_id: reference_vectors_ids.iter().cloned().collect() })]),
-            }),
-            params: request.params.clone(),
+        match request.strategy.unwrap_or_default() {
+            RecommendStrategy::AverageVector => {
+                let avg_positive = Collection::avg_vectors(positive_vectors.iter());
+
+                let search_vector = if negative_vectors.is_empty() {
+                    avg_positive
+                } else {
+                    let avg_negative = Collection::avg_vectors(negative_vectors.iter());
+
+                    avg_positive
+                        .iter()
+                        .cloned()
+                        .zip(avg_negative.iter().cloned())
+                        .map(|(pos, neg)| pos + pos - neg)
+                        .collect()
+                };
+
+                let search_request = SearchRequest {
+                    vector: search_vector.into(),
+                    filter: Some(search_filter),
+                    params: request.params,
+                    top: request.top,
+                    diversity: None,
+                    score_formula: None,
+                    explain: None,
+                };
+
+                self.search(Arc::new(search_request))
+            }
+            RecommendStrategy::BestScore => {
+                let query = ExampleQuery::BestScore {
+                    positive: positive_vectors,
+                    negative: negative_vectors,
+                };
+
+                let search_request = ExampleSearchRequest {
+                    query,
+                    filter: Some(search_filter),
+                    params: request.params,
+                    top: request.top,
+                };
+
+                self.searcher.search_examples(Arc::new(search_request))
+            }
+        }
+    }
+
+    pub fn discover(&self, request: Arc<DiscoverRequest>) -> CollectionResult<Vec<ScoredPoint>> {
+        self.discover_by(request, self)
+    }
+
+    /// Same as `discover`, but ids of the examples are resolved in `lookup_collection`.
+    pub fn discover_by(&self, request: Arc<DiscoverRequest>, lookup_collection: &Collection) -> CollectionResult<Vec<ScoredPoint>> {
+        let examples = std::iter::once(&request.target)
+            .chain(request.context.iter().flat_map(|pair| vec![&pair.positive, &pair.negative]));
+
+        let reference_vectors_ids = Self::example_point_ids(examples);
+        let vectors_map = Self::fetch_example_vectors(lookup_collection, &reference_vectors_ids)?;
+
+        let query = ExampleQuery::Discovery {
+            target: Self::example_vector(&request.target, &vectors_map),
+            context: request.context.iter()
+                .map(|pair| (
+                    Self::example_vector(&pair.positive, &vectors_map),
+                    Self::example_vector(&pair.negative, &vectors_map),
+                ))
+                .collect(),
+        };
+
+        let search_filter = if std::ptr::eq(self, lookup_collection) {
+            Self::exclude_examples_filter(request.filter.clone(), &reference_vectors_ids)
+        } else {
+            Self::exclude_examples_filter(request.filter.clone(), &[])
+        };
+
+        let search_request = ExampleSearchRequest {
+            query,
+            filter: Some(search_filter),
+            params: request.params,
             top: request.top,
         };
 
-        self.search(Arc::new(search_request))
+        self.searcher.search_examples(Arc::new(search_request))
     }
 
     /// Updates collection optimization params:
@@ -7,8 +7,8 @@ use parking_lot::{Mutex, RwLock};
 use tokio::runtime;
 use tokio::runtime::Runtime;
 
-use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
-use segment::types::HnswConfig;
+use segment::segment_constructor::segment_constructor::build_segment;
+use segment::types::{HnswConfig, SegmentConfig, Indexes, PayloadIndexType, StorageType};
 
 use crate::collection::Collection;
 use crate::collection_builder::optimizers_builder::build_optimizers;
@@ -98,11 +98,17 @@ pub fn build_collection(
 
     let mut segment_holder = SegmentHolder::new();
 
+    let segment_config = SegmentConfig {
+        vector_size: collection_params.vector_size,
+        distance: collection_params.distance,
+        index: Indexes::Plain {},
+        payload_index: Some(PayloadIndexType::Plain),
+        storage_type: StorageType::InMemory,
+        payload_storage_type: collection_params.payload_storage_type,
+    };
+
     for _sid in 0..optimizers_config.max_segment_number {
-        let segment = build_simple_segment(
-            segments_path.as_path(),
-            collection_params.vector_size,
-            collection_params.distance)?;
+        let segment = build_segment(segments_path.as_path(), &segment_config)?;
         segment_holder.add(segment);
     }
 
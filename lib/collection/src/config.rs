@@ -8,7 +8,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use wal::WalOptions;
 
-use segment::types::{Distance, HnswConfig};
+use segment::types::{Distance, HnswConfig, PayloadStorageType};
 
 use crate::collection_builder::optimizers_builder::OptimizersConfig;
 use crate::operations::types::{CollectionError, CollectionResult};
@@ -43,7 +43,10 @@ pub struct CollectionParams {
     /// Size of a vectors used
     pub vector_size: usize,
     /// Type of distance function used for measuring distance between vectors
-    pub distance: Distance
+    pub distance: Distance,
+    /// Type of payload storage used by segments of the collection
+    #[serde(default)]
+    pub payload_storage_type: PayloadStorageType,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -3,5 +3,8 @@ pub mod config;
 mod update_handler;
 pub mod operations;
 pub mod collection;
+mod clustering;
+mod sampling;
+mod similar_pairs;
 mod segment_manager;
 mod wal;
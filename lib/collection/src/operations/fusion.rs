@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use segment::spaces::tools::peek_top_scores_iterable;
+use segment::types::{PointIdType, ScoredPoint, ScoreType};
+
+/// Constant of reciprocal rank fusion, reduces influence of the top ranks
+const RRF_K: ScoreType = 60.0;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionMethod {
+    /// Reciprocal rank fusion: sum of `1 / (60 + rank)` over all result lists
+    Rrf,
+    /// Sum of scores, min-max normalized within each result list
+    ScoreSum,
+}
+
+impl Default for FusionMethod {
+    fn default() -> Self {
+        FusionMethod::Rrf
+    }
+}
+
+/// Combine multiple ranked result lists into a single list of `top` points
+pub fn fuse(results: Vec<Vec<ScoredPoint>>, method: FusionMethod, top: usize) -> Vec<ScoredPoint> {
+    let mut fused_scores: HashMap<PointIdType, ScoreType> = HashMap::new();
+
+    for result in results {
+        let min_score = result.iter().map(|point| point.score).fold(ScoreType::INFINITY, ScoreType::min);
+        let max_score = result.iter().map(|point| point.score).fold(ScoreType::NEG_INFINITY, ScoreType::max);
+
+        for (rank, point) in result.iter().enumerate() {
+            let score = match method {
+                FusionMethod::Rrf => 1.0 / (RRF_K + (rank + 1) as ScoreType),
+                FusionMethod::ScoreSum => if max_score > min_score {
+                    (point.score - min_score) / (max_score - min_score)
+                } else {
+                    1.0
+                },
+            };
+            *fused_scores.entry(point.id).or_insert(0.0) += score;
+        }
+    }
+
+    peek_top_scores_iterable(
+        fused_scores.into_iter().map(|(id, score)| ScoredPoint { id, score }),
+        top,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(ids: &[u64], scores: &[ScoreType]) -> Vec<ScoredPoint> {
+        ids.iter().zip(scores).map(|(id, score)| ScoredPoint { id: (*id).into(), score: *score }).collect()
+    }
+
+    #[test]
+    fn test_rrf() {
+        let results = vec![
+            ranked(&[1, 2, 3], &[0.9, 0.8, 0.7]),
+            ranked(&[2, 3, 4], &[10.0, 5.0, 1.0]),
+        ];
+
+        let fused = fuse(results, FusionMethod::Rrf, 3);
+
+        // Point 2 is ranked high in both lists, point 1 is present in only one of them
+        assert_eq!(fused[0].id, 2.into());
+        assert_eq!(fused[1].id, 3.into());
+        assert_eq!(fused[2].id, 1.into());
+    }
+
+    #[test]
+    fn test_score_sum() {
+        let results = vec![
+            ranked(&[1, 3, 2], &[0.9, 0.8, 0.7]),
+            ranked(&[3, 2, 4], &[10.0, 9.0, 0.0]),
+        ];
+
+        let fused = fuse(results, FusionMethod::ScoreSum, 2);
+
+        assert_eq!(fused[0].id, 3.into());
+        assert!((fused[0].score - 1.5).abs() < 1e-5);
+        assert_eq!(fused[1].id, 1.into());
+    }
+}
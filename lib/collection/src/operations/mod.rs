@@ -2,6 +2,8 @@ pub mod types;
 pub mod point_ops;
 pub mod payload_ops;
 pub mod config_diff;
+pub mod score_formula;
+pub mod fusion;
 
 use serde::{Deserialize, Serialize};
 use schemars::{JsonSchema};
@@ -29,16 +31,60 @@ pub enum CollectionUpdateOperations {
 mod tests {
     use super::*;
     use serde_json;
+    use segment::types::{PointIdType, Filter, Condition, FieldCondition, Match};
 
     #[test]
     fn test_deserialize() {
         let op = CollectionUpdateOperations::PayloadOperation(
             payload_ops::PayloadOps::ClearPayload {
-                points: vec![1, 2, 3],
+                points: vec![1.into(), 2.into(), 3.into()],
             }
         );
 
         let json = serde_json::to_string_pretty(&op).unwrap();
         println!("{}", json)
     }
+
+    #[test]
+    fn test_wal_serialization() {
+        let uuid_id: PointIdType = "4a2b6bd1-4e47-4c3f-8f8c-1d2c3b4a5e6f".parse().unwrap();
+        let op = CollectionUpdateOperations::PointOperation(
+            point_ops::PointOperations::DeletePoints {
+                ids: vec![42.into(), uuid_id],
+            }
+        );
+
+        // Same encoding as used by WAL
+        let bytes = rmp_serde::to_vec(&op).unwrap();
+        let restored: CollectionUpdateOperations = rmp_serde::from_read_ref(&bytes).unwrap();
+        match restored {
+            CollectionUpdateOperations::PointOperation(point_ops::PointOperations::DeletePoints { ids }) => {
+                assert_eq!(ids, vec![42.into(), uuid_id])
+            }
+            _ => assert!(false, "Wrong operation"),
+        }
+    }
+
+    #[test]
+    fn test_wal_filter_serialization() {
+        let filter = Filter::new_must(Condition::Field(FieldCondition {
+            key: "tenant".to_string(),
+            r#match: Some(Match { keyword: Some("acme".to_string()), integer: None }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        }));
+        let op = CollectionUpdateOperations::PointOperation(
+            point_ops::PointOperations::DeletePointsByFilter(filter.clone())
+        );
+
+        let bytes = rmp_serde::to_vec(&op).unwrap();
+        let restored: CollectionUpdateOperations = rmp_serde::from_read_ref(&bytes).unwrap();
+        match restored {
+            CollectionUpdateOperations::PointOperation(point_ops::PointOperations::DeletePointsByFilter(restored_filter)) => {
+                assert_eq!(restored_filter, filter)
+            }
+            _ => assert!(false, "Wrong operation"),
+        }
+    }
 }
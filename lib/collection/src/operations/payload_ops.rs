@@ -1,7 +1,7 @@
 use serde;
 use serde::{Deserialize, Serialize};
 use schemars::{JsonSchema};
-use segment::types::{PointIdType, PayloadKeyType, PayloadInterface};
+use segment::types::{PointIdType, PayloadKeyType, PayloadInterface, Filter, TheMap};
 use std::collections::HashMap;
 
 
@@ -25,7 +25,30 @@ pub enum PayloadOps {
     /// Drops all Payload values associated with given points.
     ClearPayload {
         points: Vec<PointIdType>,
-    }
+    },
+    /// Set payload value to all points which satisfy the filter
+    SetPayloadByFilter {
+        payload: HashMap<PayloadKeyType, PayloadInterface>,
+        filter: Filter,
+    },
+    /// Deletes specified payload values from all points which satisfy the filter
+    DeletePayloadByFilter {
+        keys: Vec<PayloadKeyType>,
+        filter: Filter,
+    },
+    /// Drops all Payload values of points which satisfy the filter
+    ClearPayloadByFilter(Filter),
+    /// Apply JSON merge patch (RFC 7396) to the payload of each point.
+    /// Nested objects are merged recursively, `null` value removes the key
+    UpdatePayload {
+        patch: TheMap<PayloadKeyType, serde_json::Value>,
+        /// Values to append to the existing values of the keys after the patch is applied.
+        /// Merge patch replaces arrays as a whole
+        #[serde(default)]
+        append: TheMap<PayloadKeyType, serde_json::Value>,
+        /// Applies patch to each point in this list
+        points: Vec<PointIdType>,
+    },
 }
 
 
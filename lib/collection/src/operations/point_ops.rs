@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use schemars::{JsonSchema};
-use segment::types::{PointIdType, PayloadKeyType, PayloadInterface};
+use segment::types::{PointIdType, PayloadKeyType, PayloadInterface, Filter};
 use crate::operations::types::VectorType;
 use std::collections::HashMap;
 
@@ -41,4 +41,6 @@ pub enum PointOperations {
     DeletePoints {
         ids: Vec<PointIdType>,
     },
+    /// Delete all points which satisfy the filter
+    DeletePointsByFilter(Filter),
 }
@@ -0,0 +1,163 @@
+use std::f64::consts::LN_2;
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use segment::payload_storage::condition_checker::geo_distance;
+use segment::types::{GeoPoint, PayloadKeyType, PayloadType, ScoreType, TheMap};
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayFunction {
+    /// Exponential decay: `0.5 ^ (distance / scale)`
+    Exp,
+    /// Gaussian decay: `0.5 ^ ((distance / scale) ^ 2)`
+    Gauss,
+}
+
+impl DecayFunction {
+    /// Decay value in range (0, 1]: 1 for zero distance, 0.5 for distance equal to `scale`
+    pub fn decay(&self, distance: f64, scale: f64) -> f64 {
+        let relative_distance = distance.abs() / scale;
+        match self {
+            DecayFunction::Exp => (-LN_2 * relative_distance).exp(),
+            DecayFunction::Gauss => (-LN_2 * relative_distance * relative_distance).exp(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreTermExpression {
+    /// Value of the numeric payload field
+    Field {
+        key: PayloadKeyType,
+    },
+    /// Decay by distance in meters between geo payload field and `origin`
+    GeoDecay {
+        key: PayloadKeyType,
+        origin: GeoPoint,
+        /// Distance in meters, at which decay is equal to 0.5
+        scale: f64,
+        function: DecayFunction,
+    },
+    /// Decay by difference between numeric payload field and `origin`.
+    /// Datetime fields should be stored as numeric timestamps.
+    NumericDecay {
+        key: PayloadKeyType,
+        origin: f64,
+        /// Difference, at which decay is equal to 0.5
+        scale: f64,
+        function: DecayFunction,
+    },
+}
+
+impl ScoreTermExpression {
+    pub fn scale(&self) -> Option<f64> {
+        match self {
+            ScoreTermExpression::Field { .. } => None,
+            ScoreTermExpression::GeoDecay { scale, .. } => Some(*scale),
+            ScoreTermExpression::NumericDecay { scale, .. } => Some(*scale),
+        }
+    }
+
+    /// Value of the expression for the point payload.
+    /// Points without the field get 0, for multi-value fields the largest value is used.
+    pub fn value(&self, payload: &TheMap<PayloadKeyType, PayloadType>) -> f64 {
+        let values = match self {
+            ScoreTermExpression::Field { key } => numeric_values(payload.get(key)),
+            ScoreTermExpression::GeoDecay { key, origin, scale, function } => match payload.get(key) {
+                Some(PayloadType::Geo(points)) => points
+                    .iter()
+                    .map(|point| function.decay(geo_distance(origin, point), *scale))
+                    .collect(),
+                _ => vec![],
+            },
+            ScoreTermExpression::NumericDecay { key, origin, scale, function } => numeric_values(payload.get(key))
+                .into_iter()
+                .map(|value| function.decay(value - origin, *scale))
+                .collect(),
+        };
+        values.into_iter().fold1(f64::max).unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ScoreTerm {
+    /// Multiplier of the expression value
+    pub weight: ScoreType,
+    pub expression: ScoreTermExpression,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Re-scoring formula: vector score plus sum of weighted terms
+pub struct ScoreFormula {
+    pub terms: Vec<ScoreTerm>,
+    /// Number of vector search candidates re-scored per requested result, at most 100. Default: 4
+    pub oversampling: Option<usize>,
+}
+
+impl ScoreFormula {
+    pub fn score(&self, score: ScoreType, payload: &TheMap<PayloadKeyType, PayloadType>) -> ScoreType {
+        self.terms
+            .iter()
+            .fold(score, |total, term| total + term.weight * term.expression.value(payload) as ScoreType)
+    }
+}
+
+fn numeric_values(payload: Option<&PayloadType>) -> Vec<f64> {
+    match payload {
+        Some(PayloadType::Integer(numbers)) => numbers.iter().map(|x| *x as f64).collect(),
+        Some(PayloadType::Float(numbers)) => numbers.clone(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay() {
+        assert_eq!(DecayFunction::Exp.decay(0.0, 10.0), 1.0);
+        assert!((DecayFunction::Exp.decay(10.0, 10.0) - 0.5).abs() < 1e-9);
+        assert!((DecayFunction::Gauss.decay(-10.0, 10.0) - 0.5).abs() < 1e-9);
+        assert!(DecayFunction::Gauss.decay(5.0, 10.0) > DecayFunction::Exp.decay(5.0, 10.0));
+    }
+
+    #[test]
+    fn test_formula_score() {
+        let mut payload: TheMap<PayloadKeyType, PayloadType> = TheMap::new();
+        payload.insert("rating".to_string(), PayloadType::Integer(vec![2, 4]));
+        payload.insert("price".to_string(), PayloadType::Float(vec![100.0]));
+
+        let formula = ScoreFormula {
+            terms: vec![
+                ScoreTerm {
+                    weight: 0.5,
+                    expression: ScoreTermExpression::Field { key: "rating".to_string() },
+                },
+                ScoreTerm {
+                    weight: 1.0,
+                    expression: ScoreTermExpression::NumericDecay {
+                        key: "price".to_string(),
+                        origin: 50.0,
+                        scale: 50.0,
+                        function: DecayFunction::Exp,
+                    },
+                },
+                ScoreTerm {
+                    weight: 10.0,
+                    expression: ScoreTermExpression::Field { key: "missing".to_string() },
+                },
+            ],
+            oversampling: None,
+        };
+
+        let score = formula.score(1.0, &payload);
+        assert!((score - 3.5).abs() < 1e-6, "{}", score);
+    }
+}
@@ -9,9 +9,11 @@ use tokio::task::JoinError;
 use std::result;
 
 use segment::entry::entry_point::OperationError;
-use segment::types::{Filter, PayloadKeyType, PayloadType, PointIdType, SearchParams, SeqNumberType, TheMap, VectorElementType};
+use segment::types::{Filter, PayloadKeyType, PayloadType, PointIdType, SearchParams, SeqNumberType, TheMap, VectorElementType, ScoredPoint, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, Direction, SegmentSearchProfile};
 
 use crate::config::CollectionConfig;
+use crate::operations::score_formula::ScoreFormula;
+use crate::operations::fusion::FusionMethod;
 use crate::wal::WalError;
 
 /// Type of vector in API
@@ -78,18 +80,92 @@ pub struct UpdateResult {
 }
 
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 /// Search request
 pub struct SearchRequest {
-    /// Look for vectors closest to this
-    pub vector: Vec<VectorElementType>,
+    /// Look for vectors closest to this: explicit vector or id of the stored point.
+    /// Stored point itself is excluded from the result
+    pub vector: RecommendExample,
     /// Look only for points which satisfies this conditions
     pub filter: Option<Filter>,
     /// Additional search params
     pub params: Option<SearchParams>,
     /// Max number of result to return
     pub top: usize,
+    /// Re-rank results with maximal marginal relevance to avoid near-duplicates.
+    /// Should be in range [0, 1]: 0 - only relevance, 1 - only diversity. Default: no re-ranking
+    pub diversity: Option<f32>,
+    /// Re-score results with payload-based formula. Default: vector score only
+    pub score_formula: Option<ScoreFormula>,
+    /// Return details of the query processing in each segment along with the result. Default: false
+    pub explain: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Multiple search requests, executed together
+pub struct SearchRequestBatch {
+    /// Search requests. Results are returned in the same order
+    pub searches: Vec<SearchRequest>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Multiple search requests, which results are fused into a single ranked list
+pub struct FusionSearchRequest {
+    /// Search requests to fuse. Each of them could use own filter and query vector
+    pub searches: Vec<SearchRequest>,
+    /// How to combine results of the searches. Default: rrf
+    pub fusion: Option<FusionMethod>,
+    /// Max number of result to return
+    pub top: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Search request, which groups found points by the value of payload field
+pub struct SearchGroupsRequest {
+    /// Look for vectors closest to this
+    pub vector: Vec<VectorElementType>,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Additional search params
+    pub params: Option<SearchParams>,
+    /// Payload field to group by. Only keyword and integer fields are supported.
+    /// Point with multiple values of the field could be included into multiple groups
+    pub group_by: PayloadKeyType,
+    /// Max number of points to return in each group
+    pub group_size: usize,
+    /// Max number of groups to return
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+/// Points, which share the same value of the group field
+pub struct PointGroup {
+    /// Value of the group field
+    pub id: FacetValue,
+    /// Best scored points of the group, ordered by score
+    pub hits: Vec<ScoredPoint>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Result of the grouped search
+pub struct SearchGroupsResult {
+    /// Groups ordered by the score of the best point in group
+    pub groups: Vec<PointGroup>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Search result with details of the query processing
+pub struct SearchExplainResult {
+    pub result: Vec<ScoredPoint>,
+    /// Profile of the search in each segment
+    pub segments: Vec<SegmentSearchProfile>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -97,15 +173,325 @@ pub struct SearchRequest {
 /// Search request
 pub struct RecommendRequest {
     /// Look for vectors closest to those
-    pub positive: Vec<PointIdType>,
+    pub positive: Vec<RecommendExample>,
     /// Try to avoid vectors like this
-    pub negative: Vec<PointIdType>,
+    pub negative: Vec<RecommendExample>,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Additional search params
+    pub params: Option<SearchParams>,
+    /// Max number of result to return
+    pub top: usize,
+    /// How to use positive and negative examples. Default: `average_vector`
+    pub strategy: Option<RecommendStrategy>,
+    /// Name of the collection to look up point ids of the examples in.
+    /// If not specified - ids are looked up in the current collection
+    pub lookup_from: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[serde(untagged)]
+/// Example for recommendation: id of the existing point or an explicit vector
+pub enum RecommendExample {
+    PointId(PointIdType),
+    Vector(Vec<VectorElementType>),
+}
+
+impl RecommendExample {
+    pub fn point_id(&self) -> Option<PointIdType> {
+        match self {
+            RecommendExample::PointId(point_id) => Some(*point_id),
+            RecommendExample::Vector(_) => None,
+        }
+    }
+}
+
+impl From<PointIdType> for RecommendExample {
+    fn from(point_id: PointIdType) -> Self {
+        RecommendExample::PointId(point_id)
+    }
+}
+
+impl From<Vec<VectorElementType>> for RecommendExample {
+    fn from(vector: Vec<VectorElementType>) -> Self {
+        RecommendExample::Vector(vector)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Strategy of combining recommendation examples into a search query
+pub enum RecommendStrategy {
+    /// Search for the average of positive vectors, shifted away from the average of negative ones
+    AverageVector,
+    /// Score each candidate against every example separately.
+    /// Best positive similarity wins unless some negative example is closer.
+    /// Works better for diverse positive examples, but requires more computations
+    BestScore,
+}
+
+impl Default for RecommendStrategy {
+    fn default() -> Self {
+        RecommendStrategy::AverageVector
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+/// Pair of examples, which defines preferred side of the space
+pub struct ContextExamplePair {
+    pub positive: RecommendExample,
+    pub negative: RecommendExample,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Discovery request - search for points, which are on the positive side of context pairs
+pub struct DiscoverRequest {
+    /// Look for vectors closest to this. Used to rank points within the same context zone
+    pub target: RecommendExample,
+    /// Points are ranked by the number of pairs, in which they are closer to positive example
+    pub context: Vec<ContextExamplePair>,
     /// Look only for points which satisfies this conditions
     pub filter: Option<Filter>,
     /// Additional search params
     pub params: Option<SearchParams>,
     /// Max number of result to return
     pub top: usize,
+    /// Name of the collection to look up point ids of the examples in.
+    /// If not specified - ids are looked up in the current collection
+    pub lookup_from: Option<String>,
+}
+
+#[derive(Debug)]
+/// Search request with the query, defined by multiple examples
+pub struct ExampleSearchRequest {
+    pub query: ExampleQuery,
+    pub filter: Option<Filter>,
+    pub params: Option<SearchParams>,
+    pub top: usize,
+}
+
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Scroll request - paginate over all points which matches given condition
+pub struct ScrollRequest {
+    /// Start ID to read points from. Default: 0
+    pub offset: Option<PointIdType>,
+    /// Page size. Default: 10
+    pub limit: Option<usize>,
+    /// Look only for points which satisfies this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Return point payload with the result. Default: true
+    pub with_payload: Option<bool>,
+    /// Return point vector with the result. Default: false
+    pub with_vector: Option<bool>,
+    /// Order points by numeric payload field instead of id. Default: order by id
+    pub order_by: Option<OrderBy>,
+}
+
+impl Default for ScrollRequest {
+    fn default() -> Self {
+        ScrollRequest {
+            offset: Some(0.into()),
+            limit: Some(10),
+            filter: None,
+            with_payload: Some(true),
+            with_vector: Some(false),
+            order_by: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+/// Order of scroll or retrieve results by numeric payload field. Scroll is fastest if the field is indexed.
+/// Points with the same value are ordered by id, points without the field are skipped.
+pub struct OrderBy {
+    /// Numeric payload field to order by
+    pub key: PayloadKeyType,
+    /// Default: asc
+    pub direction: Option<Direction>,
+    /// Start reading from this value including.
+    /// Among points with exactly this value, start from the `offset` id.
+    /// Default: from the first value in the direction
+    pub start_from: Option<FloatPayloadType>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Sample request - select uniformly random points which matches given condition
+pub struct SampleRequest {
+    /// Number of points to select. Max: 10000
+    pub limit: usize,
+    /// Look only for points which satisfies this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Return point payload with the result. Default: true
+    pub with_payload: Option<bool>,
+    /// Return point vector with the result. Default: false
+    pub with_vector: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Result of the points read request
+pub struct ScrollResult {
+    /// List of retrieved points
+    pub points: Vec<Record>,
+    /// Offset which should be used to retrieve a next page result
+    pub next_page_offset: Option<PointIdType>,
+    /// Value of `order_by.start_from`, which should be used to retrieve a next page result.
+    /// Present only for results ordered by payload field
+    pub next_page_start_from: Option<FloatPayloadType>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Similarity join request - find pairs of points with similar vectors, e.g. near-duplicates
+pub struct SimilarPairsRequest {
+    /// Min similarity of the points in pair
+    pub threshold: ScoreType,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Stop after this number of pairs is found. Default and max: 100000
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Sample points and find nearest neighbours of each sampled point among the sample
+pub struct DistanceMatrixRequest {
+    /// Number of points to sample, at most 10000
+    pub sample: usize,
+    /// Number of nearest neighbours of each sampled point. Default: 3
+    pub limit: Option<usize>,
+    /// Sample only points which satisfies this conditions
+    pub filter: Option<Filter>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Sparse matrix in compressed rows format.
+/// Neighbours of `sample_ids[i]` are `ids[offsets[i]..offsets[i + 1]]` with corresponding `scores`
+pub struct DistanceMatrixResult {
+    /// Ids of the sampled points in ascending order
+    pub sample_ids: Vec<PointIdType>,
+    /// Start position of each row in `ids` and `scores`, plus total number of neighbours
+    pub offsets: Vec<usize>,
+    /// Ids of the neighbours, ordered by decreasing score within each row
+    pub ids: Vec<PointIdType>,
+    pub scores: Vec<ScoreType>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Clustering request - split vectors of the collection into clusters with mini-batch k-means
+pub struct ClusterRequest {
+    /// Number of clusters
+    pub clusters: usize,
+    /// Cluster only points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Number of randomly selected points used on each iteration, at most 10000. Default: 100
+    pub batch_size: Option<usize>,
+    /// Number of iterations, at most 10000. Default: 100
+    pub iterations: Option<usize>,
+    /// If specified, cluster index of each point is written into this payload field
+    pub payload_key: Option<PayloadKeyType>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClusterResult {
+    /// Cluster centroids. Position of the centroid is the cluster index
+    pub centroids: Vec<Vec<VectorElementType>>,
+    /// Number of points in each cluster. Only present if clusters are written into payload
+    pub sizes: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Count Request
+/// Counts the number of points which satisfy the given filter.
+/// If filter is not provided, the count of all points in the collection will be returned.
+pub struct CountRequest {
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// If true, count exact number of points. If false, count approximate number of points faster.
+    /// Approximate count might be unreliable during the indexing process. Default: true
+    pub exact: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CountResult {
+    /// Number of points which satisfy the conditions. Expected number of points in approximate mode
+    pub count: usize,
+    /// Lower bound of the approximate count. Not present for exact count
+    pub min: Option<usize>,
+    /// Upper bound of the approximate count. Not present for exact count
+    pub max: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Facet request - count the most frequent values of keyword or integer payload field
+pub struct FacetRequest {
+    /// Payload field to count values of
+    pub key: PayloadKeyType,
+    /// Count values only of points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Max number of values to return. Default: 10
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct FacetHit {
+    pub value: FacetValue,
+    /// Number of points with this value
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FacetResult {
+    /// Most frequent values, ordered by number of points
+    pub hits: Vec<FacetHit>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+/// Histogram request - calculate distribution of numeric payload field values
+pub struct HistogramRequest {
+    /// Numeric payload field
+    pub key: PayloadKeyType,
+    /// Use values only of points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Number of equal-width buckets between min and max value. Default: 10
+    pub buckets: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HistogramBucket {
+    /// Lower bound of the bucket, inclusive
+    pub from: FloatPayloadType,
+    /// Upper bound of the bucket. Exclusive for all buckets except the last one
+    pub to: FloatPayloadType,
+    /// Number of values in the bucket
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HistogramResult {
+    /// Minimal value of the field. Not present if there are no values
+    pub min: Option<FloatPayloadType>,
+    /// Maximal value of the field. Not present if there are no values
+    pub max: Option<FloatPayloadType>,
+    pub buckets: Vec<HistogramBucket>,
 }
 
 
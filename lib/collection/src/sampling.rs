@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use segment::types::PointIdType;
+
+/// Combine random samples of several point sets into a single sample of `limit` distinct points.
+///
+/// Each sample is given with the number of points it was taken from.
+/// Next point is taken from a sample with probability proportional to the number of its not yet selected points,
+/// so each point of the union has the same chance to be selected.
+pub fn merge_weighted_samples(samples: Vec<(usize, Vec<PointIdType>)>, limit: usize) -> Vec<PointIdType> {
+    let mut rng = rand::thread_rng();
+    let mut samples = samples
+        .into_iter()
+        .map(|(weight, sample)| (weight.max(sample.len()), sample))
+        .collect::<Vec<_>>();
+
+    let mut selected: HashSet<PointIdType> = HashSet::new();
+    let mut result = vec![];
+    while result.len() < limit {
+        let total_weight: usize = samples
+            .iter()
+            .filter(|(_, sample)| !sample.is_empty())
+            .map(|(weight, _)| *weight)
+            .sum();
+        if total_weight == 0 {
+            break;
+        }
+
+        let mut position = rng.gen_range(0, total_weight);
+        for (weight, sample) in samples.iter_mut().filter(|(_, sample)| !sample.is_empty()) {
+            if position >= *weight {
+                position -= *weight;
+                continue;
+            }
+            let point_id = sample.pop().unwrap();
+            *weight -= 1;
+            if selected.insert(point_id) {
+                result.push(point_id);
+            }
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point_ids(ids: &[u64]) -> Vec<PointIdType> {
+        ids.iter().map(|id| (*id).into()).collect()
+    }
+
+    #[test]
+    fn test_merge_weighted_samples() {
+        let samples = vec![
+            (1000, point_ids(&[1, 2, 3])),
+            (2, point_ids(&[10, 11])),
+            (0, vec![]),
+        ];
+
+        let merged = merge_weighted_samples(samples, 4);
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged.iter().collect::<HashSet<_>>().len(), 4);
+
+        let samples = vec![(5, point_ids(&[1, 2])), (5, point_ids(&[2, 3]))];
+        let mut merged = merge_weighted_samples(samples, 10);
+        merged.sort();
+        assert_eq!(merged, point_ids(&[1, 2, 3]));
+    }
+}
@@ -1,7 +1,7 @@
 use segment::segment::Segment;
 use segment::entry::entry_point::SegmentEntry;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
-use segment::types::{Distance, PayloadType, SeqNumberType};
+use segment::types::{Distance, PayloadType, PointIdType, SeqNumberType};
 use crate::segment_manager::holders::segment_holder::SegmentHolder;
 use crate::segment_manager::simple_segment_searcher::SimpleSegmentSearcher;
 use tokio::runtime::Runtime;
@@ -28,7 +28,7 @@ pub fn random_segment(
     let payload_key = "number".to_owned();
     for _ in 0..num_vectors {
         let random_vector: Vec<_> = (0..dim).map(|_| rnd.gen_range(0.0, 1.0)).collect();
-        let point_id = rnd.gen_range(1, 100_000_000);
+        let point_id: PointIdType = rnd.gen_range(1u64, 100_000_000).into();
         let payload_value = rnd.gen_range(1, 1_000);
         segment.upsert_point(
             opnum,
@@ -54,11 +54,11 @@ pub fn build_segment_1(path: &Path) -> Segment {
     let vec4 = vec![1.0, 1.0, 0.0, 1.0];
     let vec5 = vec![1.0, 0.0, 0.0, 0.0];
 
-    segment1.upsert_point(1, 1, &vec1).unwrap();
-    segment1.upsert_point(2, 2, &vec2).unwrap();
-    segment1.upsert_point(3, 3, &vec3).unwrap();
-    segment1.upsert_point(4, 4, &vec4).unwrap();
-    segment1.upsert_point(5, 5, &vec5).unwrap();
+    segment1.upsert_point(1, 1.into(), &vec1).unwrap();
+    segment1.upsert_point(2, 2.into(), &vec2).unwrap();
+    segment1.upsert_point(3, 3.into(), &vec3).unwrap();
+    segment1.upsert_point(4, 4.into(), &vec4).unwrap();
+    segment1.upsert_point(5, 5.into(), &vec5).unwrap();
 
     let payload_key = "color".to_owned();
 
@@ -66,11 +66,11 @@ pub fn build_segment_1(path: &Path) -> Segment {
     let payload_option2 = PayloadType::Keyword(vec!["red".to_owned(), "blue".to_owned()]);
     let payload_option3 = PayloadType::Keyword(vec!["blue".to_owned()]);
 
-    segment1.set_payload(6, 1, &payload_key, payload_option1.clone()).unwrap();
-    segment1.set_payload(6, 2, &payload_key, payload_option1.clone()).unwrap();
-    segment1.set_payload(6, 3, &payload_key, payload_option3.clone()).unwrap();
-    segment1.set_payload(6, 4, &payload_key, payload_option2.clone()).unwrap();
-    segment1.set_payload(6, 5, &payload_key, payload_option2.clone()).unwrap();
+    segment1.set_payload(6, 1.into(), &payload_key, payload_option1.clone()).unwrap();
+    segment1.set_payload(6, 2.into(), &payload_key, payload_option1.clone()).unwrap();
+    segment1.set_payload(6, 3.into(), &payload_key, payload_option3.clone()).unwrap();
+    segment1.set_payload(6, 4.into(), &payload_key, payload_option2.clone()).unwrap();
+    segment1.set_payload(6, 5.into(), &payload_key, payload_option2.clone()).unwrap();
 
     return segment1;
 }
@@ -87,14 +87,14 @@ pub fn build_segment_2(path: &Path) -> Segment {
     let vec14 = vec![1.0, 0.0, 0.0, 1.0];
     let vec15 = vec![1.0, 1.0, 0.0, 0.0];
 
-    segment2.upsert_point(7, 4, &vec4).unwrap();
-    segment2.upsert_point(8, 5, &vec5).unwrap();
+    segment2.upsert_point(7, 4.into(), &vec4).unwrap();
+    segment2.upsert_point(8, 5.into(), &vec5).unwrap();
 
-    segment2.upsert_point(11, 11, &vec11).unwrap();
-    segment2.upsert_point(12, 12, &vec12).unwrap();
-    segment2.upsert_point(13, 13, &vec13).unwrap();
-    segment2.upsert_point(14, 14, &vec14).unwrap();
-    segment2.upsert_point(15, 15, &vec15).unwrap();
+    segment2.upsert_point(11, 11.into(), &vec11).unwrap();
+    segment2.upsert_point(12, 12.into(), &vec12).unwrap();
+    segment2.upsert_point(13, 13.into(), &vec13).unwrap();
+    segment2.upsert_point(14, 14.into(), &vec14).unwrap();
+    segment2.upsert_point(15, 15.into(), &vec15).unwrap();
 
     return segment2;
 }
@@ -1,10 +1,13 @@
 use segment::entry::entry_point::{SegmentEntry, OperationResult};
-use segment::types::{Filter, Condition, SearchParams, ScoredPoint, PayloadKeyType, PayloadType, TheMap, SeqNumberType, VectorElementType, PointIdType, SegmentInfo, SegmentType, SegmentConfig};
+use segment::types::{Filter, Condition, SearchParams, ScoredPoint, PayloadKeyType, PayloadType, TheMap, SeqNumberType, VectorElementType, PointIdType, SegmentInfo, SegmentType, SegmentConfig, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, SimilarPair, Direction, SegmentSearchProfile};
 use std::cmp::max;
 use crate::segment_manager::holders::segment_holder::LockedSegment;
-use std::collections::HashSet;
+use crate::sampling::merge_weighted_samples;
+use crate::similar_pairs::cross_segment_pairs;
+use std::collections::{HashSet, HashMap};
 use std::sync::Arc;
 use parking_lot::RwLock;
+use segment::index::field_index::CardinalityEstimation;
 
 type LockedRmSet = Arc<RwLock<HashSet<PointIdType>>>;
 type LockedFieldsSet = Arc<RwLock<HashSet<PayloadKeyType>>>;
@@ -59,6 +62,42 @@ impl ProxySegment {
         Ok(true)
     }
 
+    /// Extends `filter` with a condition which excludes points, deleted from the wrapped segment
+    fn add_deleted_points_condition_to_filter(filter: Option<&Filter>, deleted_points: &HashSet<PointIdType>) -> Filter {
+        // ToDo: Come up with better way to pass deleted points into Filter
+        // e.g. implement AtomicRefCell for Serializer.
+        // This copy might slow process down if there will be a lot of deleted points
+        let wrapper_condition = Condition::HasId(deleted_points.clone().into());
+        match filter {
+            None => Filter::new_must_not(wrapper_condition),
+            Some(f) => {
+                let mut new_filter = f.clone();
+                let must_not = new_filter.must_not;
+
+                let new_must_not = match must_not {
+                    None => Some(vec![wrapper_condition]),
+                    Some(mut conditions) => {
+                        conditions.push(wrapper_condition);
+                        Some(conditions)
+                    }
+                };
+                new_filter.must_not = new_must_not;
+                new_filter
+            }
+        }
+    }
+
+    /// Filter for the wrapped segment, which also excludes deleted points.
+    /// Returns `None` if there are no deleted points and original filter could be used as is
+    fn wrapped_segment_filter(&self, filter: Option<&Filter>) -> Option<Filter> {
+        let deleted_points = self.deleted_points.read();
+        if deleted_points.is_empty() {
+            None
+        } else {
+            Some(Self::add_deleted_points_condition_to_filter(filter, &deleted_points))
+        }
+    }
+
     fn move_if_exists(&self, op_num: SeqNumberType, point_id: PointIdType) -> OperationResult<bool> {
         let wrapped_has_point = self.wrapped_segment.get().read().has_point(point_id);
         let already_deleted = self.deleted_points.read().contains(&point_id);
@@ -85,33 +124,11 @@ impl SegmentEntry for ProxySegment {
         // That is why we need to pass additional filter for deleted points
         let do_update_filter = !deleted_points.is_empty();
         let mut wrapped_result = if do_update_filter {
-            // ToDo: Come up with better way to pass deleted points into Filter
-            // e.g. implement AtomicRefCell for Serializer.
-            // This copy might slow process down if there will be a lot of deleted points
-            let wrapper_condition = Condition::HasId(deleted_points.clone().into());
-            let wrapped_filter = match filter {
-                None => {
-                    Some(Filter::new_must_not(wrapper_condition))
-                }
-                Some(f) => {
-                    let mut new_filter = f.clone();
-                    let must_not = new_filter.must_not;
-
-                    let new_must_not = match must_not {
-                        None => Some(vec![wrapper_condition]),
-                        Some(mut conditions) => {
-                            conditions.push(wrapper_condition);
-                            Some(conditions)
-                        }
-                    };
-                    new_filter.must_not = new_must_not;
-                    Some(new_filter)
-                }
-            };
+            let wrapped_filter = Self::add_deleted_points_condition_to_filter(filter, &deleted_points);
 
             self.wrapped_segment.get().read().search(
                 vector,
-                wrapped_filter.as_ref(),
+                Some(&wrapped_filter),
                 top,
                 params,
             )?
@@ -135,6 +152,168 @@ impl SegmentEntry for ProxySegment {
         return Ok(wrapped_result);
     }
 
+    fn search_explain(&self, vector: &[VectorElementType], filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+
+        let (mut result, mut profiles) = self.wrapped_segment.get().read().search_explain(
+            vector,
+            wrapped_filter.as_ref().or(filter),
+            top,
+            params,
+        )?;
+
+        let (mut write_result, mut write_profiles) = self.write_segment.get().read().search_explain(
+            vector,
+            filter,
+            top,
+            params,
+        )?;
+
+        result.append(&mut write_result);
+        profiles.append(&mut write_profiles);
+        Ok((result, profiles))
+    }
+
+    fn search_batch(&self, vectors: &[&Vec<VectorElementType>], filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> OperationResult<Vec<Vec<ScoredPoint>>> {
+        let deleted_points = self.deleted_points.read();
+
+        let mut wrapped_results = if !deleted_points.is_empty() {
+            let wrapped_filter = Self::add_deleted_points_condition_to_filter(filter, &deleted_points);
+
+            self.wrapped_segment.get().read().search_batch(
+                vectors,
+                Some(&wrapped_filter),
+                top,
+                params,
+            )?
+        } else {
+            self.wrapped_segment.get().read().search_batch(
+                vectors,
+                filter,
+                top,
+                params,
+            )?
+        };
+
+        let write_results = self.write_segment.get().read().search_batch(
+            vectors,
+            filter,
+            top,
+            params,
+        )?;
+
+        for (wrapped_result, mut write_result) in wrapped_results.iter_mut().zip(write_results) {
+            wrapped_result.append(&mut write_result);
+        }
+        Ok(wrapped_results)
+    }
+
+    fn search_examples(&self, query: &ExampleQuery, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> OperationResult<Vec<ScoredPoint>> {
+        let deleted_points = self.deleted_points.read();
+
+        let mut wrapped_result = if !deleted_points.is_empty() {
+            let wrapped_filter = Self::add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment.get().read().search_examples(query, Some(&wrapped_filter), top, params)?
+        } else {
+            self.wrapped_segment.get().read().search_examples(query, filter, top, params)?
+        };
+
+        let mut write_result = self.write_segment.get().read().search_examples(query, filter, top, params)?;
+
+        wrapped_result.append(&mut write_result);
+        Ok(wrapped_result)
+    }
+
+    fn search_by_point(&self, point_id: PointIdType, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> OperationResult<Vec<ScoredPoint>> {
+        // Segment, which does not hold the point, is searched with its vector
+        let vector = self.vector(point_id)?;
+        let deleted_points = self.deleted_points.read();
+        let is_moved = deleted_points.contains(&point_id);
+
+        let wrapped_filter = if deleted_points.is_empty() {
+            filter.cloned()
+        } else {
+            Some(Self::add_deleted_points_condition_to_filter(filter, &deleted_points))
+        };
+
+        let wrapped_segment = self.wrapped_segment.get();
+        let wrapped_segment_guard = wrapped_segment.read();
+        let mut wrapped_result = if is_moved {
+            wrapped_segment_guard.search(&vector, wrapped_filter.as_ref(), top, params)?
+        } else {
+            wrapped_segment_guard.search_by_point(point_id, wrapped_filter.as_ref(), top, params)?
+        };
+
+        let write_segment = self.write_segment.get();
+        let write_segment_guard = write_segment.read();
+        let mut write_result = if is_moved {
+            write_segment_guard.search_by_point(point_id, filter, top, params)?
+        } else {
+            write_segment_guard.search(&vector, filter, top, params)?
+        };
+
+        wrapped_result.append(&mut write_result);
+        Ok(wrapped_result)
+    }
+
+    fn search_by_point_explain(&self, point_id: PointIdType, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)> {
+        // Segment, which does not hold the point, is searched with its vector
+        let vector = self.vector(point_id)?;
+        let deleted_points = self.deleted_points.read();
+        let is_moved = deleted_points.contains(&point_id);
+
+        let wrapped_filter = if deleted_points.is_empty() {
+            filter.cloned()
+        } else {
+            Some(Self::add_deleted_points_condition_to_filter(filter, &deleted_points))
+        };
+
+        let wrapped_segment = self.wrapped_segment.get();
+        let wrapped_segment_guard = wrapped_segment.read();
+        let (mut result, mut profiles) = if is_moved {
+            wrapped_segment_guard.search_explain(&vector, wrapped_filter.as_ref(), top, params)?
+        } else {
+            wrapped_segment_guard.search_by_point_explain(point_id, wrapped_filter.as_ref(), top, params)?
+        };
+
+        let write_segment = self.write_segment.get();
+        let write_segment_guard = write_segment.read();
+        let (mut write_result, mut write_profiles) = if is_moved {
+            write_segment_guard.search_by_point_explain(point_id, filter, top, params)?
+        } else {
+            write_segment_guard.search_explain(&vector, filter, top, params)?
+        };
+
+        result.append(&mut write_result);
+        profiles.append(&mut write_profiles);
+        Ok((result, profiles))
+    }
+
+    /// Pairs between wrapped and write segments are verified among search results of the write segment points,
+    /// as write segment is expected to be much smaller
+    fn similar_pairs(&self, threshold: ScoreType, filter: Option<&Filter>, limit: usize) -> OperationResult<Vec<SimilarPair>> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+        let wrapped_filter = wrapped_filter.as_ref().or(filter);
+
+        let wrapped_segment = self.wrapped_segment.get();
+        let wrapped_segment = wrapped_segment.read();
+        let write_segment = self.write_segment.get();
+        let write_segment = write_segment.read();
+
+        let mut pairs = wrapped_segment.similar_pairs(threshold, wrapped_filter, limit)?;
+        if pairs.len() < limit {
+            pairs.extend(write_segment.similar_pairs(threshold, filter, limit - pairs.len())?);
+        }
+        if pairs.len() < limit {
+            cross_segment_pairs(&*write_segment, filter, &*wrapped_segment, wrapped_filter, threshold, &mut |pair| {
+                pairs.push(pair);
+                pairs.len() < limit
+            })?;
+        }
+
+        Ok(pairs)
+    }
+
     fn upsert_point(&mut self, op_num: SeqNumberType, point_id: PointIdType, vector: &Vec<VectorElementType>) -> OperationResult<bool> {
         if self.version() > op_num { return Ok(false); }
         self.move_if_exists(op_num, point_id)?;
@@ -160,12 +339,24 @@ impl SegmentEntry for ProxySegment {
         self.write_segment.get().write().set_full_payload(op_num, point_id, full_payload)
     }
 
-    fn set_full_payload_with_json(&mut self, op_num: u64, point_id: u64, full_payload: &str) -> OperationResult<bool> {
+    fn set_full_payload_with_json(&mut self, op_num: SeqNumberType, point_id: PointIdType, full_payload: &str) -> OperationResult<bool> {
         if self.version() > op_num { return Ok(false); }
         self.move_if_exists(op_num, point_id)?;
         self.write_segment.get().write().set_full_payload_with_json(op_num, point_id, full_payload)
     }
 
+    fn update_payload(
+        &mut self,
+        op_num: SeqNumberType,
+        point_id: PointIdType,
+        patch: &TheMap<PayloadKeyType, serde_json::Value>,
+        append: &TheMap<PayloadKeyType, serde_json::Value>,
+    ) -> OperationResult<bool> {
+        if self.version() > op_num { return Ok(false); }
+        self.move_if_exists(op_num, point_id)?;
+        self.write_segment.get().write().update_payload(op_num, point_id, patch, append)
+    }
+
     fn set_payload(&mut self, op_num: SeqNumberType, point_id: PointIdType, key: &PayloadKeyType, payload: PayloadType) -> OperationResult<bool> {
         if self.version() > op_num { return Ok(false); }
         self.move_if_exists(op_num, point_id)?;
@@ -201,12 +392,142 @@ impl SegmentEntry for ProxySegment {
     }
 
     /// Not implemented for proxy
-    fn iter_points(&self) -> Box<dyn Iterator<Item=u64> + '_> {
+    fn iter_points(&self) -> Box<dyn Iterator<Item=PointIdType> + '_> {
         // iter_points is not available for Proxy implementation
         // Due to internal locks it is almost impossible to return iterator with proper owning, lifetimes, e.t.c.
         unimplemented!()
     }
 
+    fn read_filtered(&self, offset: PointIdType, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType> {
+        let deleted_points = self.deleted_points.read();
+        let mut read_points = if deleted_points.is_empty() {
+            self.wrapped_segment.get().read().read_filtered(offset, limit, filter)
+        } else {
+            let wrapped_filter = Self::add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment.get().read().read_filtered(offset, limit, Some(&wrapped_filter))
+        };
+        let mut write_segment_points = self.write_segment.get().read().read_filtered(offset, limit, filter);
+        read_points.append(&mut write_segment_points);
+        read_points.sort_unstable();
+        read_points.dedup();
+        read_points.truncate(limit);
+        read_points
+    }
+
+    fn filtered_points(&self, filter: &Filter) -> Vec<PointIdType> {
+        let wrapped_filter = self.wrapped_segment_filter(Some(filter));
+        let wrapped_filter = wrapped_filter.as_ref().unwrap_or(filter);
+        let mut points = self.wrapped_segment.get().read().filtered_points(wrapped_filter);
+        let mut write_segment_points = self.write_segment.get().read().filtered_points(filter);
+        points.append(&mut write_segment_points);
+        points.sort_unstable();
+        points.dedup();
+        points
+    }
+
+    fn read_vectors(&self, filter: Option<&Filter>, f: &mut dyn FnMut(PointIdType, Vec<VectorElementType>) -> bool) {
+        let deleted_points = self.deleted_points.read();
+        let mut stopped = false;
+        self.wrapped_segment.get().read().read_vectors(filter, &mut |point_id, vector| {
+            if deleted_points.contains(&point_id) {
+                return true;
+            }
+            stopped = !f(point_id, vector);
+            !stopped
+        });
+        if !stopped {
+            self.write_segment.get().read().read_vectors(filter, f);
+        }
+    }
+
+    fn sample_filtered(&self, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+        let wrapped_filter = wrapped_filter.as_ref().or(filter);
+
+        let wrapped_segment = self.wrapped_segment.get();
+        let wrapped_segment = wrapped_segment.read();
+        let write_segment = self.write_segment.get();
+        let write_segment = write_segment.read();
+
+        merge_weighted_samples(vec![
+            (
+                wrapped_segment.estimate_points_count(wrapped_filter).exp,
+                wrapped_segment.sample_filtered(limit, wrapped_filter),
+            ),
+            (
+                write_segment.estimate_points_count(filter).exp,
+                write_segment.sample_filtered(limit, filter),
+            ),
+        ], limit)
+    }
+
+    fn read_ordered(
+        &self,
+        key: &PayloadKeyType,
+        direction: Direction,
+        from: Option<(FloatPayloadType, PointIdType)>,
+        limit: usize,
+        filter: Option<&Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        let deleted_points = self.deleted_points.read();
+        let mut read_points = if deleted_points.is_empty() {
+            self.wrapped_segment.get().read().read_ordered(key, direction, from, limit, filter)
+        } else {
+            let wrapped_filter = Self::add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment.get().read().read_ordered(key, direction, from, limit, Some(&wrapped_filter))
+        };
+        let mut write_segment_points = self.write_segment.get().read().read_ordered(key, direction, from, limit, filter);
+        read_points.append(&mut write_segment_points);
+        read_points.sort_by(|(value_a, id_a), (value_b, id_b)| direction.compare(*value_a, *value_b).then(id_a.cmp(id_b)));
+
+        let mut unique_ids: HashSet<PointIdType> = HashSet::new();
+        read_points.retain(|(_, point_id)| unique_ids.insert(*point_id));
+        read_points.truncate(limit);
+        read_points
+    }
+
+    fn estimate_points_count(&self, filter: Option<&Filter>) -> CardinalityEstimation {
+        let deleted_points_count = self.deleted_points.read().len();
+        let wrapped_estimation = self.wrapped_segment.get().read().estimate_points_count(filter);
+        let write_estimation = self.write_segment.get().read().estimate_points_count(filter);
+
+        // Any of the deleted points might or might not match the filter
+        CardinalityEstimation {
+            primary_clauses: vec![],
+            min: wrapped_estimation.min.saturating_sub(deleted_points_count) + write_estimation.min,
+            exp: wrapped_estimation.exp.saturating_sub(deleted_points_count) + write_estimation.exp,
+            max: wrapped_estimation.max + write_estimation.max,
+            sketch: None,
+        }
+    }
+
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+        let mut counts = self.wrapped_segment.get().read().facet_counts(key, wrapped_filter.as_ref().or(filter));
+        let write_counts = self.write_segment.get().read().facet_counts(key, filter);
+        for (value, count) in write_counts {
+            *counts.entry(value).or_insert(0) += count;
+        }
+        counts
+    }
+
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+        let wrapped_range = self.wrapped_segment.get().read().numeric_range(key, wrapped_filter.as_ref().or(filter));
+        let write_range = self.write_segment.get().read().numeric_range(key, filter);
+        match (wrapped_range, write_range) {
+            (Some((wrapped_min, wrapped_max)), Some((write_min, write_max))) => Some((wrapped_min.min(write_min), wrapped_max.max(write_max))),
+            (wrapped_range, write_range) => wrapped_range.or(write_range),
+        }
+    }
+
+    fn numeric_histogram(&self, key: &PayloadKeyType, filter: Option<&Filter>, min: FloatPayloadType, max: FloatPayloadType, buckets: usize) -> Vec<usize> {
+        let wrapped_filter = self.wrapped_segment_filter(filter);
+        let wrapped_histogram = self.wrapped_segment.get().read().numeric_histogram(key, wrapped_filter.as_ref().or(filter), min, max, buckets);
+        let write_histogram = self.write_segment.get().read().numeric_histogram(key, filter, min, max, buckets);
+        wrapped_histogram.into_iter().zip(write_histogram).map(|(a, b)| a + b).collect()
+    }
+
     fn has_point(&self, point_id: PointIdType) -> bool {
         return if self.deleted_points.read().contains(&point_id) {
             self.write_segment.get().read().has_point(point_id)
@@ -285,6 +606,10 @@ impl SegmentEntry for ProxySegment {
             .filter(|x| !self.deleted_indexes.read().contains(x))
             .collect()
     }
+
+    fn get_outdated_fields(&self) -> Vec<PayloadKeyType> {
+        self.wrapped_segment.get().read().get_outdated_fields()
+    }
 }
 
 
@@ -313,10 +638,10 @@ mod tests {
         );
 
         let vec4 = vec![1.1, 1.0, 0.0, 1.0];
-        proxy_segment.upsert_point(100, 4, &vec4).unwrap();
+        proxy_segment.upsert_point(100, 4.into(), &vec4).unwrap();
         let vec6 = vec![1.0, 1.0, 0.5, 1.0];
-        proxy_segment.upsert_point(101, 6, &vec6).unwrap();
-        proxy_segment.delete_point(102, 1).unwrap();
+        proxy_segment.upsert_point(101, 6.into(), &vec6).unwrap();
+        proxy_segment.delete_point(102, 1.into()).unwrap();
 
 
         let query_vector = vec![1.0, 1.0, 1.0, 1.0];
@@ -333,15 +658,45 @@ mod tests {
             seen_points.insert(res.id);
         }
 
-        assert!(seen_points.contains(&4));
-        assert!(seen_points.contains(&6));
-        assert!(!seen_points.contains(&1));
+        assert!(seen_points.contains(&PointIdType::from(4)));
+        assert!(seen_points.contains(&PointIdType::from(6)));
+        assert!(!seen_points.contains(&PointIdType::from(1)));
 
-        assert!(!proxy_segment.write_segment.get().read().has_point(2));
+        assert!(!proxy_segment.write_segment.get().read().has_point(2.into()));
 
         let payload_key = "color".to_owned();
-        proxy_segment.delete_payload(103, 2, &payload_key).unwrap();
+        proxy_segment.delete_payload(103, 2.into(), &payload_key).unwrap();
+
+        assert!(proxy_segment.write_segment.get().read().has_point(2.into()))
+    }
+
+    #[test]
+    fn test_similar_pairs() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let original_segment = LockedSegment::new(build_segment_1(dir.path()));
+        let write_segment = LockedSegment::new(empty_segment(dir.path()));
+        let deleted_points = Arc::new(RwLock::new(HashSet::<PointIdType>::new()));
+
+        let deleted_indexes = Arc::new(RwLock::new(HashSet::<PayloadKeyType>::new()));
+        let created_indexes = Arc::new(RwLock::new(HashSet::<PayloadKeyType>::new()));
+
+        let mut proxy_segment = ProxySegment::new(
+            original_segment,
+            write_segment,
+            deleted_points,
+            deleted_indexes,
+            created_indexes
+        );
+
+        let vec10 = vec![1.0, 1.0, 1.0, 1.0];
+        proxy_segment.upsert_point(100, 10.into(), &vec10).unwrap();
+
+        let pairs = proxy_segment.similar_pairs(3.5, None, usize::MAX).unwrap();
+        assert_eq!(pairs, vec![SimilarPair::new(3.into(), 10.into(), 4.0)]);
+
+        proxy_segment.delete_point(101, 3.into()).unwrap();
 
-        assert!(proxy_segment.write_segment.get().read().has_point(2))
+        let pairs = proxy_segment.similar_pairs(3.5, None, usize::MAX).unwrap();
+        assert!(pairs.is_empty());
     }
 }
@@ -1,14 +1,15 @@
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use itertools::Itertools;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use rand::{Rng, thread_rng};
 use rand::seq::SliceRandom;
 
 use segment::entry::entry_point::{OperationError, OperationResult, SegmentEntry};
 use segment::segment::Segment;
-use segment::types::{PointIdType, SeqNumberType};
+use segment::types::{Filter, PointIdType, SeqNumberType};
 
 use crate::segment_manager::holders::proxy_segment::ProxySegment;
 
@@ -159,9 +160,7 @@ impl<'s> SegmentHolder {
     }
 
     /// Selects point ids, which is stored in this segment
-    fn segment_points(&self, ids: &Vec<PointIdType>, segment: &LockedSegment) -> Vec<PointIdType> {
-        let segment_arc = segment.get();
-        let entry = segment_arc.read();
+    fn segment_points(ids: &[PointIdType], entry: &dyn SegmentEntry) -> Vec<PointIdType> {
         ids
             .iter()
             .cloned()
@@ -185,15 +184,30 @@ impl<'s> SegmentHolder {
     }
 
 
-    pub fn apply_points<F>(&self, op_num: SeqNumberType, ids: &Vec<PointIdType>, mut f: F) -> OperationResult<usize>
+    pub fn apply_points<F>(&self, op_num: SeqNumberType, ids: &[PointIdType], f: F) -> OperationResult<usize>
         where F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
+    {
+        self.apply_selected_points(op_num, |entry| Self::segment_points(ids, entry), f)
+    }
+
+    /// Apply function to the points, which satisfy the filter.
+    /// Filter is resolved by each segment separately, right before the function is applied to the segment.
+    pub fn apply_filtered_points<F>(&self, op_num: SeqNumberType, filter: &Filter, f: F) -> OperationResult<usize>
+        where F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
+    {
+        self.apply_selected_points(op_num, |entry| entry.filtered_points(filter), f)
+    }
+
+    fn apply_selected_points<S, F>(&self, op_num: SeqNumberType, select: S, mut f: F) -> OperationResult<usize>
+        where S: Fn(&dyn SegmentEntry) -> Vec<PointIdType>,
+              F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
     {
         let mut applied_points = 0;
         for (_idx, segment) in self.segments.iter() {
             // Skip this segment if it already have bigger version (WAL recovery related)
             if segment.get().read().version() > op_num { continue; }
             // Collect affected points first, we want to lock segment for writing as rare as possible
-            let segment_points = self.segment_points(ids, segment);
+            let segment_points = select(&*segment.get().read());
             if !segment_points.is_empty() {
                 let segment_arc = segment.get();
                 let mut write_segment = segment_arc.write();
@@ -211,17 +225,49 @@ impl<'s> SegmentHolder {
     pub fn apply_points_to_appendable<F>(
         &self,
         op_num: SeqNumberType,
-        ids: &Vec<PointIdType>, mut f: F) -> OperationResult<usize>
+        ids: &[PointIdType], f: F) -> OperationResult<usize>
         where F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
+    {
+        self.apply_selected_points_to_appendable(op_num, |entry| Self::segment_points(ids, entry), f)
+    }
+
+    /// Same as `apply_points_to_appendable`, but points are selected by the filter.
+    /// Points of all segments are selected before any update, so moved points are not matched again
+    pub fn apply_filtered_points_to_appendable<F>(
+        &self,
+        op_num: SeqNumberType,
+        filter: &Filter, f: F) -> OperationResult<usize>
+        where F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
+    {
+        let matched_points = self.segments
+            .values()
+            .flat_map(|segment| segment.get().read().filtered_points(filter))
+            .unique()
+            .collect_vec();
+        self.apply_points_to_appendable(op_num, &matched_points, f)
+    }
+
+    fn apply_selected_points_to_appendable<S, F>(
+        &self,
+        op_num: SeqNumberType,
+        select: S, mut f: F) -> OperationResult<usize>
+        where S: Fn(&dyn SegmentEntry) -> Vec<PointIdType>,
+              F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>
     {
         // Choose random appendable segment
         let default_write_segment = self.random_appendable_segment()
             .ok_or(OperationError::ServiceError { description: "No appendable segments exists, expected at least one".to_string() })?;
 
-        let applied_points = self.apply_points(
+        // Points, moved into the appendable segment, are already updated and should be skipped there
+        let mut moved_points: HashSet<PointIdType> = HashSet::new();
+
+        let applied_points = self.apply_selected_points(
             op_num,
-            ids,
+            select,
             |point_id, write_segment| {
+                if moved_points.contains(&point_id) {
+                    return Ok(false);
+                }
                 let is_applied = if write_segment.is_appendable() {
                     f(point_id, write_segment)?
                 } else {
@@ -234,6 +280,7 @@ impl<'s> SegmentHolder {
                     default_segment_guard.set_full_payload(op_num, point_id, payload)?;
 
                     write_segment.delete_point(op_num, point_id)?;
+                    moved_points.insert(point_id);
 
                     f(point_id, &mut default_segment_guard)?
                 };
@@ -46,7 +46,10 @@ impl IndexingOptimizer {
 
                 let require_indexing = is_big_for_index || (has_payload && is_big_for_payload_index);
 
-                match is_plain && require_indexing {
+                // Re-build indexed segments, which payload indexes are outdated by in-place payload changes
+                let has_outdated_indexes = !read_segment.get_outdated_fields().is_empty();
+
+                match (is_plain && require_indexing) || has_outdated_indexes {
                     true => Some((*idx, vector_count)),
                     false => None
                 }
@@ -97,7 +100,7 @@ mod tests {
     use crate::segment_manager::simple_segment_updater::SimpleSegmentUpdater;
     use crate::operations::FieldIndexOperations;
     use crate::operations::point_ops::{PointOperations, PointInsertOperations};
-    use segment::types::StorageType;
+    use segment::types::{StorageType, PayloadType};
 
 
     fn init() {
@@ -139,6 +142,7 @@ mod tests {
             CollectionParams {
                 vector_size: segment_config.vector_size,
                 distance: segment_config.distance,
+                payload_storage_type: Default::default(),
             },
             Default::default()
         );
@@ -196,7 +200,7 @@ mod tests {
         }
 
         let insert_point_ops = PointOperations::UpsertPoints(PointInsertOperations::BatchPoints {
-            ids: vec![501, 502, 503],
+            ids: vec![501.into(), 502.into(), 503.into()],
             vectors: vec![
                 vec![1.0, 0.0, 0.5, 0.0],
                 vec![1.0, 0.0, 0.5, 0.5],
@@ -228,7 +232,7 @@ mod tests {
         assert!(new_infos2.len() > new_infos.len(), "Check that new appendable segment was created");
 
         let insert_point_ops = PointOperations::UpsertPoints(PointInsertOperations::BatchPoints {
-            ids: vec![601, 602, 603],
+            ids: vec![601.into(), 602.into(), 603.into()],
             vectors: vec![
                 vec![0.0, 1.0, 0.5, 0.0],
                 vec![0.0, 1.0, 0.5, 0.5],
@@ -238,5 +242,20 @@ mod tests {
         });
 
         updater.process_point_operation(opnum.next().unwrap(), insert_point_ops).unwrap();
+
+        // ---- In-place payload change of an indexed segment makes its payload index outdated
+        let (indexed_segment_id, indexed_segment) = locked_holder.read().iter()
+            .map(|(sid, segment)| (*sid, segment.get()))
+            .find(|(_sid, segment)| segment.read().segment_type() == SegmentType::Indexed)
+            .unwrap();
+        let point_id = indexed_segment.read().iter_points().next().unwrap();
+        indexed_segment.write().set_payload(opnum.next().unwrap(), point_id, &payload_field, PayloadType::Integer(vec![1])).unwrap();
+
+        let suggested_to_optimize = index_optimizer.check_condition(locked_holder.clone());
+        assert_eq!(suggested_to_optimize, vec![indexed_segment_id]);
+        index_optimizer.optimize(locked_holder.clone(), suggested_to_optimize).unwrap();
+
+        let suggested_to_optimize = index_optimizer.check_condition(locked_holder.clone());
+        assert!(suggested_to_optimize.is_empty());
     }
 }
@@ -11,7 +11,7 @@ use itertools::Itertools;
 use segment::segment_constructor::segment_builder::SegmentBuilder;
 use std::convert::TryInto;
 use std::path::Path;
-use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
+use segment::segment_constructor::segment_constructor::build_segment;
 use crate::config::CollectionParams;
 
 
@@ -50,11 +50,11 @@ pub trait SegmentOptimizer {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: collection_params.payload_storage_type,
         };
-        Ok(LockedSegment::new(build_simple_segment(
+        Ok(LockedSegment::new(build_segment(
             self.collection_path(),
-            config.vector_size,
-            config.distance,
+            &config,
         )?))
     }
 
@@ -82,6 +82,7 @@ pub trait SegmentOptimizer {
             index: if is_indexed { Indexes::Hnsw(self.hnsw_config()) } else { Indexes::Plain {} },
             payload_index: Some(if is_payload_indexed { PayloadIndexType::Struct } else { PayloadIndexType::Plain }),
             storage_type: if is_on_disk { StorageType::Mmap } else { StorageType::InMemory },
+            payload_storage_type: collection_params.payload_storage_type,
         };
 
         Ok(SegmentBuilder::new(
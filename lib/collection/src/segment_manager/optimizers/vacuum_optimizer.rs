@@ -173,6 +173,7 @@ mod tests {
             CollectionParams {
                 vector_size: 4,
                 distance: Distance::Dot,
+                payload_storage_type: Default::default(),
             },
             Default::default()
         );
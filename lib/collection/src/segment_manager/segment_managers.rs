@@ -3,7 +3,7 @@ use std::sync::Arc;
 use segment::types::{PointIdType, ScoredPoint, SeqNumberType};
 
 use crate::operations::CollectionUpdateOperations;
-use crate::operations::types::{CollectionResult, Record, SearchRequest};
+use crate::operations::types::{CollectionResult, Record, SearchRequest, SearchRequestBatch, SearchGroupsRequest, SearchGroupsResult, ExampleSearchRequest, SearchExplainResult};
 
 pub trait SegmentSearcher {
     fn search(&self,
@@ -11,6 +11,18 @@ pub trait SegmentSearcher {
               request: Arc<SearchRequest>,
     ) -> CollectionResult<Vec<ScoredPoint>>;
 
+    /// Same as `search`, but also returns profiles of the search in each segment
+    fn search_explain(&self, request: Arc<SearchRequest>) -> CollectionResult<SearchExplainResult>;
+
+    /// Perform multiple searches at once. Returns list of results for each request in the same order
+    fn search_batch(&self, request: Arc<SearchRequestBatch>) -> CollectionResult<Vec<Vec<ScoredPoint>>>;
+
+    /// Search for best points, grouped by the value of payload field
+    fn search_groups(&self, request: Arc<SearchGroupsRequest>) -> CollectionResult<SearchGroupsResult>;
+
+    /// Search for points, best scored against multiple examples
+    fn search_examples(&self, request: Arc<ExampleSearchRequest>) -> CollectionResult<Vec<ScoredPoint>>;
+
     fn retrieve(
         &self,
         points: &Vec<PointIdType>,
@@ -1,13 +1,18 @@
 use crate::segment_manager::holders::segment_holder::{LockedSegment, LockedSegmentHolder};
 use std::sync::Arc;
 use crate::segment_manager::segment_managers::{SegmentSearcher};
-use crate::operations::types::CollectionResult;
-use segment::types::{ScoredPoint, PointIdType, SeqNumberType};
+use crate::operations::types::{CollectionResult, CollectionError};
+use segment::types::{ScoredPoint, PointIdType, SeqNumberType, VectorElementType, SegmentSearchProfile, FacetValue};
 use tokio::runtime::Runtime;
 use std::collections::{HashSet, HashMap};
 use segment::spaces::tools::peek_top_scores_iterable;
 use futures::future::try_join_all;
-use crate::operations::types::{Record, SearchRequest};
+use crate::operations::types::{Record, SearchRequest, SearchRequestBatch, SearchGroupsRequest, SearchGroupsResult, PointGroup, ExampleSearchRequest, RecommendExample, SearchExplainResult};
+use itertools::Itertools;
+use std::mem;
+
+/// Max number of search attempts with growing `top`, required to fill groups in grouped search
+const MAX_GROUP_SEARCH_ATTEMPTS: usize = 5;
 
 /// Simple implementation of segment manager
 ///  - owens segments
@@ -30,9 +35,61 @@ impl SimpleSegmentSearcher {
     pub async fn search_in_segment(
         segment: LockedSegment,
         request: Arc<SearchRequest>,
+        point_vectors: Arc<HashMap<PointIdType, Vec<VectorElementType>>>,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let segment_arc = segment.get();
+        let read_segment = segment_arc.read();
+
+        let res = match request.vector.point_id() {
+            Some(point_id) if read_segment.has_point(point_id) => read_segment.search_by_point(
+                point_id,
+                request.filter.as_ref(),
+                request.top,
+                request.params.as_ref(),
+            )?,
+            _ => read_segment.search(
+                Self::query_vector(&request.vector, &point_vectors),
+                request.filter.as_ref(),
+                request.top,
+                request.params.as_ref(),
+            )?,
+        };
+
+        Ok(res)
+    }
+
+    pub async fn search_explain_in_segment(
+        segment: LockedSegment,
+        request: Arc<SearchRequest>,
+        point_vectors: Arc<HashMap<PointIdType, Vec<VectorElementType>>>,
+    ) -> CollectionResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)> {
+        let segment_arc = segment.get();
+        let read_segment = segment_arc.read();
+
+        let res = match request.vector.point_id() {
+            Some(point_id) if read_segment.has_point(point_id) => read_segment.search_by_point_explain(
+                point_id,
+                request.filter.as_ref(),
+                request.top,
+                request.params.as_ref(),
+            )?,
+            _ => read_segment.search_explain(
+                Self::query_vector(&request.vector, &point_vectors),
+                request.filter.as_ref(),
+                request.top,
+                request.params.as_ref(),
+            )?,
+        };
+
+        Ok(res)
+    }
+
+    pub async fn search_examples_in_segment(
+        segment: LockedSegment,
+        request: Arc<ExampleSearchRequest>,
     ) -> CollectionResult<Vec<ScoredPoint>> {
-        let res = segment.get().read().search(
-            &request.vector,
+        let res = segment.get().read().search_examples(
+            &request.query,
             request.filter.as_ref(),
             request.top,
             request.params.as_ref(),
@@ -40,6 +97,156 @@ impl SimpleSegmentSearcher {
 
         Ok(res)
     }
+
+    /// Search all requests of the batch under a single segment read lock.
+    /// Requests with the same filter and params are searched together, regardless of their order in the batch.
+    pub async fn search_batch_in_segment(
+        segment: LockedSegment,
+        request: Arc<SearchRequestBatch>,
+        point_vectors: Arc<HashMap<PointIdType, Vec<VectorElementType>>>,
+    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        let segment_arc = segment.get();
+        let read_segment = segment_arc.read();
+
+        // Filters could not be hashed, so groups are looked up linearly
+        let mut groups: Vec<(_, Vec<usize>)> = vec![];
+        for (request_idx, search) in request.searches.iter().enumerate() {
+            let key = (&search.filter, &search.params, search.top, search.vector.point_id());
+            match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+                Some((_, group)) => group.push(request_idx),
+                None => groups.push((key, vec![request_idx])),
+            }
+        }
+
+        let mut results = vec![vec![]; request.searches.len()];
+        for ((filter, params, top, point_id), group) in groups {
+            match point_id.filter(|point_id| read_segment.has_point(*point_id)) {
+                Some(point_id) => {
+                    let result = read_segment.search_by_point(
+                        point_id,
+                        filter.as_ref(),
+                        top,
+                        params.as_ref(),
+                    )?;
+                    for request_idx in group {
+                        results[request_idx] = result.clone();
+                    }
+                }
+                None => {
+                    let vectors = group
+                        .iter()
+                        .map(|request_idx| Self::query_vector(&request.searches[*request_idx].vector, &point_vectors))
+                        .collect_vec();
+                    let group_results = read_segment.search_batch(
+                        &vectors,
+                        filter.as_ref(),
+                        top,
+                        params.as_ref(),
+                    )?;
+                    for (request_idx, result) in group.into_iter().zip(group_results) {
+                        results[request_idx] = result;
+                    }
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Retrieve vectors of the stored points, used by search requests as a query
+    fn fetch_query_point_vectors<'a>(
+        &self,
+        searches: impl Iterator<Item=&'a SearchRequest>,
+    ) -> CollectionResult<HashMap<PointIdType, Vec<VectorElementType>>> {
+        let point_ids = searches
+            .filter_map(|search| search.vector.point_id())
+            .unique()
+            .collect_vec();
+
+        if point_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let point_vectors: HashMap<PointIdType, Vec<VectorElementType>> = self.retrieve(&point_ids, false, true)?
+            .into_iter()
+            .filter_map(|record| {
+                let id = record.id;
+                record.vector.map(|vector| (id, vector))
+            })
+            .collect();
+
+        for point_id in point_ids {
+            if !point_vectors.contains_key(&point_id) {
+                return Err(CollectionError::NotFound { missed_point_id: point_id });
+            }
+        }
+
+        Ok(point_vectors)
+    }
+
+    fn query_vector<'a>(
+        query: &'a RecommendExample,
+        point_vectors: &'a HashMap<PointIdType, Vec<VectorElementType>>,
+    ) -> &'a Vec<VectorElementType> {
+        match query {
+            RecommendExample::PointId(point_id) => &point_vectors[point_id],
+            RecommendExample::Vector(vector) => vector,
+        }
+    }
+
+    /// Merge results of different segments into a single top, skipping duplicated points
+    fn merge_search_results(
+        segment_results: impl Iterator<Item=Vec<ScoredPoint>>,
+        top: usize,
+    ) -> Vec<ScoredPoint> {
+        let mut seen_idx: HashSet<PointIdType> = HashSet::new();
+
+        peek_top_scores_iterable(
+            segment_results
+                .flatten()
+                .filter(|scored| seen_idx.insert(scored.id)),
+            top,
+        )
+    }
+
+    /// Split search results into groups by the value of `group_by` payload field.
+    /// Groups are created in order of the best point score, points without group value are skipped.
+    fn group_hits(&self, hits: &[ScoredPoint], request: &SearchGroupsRequest) -> CollectionResult<Vec<PointGroup>> {
+        let ids = hits.iter().map(|hit| hit.id).collect_vec();
+        let point_group_ids: HashMap<PointIdType, Vec<FacetValue>> = self.retrieve(&ids, true, false)?
+            .into_iter()
+            .map(|record| {
+                let group_ids = record.payload
+                    .as_ref()
+                    .and_then(|payload| payload.get(&request.group_by))
+                    .map(FacetValue::from_payload)
+                    .unwrap_or_default();
+                (record.id, group_ids)
+            })
+            .collect();
+
+        let mut groups: Vec<PointGroup> = vec![];
+        let mut group_positions: HashMap<FacetValue, usize> = HashMap::new();
+
+        for hit in hits {
+            for group_id in point_group_ids.get(&hit.id).into_iter().flatten() {
+                match group_positions.get(group_id) {
+                    Some(&position) => {
+                        let group = &mut groups[position];
+                        if group.hits.len() < request.group_size {
+                            group.hits.push(*hit);
+                        }
+                    }
+                    None => if groups.len() < request.limit {
+                        group_positions.insert(group_id.clone(), groups.len());
+                        groups.push(PointGroup { id: group_id.clone(), hits: vec![*hit] });
+                    }
+                }
+            }
+        }
+
+        Ok(groups)
+    }
 }
 
 impl SegmentSearcher for SimpleSegmentSearcher {
@@ -47,6 +254,9 @@ impl SegmentSearcher for SimpleSegmentSearcher {
         &self,
         request: Arc<SearchRequest>,
     ) -> CollectionResult<Vec<ScoredPoint>> {
+        // Vectors should be retrieved before acquiring segments lock
+        let point_vectors = Arc::new(self.fetch_query_point_vectors(std::iter::once(request.as_ref()))?);
+
         let segments = self.segments.read();
 
         let some_segment = segments.iter().next();
@@ -58,7 +268,7 @@ impl SegmentSearcher for SimpleSegmentSearcher {
         let searches: Vec<_> = segments
             .iter()
             .map(|(_id, segment)|
-                SimpleSegmentSearcher::search_in_segment(segment.clone(), request.clone())
+                SimpleSegmentSearcher::search_in_segment(segment.clone(), request.clone(), point_vectors.clone())
             )
             .map(|f| self.runtime_handle.spawn(f))
             .collect();
@@ -74,24 +284,144 @@ impl SegmentSearcher for SimpleSegmentSearcher {
             Some(error) => return Err(error),
         }
 
-        let mut seen_idx: HashSet<PointIdType> = HashSet::new();
-
-        let top_scores = peek_top_scores_iterable(
+        let top_scores = Self::merge_search_results(
             all_search_results
                 .into_iter()
-                .map(|x| x.unwrap())
-                .flatten()
-                .filter(|scored| {
-                    let res = seen_idx.contains(&scored.id);
-                    seen_idx.insert(scored.id);
-                    !res
-                }),
+                .map(|x| x.unwrap()),
             request.top,
         );
 
         Ok(top_scores)
     }
 
+    fn search_explain(&self, request: Arc<SearchRequest>) -> CollectionResult<SearchExplainResult> {
+        // Vectors should be retrieved before acquiring segments lock
+        let point_vectors = Arc::new(self.fetch_query_point_vectors(std::iter::once(request.as_ref()))?);
+
+        let segments = self.segments.read();
+
+        let searches: Vec<_> = segments
+            .iter()
+            .map(|(_id, segment)|
+                SimpleSegmentSearcher::search_explain_in_segment(segment.clone(), request.clone(), point_vectors.clone())
+            )
+            .map(|f| self.runtime_handle.spawn(f))
+            .collect();
+
+        let all_search_results = self.runtime_handle.block_on(try_join_all(searches))?;
+
+        let mut segment_results = vec![];
+        let mut segment_profiles = vec![];
+        for search_result in all_search_results {
+            let (result, mut profiles) = search_result?;
+            segment_results.push(result);
+            segment_profiles.append(&mut profiles);
+        }
+
+        Ok(SearchExplainResult {
+            result: Self::merge_search_results(segment_results.into_iter(), request.top),
+            segments: segment_profiles,
+        })
+    }
+
+    fn search_batch(&self, request: Arc<SearchRequestBatch>) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        // Vectors should be retrieved before acquiring segments lock
+        let point_vectors = Arc::new(self.fetch_query_point_vectors(request.searches.iter())?);
+
+        let segments = self.segments.read();
+
+        if segments.iter().next().is_none() {
+            return Ok(request.searches.iter().map(|_| vec![]).collect());
+        }
+
+        let searches: Vec<_> = segments
+            .iter()
+            .map(|(_id, segment)|
+                SimpleSegmentSearcher::search_batch_in_segment(segment.clone(), request.clone(), point_vectors.clone())
+            )
+            .map(|f| self.runtime_handle.spawn(f))
+            .collect();
+
+        let all_searches = try_join_all(searches);
+        let mut all_search_results = self.runtime_handle.block_on(all_searches)?
+            .into_iter()
+            .collect::<CollectionResult<Vec<_>>>()?;
+
+        let top_scores = request.searches
+            .iter()
+            .enumerate()
+            .map(|(request_idx, search)| Self::merge_search_results(
+                all_search_results
+                    .iter_mut()
+                    .map(|segment_results| mem::take(&mut segment_results[request_idx])),
+                search.top,
+            ))
+            .collect();
+
+        Ok(top_scores)
+    }
+
+    fn search_groups(&self, request: Arc<SearchGroupsRequest>) -> CollectionResult<SearchGroupsResult> {
+        let mut groups = vec![];
+        // There is no need to search for more hits than there are points
+        let points_count: usize = self.segments
+            .read()
+            .iter()
+            .map(|(_id, segment)| segment.get().read().vectors_count())
+            .sum();
+        let mut top = request.limit.saturating_mul(request.group_size).min(points_count);
+
+        // Groups could be dominated by a few popular values,
+        // so search is repeated with larger `top` until all requested groups are filled
+        for _ in 0..MAX_GROUP_SEARCH_ATTEMPTS {
+            if top == 0 {
+                break;
+            }
+
+            let hits = self.search(Arc::new(SearchRequest {
+                vector: request.vector.clone().into(),
+                filter: request.filter.clone(),
+                params: request.params,
+                top,
+                diversity: None,
+                score_formula: None,
+                explain: None,
+            }))?;
+
+            let is_exhausted = hits.len() < top || top == points_count;
+            groups = self.group_hits(&hits, &request)?;
+
+            let is_filled = groups.len() >= request.limit
+                && groups.iter().all(|group| group.hits.len() >= request.group_size);
+
+            if is_filled || is_exhausted {
+                break;
+            }
+            top = top.saturating_mul(2).min(points_count);
+        }
+
+        Ok(SearchGroupsResult { groups })
+    }
+
+    fn search_examples(&self, request: Arc<ExampleSearchRequest>) -> CollectionResult<Vec<ScoredPoint>> {
+        let segments = self.segments.read();
+
+        let searches: Vec<_> = segments
+            .iter()
+            .map(|(_id, segment)|
+                SimpleSegmentSearcher::search_examples_in_segment(segment.clone(), request.clone())
+            )
+            .map(|f| self.runtime_handle.spawn(f))
+            .collect();
+
+        let all_searches = try_join_all(searches);
+        let all_search_results = self.runtime_handle.block_on(all_searches)?
+            .into_iter()
+            .collect::<CollectionResult<Vec<_>>>()?;
+
+        Ok(Self::merge_search_results(all_search_results.into_iter(), request.top))
+    }
+
     fn retrieve(&self, points: &Vec<PointIdType>, with_payload: bool, with_vector: bool) -> CollectionResult<Vec<Record>> {
         let mut point_version: HashMap<PointIdType, SeqNumberType> = Default::default();
         let mut point_records: HashMap<PointIdType, Record> = Default::default();
@@ -121,6 +451,7 @@ mod tests {
     use crate::segment_manager::fixtures::build_test_holder;
     use tempdir::TempDir;
     use parking_lot::RwLock;
+    use segment::types::SearchPath;
 
     #[test]
     fn test_segments_search() {
@@ -141,10 +472,13 @@ mod tests {
         let query = vec![1.0, 1.0, 1.0, 1.0];
 
         let req = Arc::new(SearchRequest {
-            vector: query,
+            vector: query.into(),
             filter: None,
             params: None,
             top: 5,
+            diversity: None,
+            score_formula: None,
+            explain: None,
         });
 
         let result = searcher.search(req).unwrap();
@@ -153,8 +487,96 @@ mod tests {
 
         assert_eq!(result.len(), 5);
 
-        assert!(result[0].id == 3 || result[0].id == 11);
-        assert!(result[1].id == 3 || result[1].id == 11);
+        assert!(result[0].id == 3.into() || result[0].id == 11.into());
+        assert!(result[1].id == 3.into() || result[1].id == 11.into());
+    }
+
+    #[test]
+    fn test_segments_search_batch() {
+        let dir = TempDir::new("segment_dir").unwrap();
+
+        let segment_holder = build_test_holder(dir.path());
+
+        let threaded_rt1: Runtime = runtime::Builder::new_multi_thread()
+            .max_threads(2)
+            .build().unwrap();
+
+        let searcher = SimpleSegmentSearcher::new(
+            Arc::new(RwLock::new(segment_holder)),
+            Arc::new(threaded_rt1),
+        );
+
+        let searches = vec![
+            SearchRequest { vector: vec![1.0, 1.0, 1.0, 1.0].into(), filter: None, params: None, top: 5, diversity: None, score_formula: None, explain: None },
+            SearchRequest { vector: vec![1.0, 1.0, 1.0, 1.0].into(), filter: None, params: None, top: 2, diversity: None, score_formula: None, explain: None },
+            // Same group as the first request, separated by a different one
+            SearchRequest { vector: vec![1.0, 0.0, 1.0, 1.0].into(), filter: None, params: None, top: 5, diversity: None, score_formula: None, explain: None },
+        ];
+
+        let single_results: Vec<_> = searches.iter()
+            .map(|search| searcher.search(Arc::new(SearchRequest {
+                vector: search.vector.clone(),
+                filter: None,
+                params: None,
+                top: search.top,
+                diversity: None,
+                score_formula: None,
+                explain: None,
+            })).unwrap())
+            .collect();
+
+        let batch_results = searcher.search_batch(Arc::new(SearchRequestBatch { searches })).unwrap();
+
+        assert_eq!(batch_results.len(), 3);
+        assert_eq!(batch_results[1].len(), 2);
+
+        for (batch_result, single_result) in batch_results.iter().zip(single_results.iter()) {
+            let batch_ids = batch_result.iter().map(|x| x.id).collect_vec();
+            let single_ids = single_result.iter().map(|x| x.id).collect_vec();
+            assert_eq!(batch_ids, single_ids);
+        }
+    }
+
+    #[test]
+    fn test_segments_search_explain() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let segment_holder = build_test_holder(dir.path());
+
+        let threaded_rt1: Runtime = runtime::Builder::new_multi_thread()
+            .max_threads(2)
+            .build().unwrap();
+
+        let searcher = SimpleSegmentSearcher::new(
+            Arc::new(RwLock::new(segment_holder)),
+            Arc::new(threaded_rt1),
+        );
+
+        let req = Arc::new(SearchRequest {
+            vector: vec![1.0, 1.0, 1.0, 1.0].into(),
+            filter: None,
+            params: None,
+            top: 5,
+            diversity: None,
+            score_formula: None,
+            explain: Some(true),
+        });
+
+        let result = searcher.search(req.clone()).unwrap();
+        let explained = searcher.search_explain(req).unwrap();
+
+        assert_eq!(
+            explained.result.iter().map(|x| x.id).collect_vec(),
+            result.iter().map(|x| x.id).collect_vec(),
+        );
+
+        assert_eq!(explained.segments.len(), 2);
+        for profile in explained.segments.iter() {
+            assert_eq!(profile.search.path, SearchPath::Plain);
+            assert!(profile.search.cardinality.is_none());
+        }
+        // Without filter all points of both segments are scored
+        let scored_points: usize = explained.segments.iter().map(|x| x.search.scored_points).sum();
+        assert_eq!(scored_points, 12);
     }
 
     #[test]
@@ -171,7 +593,7 @@ mod tests {
             Arc::new(threaded_rt1),
         );
 
-        let records = searcher.retrieve(&vec![1, 2, 3], true, true).unwrap();
+        let records = searcher.retrieve(&vec![1.into(), 2.into(), 3.into()], true, true).unwrap();
 
         assert_eq!(records.len(), 3);
     }
@@ -1,6 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
-use segment::types::{PayloadKeyType, PointIdType, SeqNumberType, PayloadInterface};
+use segment::types::{PayloadKeyType, PointIdType, SeqNumberType, PayloadInterface, Filter, TheMap};
 
 use crate::operations::{CollectionUpdateOperations, FieldIndexOperations};
 use crate::operations::point_ops::{PointInsertOperations, PointOperations};
@@ -43,6 +43,14 @@ impl SimpleSegmentUpdater {
         Ok(res)
     }
 
+    /// Deletes points, which satisfy the filter, in each segment. Returns number of deleted points
+    fn delete_points_by_filter(&self, op_num: SeqNumberType, filter: &Filter) -> CollectionResult<usize> {
+        let res = self.segments.read()
+            .apply_filtered_points(op_num, filter, |id, write_segment|
+                write_segment.delete_point(op_num, id),
+            )?;
+        Ok(res)
+    }
 
     /// Checks point id in each segment, update point if found.
     /// All not found points are inserted into random segment.
@@ -145,6 +153,46 @@ impl SimpleSegmentUpdater {
         Ok(res)
     }
 
+    fn set_payload_by_filter(
+        &self,
+        op_num: SeqNumberType,
+        payload: &HashMap<PayloadKeyType, PayloadInterface>,
+        filter: &Filter,
+    ) -> CollectionResult<usize> {
+        let res = self.segments.read().apply_filtered_points_to_appendable(
+            op_num,
+            filter,
+            |id, write_segment| {
+                let mut res = true;
+                for (key, payload) in payload {
+                    res = write_segment.set_payload(op_num, id, key, payload.into())? && res;
+                }
+                Ok(res)
+            })?;
+        Ok(res)
+    }
+
+    fn update_payload(
+        &self,
+        op_num: SeqNumberType,
+        patch: &TheMap<PayloadKeyType, serde_json::Value>,
+        append: &TheMap<PayloadKeyType, serde_json::Value>,
+        points: &Vec<PointIdType>,
+    ) -> CollectionResult<usize> {
+        let mut updated_points: HashSet<PointIdType> = Default::default();
+
+        let res = self.segments.read().apply_points_to_appendable(
+            op_num,
+            points,
+            |id, write_segment| {
+                updated_points.insert(id);
+                write_segment.update_payload(op_num, id, patch, append)
+            })?;
+
+        SimpleSegmentUpdater::check_unprocessed_points(points, &updated_points)?;
+        Ok(res)
+    }
+
     fn delete_payload(
         &self,
         op_num: SeqNumberType,
@@ -171,6 +219,27 @@ impl SimpleSegmentUpdater {
         Ok(res)
     }
 
+    fn delete_payload_by_filter(
+        &self,
+        op_num: SeqNumberType,
+        filter: &Filter,
+        keys: &Vec<PayloadKeyType>,
+    ) -> CollectionResult<usize> {
+        let res = self.segments
+            .read()
+            .apply_filtered_points_to_appendable(
+                op_num,
+                filter,
+                |id, write_segment| {
+                    let mut res = true;
+                    for key in keys {
+                        res = write_segment.delete_payload(op_num, id, key)? && res;
+                    }
+                    Ok(res)
+                })?;
+        Ok(res)
+    }
+
     fn clear_payload(
         &self,
         op_num: SeqNumberType,
@@ -191,6 +260,20 @@ impl SimpleSegmentUpdater {
         Ok(res)
     }
 
+    fn clear_payload_by_filter(
+        &self,
+        op_num: SeqNumberType,
+        filter: &Filter,
+    ) -> CollectionResult<usize> {
+        let res = self.segments
+            .read()
+            .apply_filtered_points_to_appendable(
+                op_num,
+                filter,
+                |id, write_segment| write_segment.clear_payload(op_num, id))?;
+        Ok(res)
+    }
+
     fn create_field_index(&self, op_num: SeqNumberType, field_name: &PayloadKeyType) -> CollectionResult<usize> {
         let res = self.segments
             .read()
@@ -212,6 +295,7 @@ impl SimpleSegmentUpdater {
     pub fn process_point_operation(&self, op_num: SeqNumberType, point_operation: PointOperations) -> CollectionResult<usize> {
         match point_operation {
             PointOperations::DeletePoints { ids, .. } => self.delete_points(op_num, &ids),
+            PointOperations::DeletePointsByFilter(filter) => self.delete_points_by_filter(op_num, &filter),
             PointOperations::UpsertPoints(operation) => {
                 let (ids, vectors, payloads) = match operation {
                     PointInsertOperations::BatchPoints { ids, vectors, payloads, .. } => {
@@ -251,6 +335,20 @@ impl SimpleSegmentUpdater {
             PayloadOps::ClearPayload {
                 points, ..
             } => self.clear_payload(op_num, points),
+            PayloadOps::SetPayloadByFilter {
+                payload,
+                filter,
+            } => self.set_payload_by_filter(op_num, payload, filter),
+            PayloadOps::DeletePayloadByFilter {
+                keys,
+                filter,
+            } => self.delete_payload_by_filter(op_num, filter, keys),
+            PayloadOps::ClearPayloadByFilter(filter) => self.clear_payload_by_filter(op_num, filter),
+            PayloadOps::UpdatePayload {
+                patch,
+                append,
+                points,
+            } => self.update_payload(op_num, patch, append, points),
         }
     }
 
@@ -280,8 +378,15 @@ impl SegmentUpdater for SimpleSegmentUpdater {
 mod tests {
     use tempdir::TempDir;
 
-    use segment::types::PayloadVariant;
-    use crate::segment_manager::fixtures::build_searcher;
+    use std::sync::Arc;
+
+    use parking_lot::RwLock;
+
+    use segment::entry::entry_point::SegmentEntry;
+    use segment::segment_constructor::segment_constructor::build_segment;
+    use segment::types::{PayloadVariant, PayloadType, Condition, FieldCondition, Match, SegmentConfig, Distance, Indexes, PayloadIndexType, StorageType};
+    use crate::segment_manager::fixtures::{build_searcher, empty_segment};
+    use crate::segment_manager::holders::segment_holder::SegmentHolder;
     use crate::segment_manager::segment_managers::SegmentSearcher;
 
     use super::*;
@@ -295,7 +400,7 @@ mod tests {
         let updater = SimpleSegmentUpdater {
             segments: searcher.segments.clone(),
         };
-        let points = vec![1, 500];
+        let points = vec![1.into(), 500.into()];
 
         let vectors = vec![
             vec![2., 2., 2., 2.],
@@ -314,29 +419,29 @@ mod tests {
             Err(_) => assert!(false),
         };
 
-        let records = searcher.retrieve(&vec![1, 2, 500], true, true).unwrap();
+        let records = searcher.retrieve(&vec![1.into(), 2.into(), 500.into()], true, true).unwrap();
 
         assert_eq!(records.len(), 3);
 
         for record in records {
             let v = record.vector.unwrap();
 
-            if record.id == 1 {
+            if record.id == 1.into() {
                 assert_eq!(&v, &vec![2., 2., 2., 2.])
             }
-            if record.id == 500 {
+            if record.id == 500.into() {
                 assert_eq!(&v, &vec![2., 0., 2., 0.])
             }
         }
 
-        updater.delete_points(101, &vec![500]).unwrap();
+        updater.delete_points(101, &vec![500.into()]).unwrap();
 
-        let records = searcher.retrieve(&vec![1, 2, 500], true, true).unwrap();
+        let records = searcher.retrieve(&vec![1.into(), 2.into(), 500.into()], true, true).unwrap();
 
         for record in records {
             let _v = record.vector.unwrap();
 
-            if record.id == 500 {
+            if record.id == 500.into() {
                 assert!(false)
             }
         }
@@ -358,7 +463,7 @@ mod tests {
             PayloadInterface::KeywordShortcut(PayloadVariant::Value("red".to_string())),
         );
 
-        let points = vec![1, 2, 3];
+        let points = vec![1.into(), 2.into(), 3.into()];
 
         updater.process_payload_operation(100, &PayloadOps::SetPayload {
             payload,
@@ -381,20 +486,230 @@ mod tests {
 
         // Test payload delete
 
-        updater.delete_payload(101, &vec![3], &vec!["color".to_string(), "empty".to_string()]).unwrap();
-        let res = searcher.retrieve(&vec![3], true, false).unwrap();
+        updater.delete_payload(101, &vec![3.into()], &vec!["color".to_string(), "empty".to_string()]).unwrap();
+        let res = searcher.retrieve(&vec![3.into()], true, false).unwrap();
         assert_eq!(res.len(), 1);
         assert!(!res[0].payload.as_ref().unwrap().contains_key("color"));
 
         // Test clear payload
 
-        let res = searcher.retrieve(&vec![2], true, false).unwrap();
+        let res = searcher.retrieve(&vec![2.into()], true, false).unwrap();
         assert_eq!(res.len(), 1);
         assert!(res[0].payload.as_ref().unwrap().contains_key("color"));
 
-        updater.clear_payload(102, &vec![2]).unwrap();
-        let res = searcher.retrieve(&vec![2], true, false).unwrap();
+        updater.clear_payload(102, &vec![2.into()]).unwrap();
+        let res = searcher.retrieve(&vec![2.into()], true, false).unwrap();
         assert_eq!(res.len(), 1);
         assert!(!res[0].payload.as_ref().unwrap().contains_key("color"))
     }
+
+    fn color_filter(color: &str) -> Filter {
+        Filter::new_must(Condition::Field(FieldCondition {
+            key: "color".to_string(),
+            r#match: Some(Match { keyword: Some(color.to_string()), integer: None }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        }))
+    }
+
+    #[test]
+    fn test_ops_by_filter() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let (_rt, searcher) = build_searcher(dir.path());
+
+        let updater = SimpleSegmentUpdater {
+            segments: searcher.segments.clone(),
+        };
+
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "tenant".to_string(),
+            PayloadInterface::KeywordShortcut(PayloadVariant::Value("acme".to_string())),
+        );
+
+        // Points 3, 4 and 5 are blue
+        updater.process_payload_operation(100, &PayloadOps::SetPayloadByFilter {
+            payload,
+            filter: color_filter("blue"),
+        }).unwrap();
+
+        let res = searcher.retrieve(&vec![2.into(), 3.into()], true, false).unwrap();
+        for record in res {
+            let has_tenant = record.payload.as_ref().unwrap().contains_key("tenant");
+            assert_eq!(has_tenant, record.id == 3.into());
+        }
+
+        updater.process_payload_operation(101, &PayloadOps::DeletePayloadByFilter {
+            keys: vec!["tenant".to_string()],
+            filter: color_filter("blue"),
+        }).unwrap();
+        let res = searcher.retrieve(&vec![3.into()], true, false).unwrap();
+        assert!(!res[0].payload.as_ref().unwrap().contains_key("tenant"));
+
+        // Filter is resolved in each segment: copies of points 4 and 5 in the second segment have no color
+        updater.process_point_operation(102, PointOperations::DeletePointsByFilter(color_filter("blue"))).unwrap();
+        let res = searcher.retrieve(&vec![1.into(), 2.into(), 3.into(), 4.into(), 5.into()], false, false).unwrap();
+        let mut remaining = res.iter().map(|record| record.id).collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(remaining, vec![1.into(), 2.into(), 4.into(), 5.into()]);
+
+        updater.process_payload_operation(103, &PayloadOps::ClearPayloadByFilter(color_filter("red"))).unwrap();
+        let res = searcher.retrieve(&vec![1.into(), 2.into()], true, false).unwrap();
+        assert_eq!(res.len(), 2);
+        for record in res {
+            assert!(!record.payload.as_ref().unwrap().contains_key("color"));
+        }
+
+        // Nothing matches anymore, operation is a no-op
+        let updated = updater.process_payload_operation(104, &PayloadOps::ClearPayloadByFilter(color_filter("red"))).unwrap();
+        assert_eq!(updated, 0);
+    }
+
+    #[test]
+    fn test_ops_by_filter_on_indexed_field() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let config = SegmentConfig {
+            vector_size: 4,
+            distance: Distance::Dot,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Struct),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+        let color_key = "color".to_string();
+
+        let mut indexed_segment = build_segment(dir.path(), &config).unwrap();
+        for (point_id, color) in vec![(1, "red"), (2, "blue"), (3, "blue"), (4, "red")] {
+            indexed_segment.upsert_point(1, point_id.into(), &vec![1.0, 0.0, 0.0, 0.0]).unwrap();
+            indexed_segment.set_payload(1, point_id.into(), &color_key, PayloadType::Keyword(vec![color.to_string()])).unwrap();
+        }
+        indexed_segment.create_field_index(2, &color_key).unwrap();
+        assert!(!indexed_segment.is_appendable());
+
+        let mut holder = SegmentHolder::new();
+        holder.add(indexed_segment);
+        holder.add(empty_segment(dir.path()));
+
+        let updater = SimpleSegmentUpdater {
+            segments: Arc::new(RwLock::new(holder)),
+        };
+
+        // Blue points are moved out of the indexed segment, moved points are not matched again in the appendable one
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "shape".to_string(),
+            PayloadInterface::KeywordShortcut(PayloadVariant::Value("square".to_string())),
+        );
+        let updated = updater.process_payload_operation(99, &PayloadOps::SetPayloadByFilter {
+            payload,
+            filter: color_filter("blue"),
+        }).unwrap();
+        assert_eq!(updated, 2);
+
+        // Blue points are re-colored in the appendable segment
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            color_key.clone(),
+            PayloadInterface::KeywordShortcut(PayloadVariant::Value("green".to_string())),
+        );
+        updater.process_payload_operation(100, &PayloadOps::SetPayloadByFilter {
+            payload,
+            filter: color_filter("blue"),
+        }).unwrap();
+
+        // Field index of the indexed segment still has blue values, but they should not be selected
+        let updated = updater.process_payload_operation(101, &PayloadOps::ClearPayloadByFilter(color_filter("blue"))).unwrap();
+        assert_eq!(updated, 0);
+
+        updater.process_point_operation(102, PointOperations::DeletePointsByFilter(color_filter("green"))).unwrap();
+
+        let mut remaining = vec![];
+        updater.segments.read().read_points(&(1..=4).map(PointIdType::from).collect(), |id, segment| {
+            match segment.payload(id)?.get(&color_key) {
+                Some(PayloadType::Keyword(colors)) => assert_eq!(colors, &vec!["red".to_string()]),
+                payload => panic!("unexpected color payload: {:?}", payload),
+            }
+            remaining.push(id);
+            Ok(true)
+        }).unwrap();
+        remaining.sort();
+        assert_eq!(remaining, vec![1.into(), 4.into()]);
+    }
+
+    #[test]
+    fn test_update_payload() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let (_rt, searcher) = build_searcher(dir.path());
+
+        let updater = SimpleSegmentUpdater {
+            segments: searcher.segments.clone(),
+        };
+
+        let patch: TheMap<PayloadKeyType, serde_json::Value> = serde_json::from_str(r#"
+        {
+            "color": null,
+            "source": {"name": "crawler", "rank": 3}
+        }"#).unwrap();
+
+        updater.process_payload_operation(100, &PayloadOps::UpdatePayload {
+            patch,
+            append: Default::default(),
+            points: vec![1.into(), 2.into()],
+        }).unwrap();
+
+        let res = searcher.retrieve(&vec![1.into(), 2.into(), 3.into()], true, false).unwrap();
+        assert_eq!(res.len(), 3);
+        for record in res {
+            let payload = record.payload.unwrap();
+            if record.id == 3.into() {
+                assert!(payload.contains_key("color"));
+                assert!(!payload.contains_key("source__name"));
+            } else {
+                assert!(!payload.contains_key("color"));
+                assert!(payload.contains_key("source__name"));
+                assert!(payload.contains_key("source__rank"));
+            }
+        }
+
+        let append: TheMap<PayloadKeyType, serde_json::Value> = serde_json::from_str(r#"
+        {
+            "source": {"rank": [4, 5]}
+        }"#).unwrap();
+
+        updater.process_payload_operation(101, &PayloadOps::UpdatePayload {
+            patch: Default::default(),
+            append,
+            points: vec![1.into()],
+        }).unwrap();
+
+        let res = searcher.retrieve(&vec![1.into()], true, false).unwrap();
+        match &res[0].payload.as_ref().unwrap()["source__rank"] {
+            PayloadType::Integer(x) => assert_eq!(x, &vec![3, 4, 5]),
+            _ => assert!(false, "Wrong type of appended values"),
+        }
+
+        let patch: TheMap<PayloadKeyType, serde_json::Value> = serde_json::from_str(r#"
+        {
+            "source": {"rank": "high"}
+        }"#).unwrap();
+
+        let res = updater.process_payload_operation(102, &PayloadOps::UpdatePayload {
+            patch,
+            append: Default::default(),
+            points: vec![1.into()],
+        });
+        assert!(res.is_err());
+
+        let patch: TheMap<PayloadKeyType, serde_json::Value> = serde_json::from_str(r#"{"color": "green"}"#).unwrap();
+        let res = updater.process_payload_operation(103, &PayloadOps::UpdatePayload {
+            patch,
+            append: Default::default(),
+            points: vec![1.into(), 100.into()],
+        });
+        match res {
+            Err(CollectionError::NotFound { missed_point_id }) => assert_eq!(missed_point_id, 100.into()),
+            _ => assert!(false, "Missed point is not reported"),
+        }
+    }
 }
@@ -0,0 +1,41 @@
+use itertools::Itertools;
+
+use segment::entry::entry_point::{OperationResult, SegmentEntry};
+use segment::types::{Filter, ScoreType, SimilarPair};
+
+/// Number of points, searched at once in other segments for similar pairs
+const SIMILAR_PAIRS_SEARCH_BATCH: usize = 64;
+
+/// Number of candidates in other segments, verified for each point in similar pairs search
+const SIMILAR_PAIRS_SEARCH_TOP: usize = 16;
+
+/// Find pairs of similar points, one of which is stored in `source` segment and another - in `target`.
+/// Pairs are verified among search results of each `source` point in the `target` segment.
+pub fn cross_segment_pairs(
+    source: &dyn SegmentEntry,
+    source_filter: Option<&Filter>,
+    target: &dyn SegmentEntry,
+    target_filter: Option<&Filter>,
+    threshold: ScoreType,
+    on_pair: &mut dyn FnMut(SimilarPair) -> bool,
+) -> OperationResult<()> {
+    let point_ids = source.read_filtered(0.into(), usize::MAX, source_filter);
+    for chunk in point_ids.chunks(SIMILAR_PAIRS_SEARCH_BATCH) {
+        let vectors = chunk
+            .iter()
+            .map(|point_id| source.vector(*point_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let vector_refs = vectors.iter().collect_vec();
+
+        let results = target.search_batch(&vector_refs, target_filter, SIMILAR_PAIRS_SEARCH_TOP, None)?;
+        for (point_id, result) in chunk.iter().zip(results) {
+            for scored in result {
+                if scored.id != *point_id && scored.score >= threshold
+                    && !on_pair(SimilarPair::new(*point_id, scored.id, scored.score)) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
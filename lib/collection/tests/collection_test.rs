@@ -4,16 +4,22 @@ use collection::operations::CollectionUpdateOperations;
 use collection::operations::point_ops::{PointOperations, PointStruct};
 
 use crate::common::{simple_collection_fixture};
-use collection::operations::types::{UpdateStatus, SearchRequest, RecommendRequest};
+use collection::operations::types::{UpdateStatus, SearchRequest, SearchRequestBatch, RecommendRequest, ScrollRequest, OrderBy, SampleRequest, CountRequest, SearchGroupsRequest, RecommendStrategy, RecommendExample, DiscoverRequest, ContextExamplePair, FacetRequest, FacetHit, HistogramRequest, FusionSearchRequest, SimilarPairsRequest, ClusterRequest, DistanceMatrixRequest};
 use std::sync::Arc;
 use collection::operations::payload_ops::PayloadOps;
+use collection::operations::score_formula::{ScoreFormula, ScoreTerm, ScoreTermExpression, DecayFunction};
+use collection::operations::fusion::FusionMethod;
 use std::collections::HashMap;
-use segment::types::{PayloadKeyType, PayloadVariant, PayloadInterface};
+use segment::types::{PayloadKeyType, PayloadVariant, PayloadInterface, Filter, Condition, FieldCondition, Match, FacetValue, SimilarPair, PayloadType, Direction, SearchPath, PointIdType, HasIdCondition, ScoredPoint};
 use collection::collection_builder::collection_loader::load_collection;
 use tempdir::TempDir;
 use tokio::runtime;
 use collection::operations::point_ops::PointInsertOperations::{BatchPoints, PointsList};
 
+fn point_ids(ids: &[u64]) -> Vec<PointIdType> {
+    ids.iter().map(|id| (*id).into()).collect()
+}
+
 
 #[test]
 fn test_collection_updater() {
@@ -23,7 +29,7 @@ fn test_collection_updater() {
 
     let insert_points = CollectionUpdateOperations::PointOperation(
         PointOperations::UpsertPoints(BatchPoints {
-            ids: vec![0, 1, 2, 3, 4],
+            ids: point_ids(&[0, 1, 2, 3, 4]),
             vectors: vec![
                 vec![1.0, 0.0, 1.0, 1.0],
                 vec![1.0, 0.0, 1.0, 0.0],
@@ -45,10 +51,13 @@ fn test_collection_updater() {
     }
 
     let search_request = Arc::new(SearchRequest {
-        vector: vec![1.0, 1.0, 1.0, 1.0],
+        vector: vec![1.0, 1.0, 1.0, 1.0].into(),
         filter: None,
         params: None,
         top: 3,
+        diversity: None,
+        score_formula: None,
+        explain: None,
     });
 
     let search_res = collection.search(search_request);
@@ -57,7 +66,7 @@ fn test_collection_updater() {
     match search_res {
         Ok(res) => {
             assert_eq!(res.len(), 3);
-            assert_eq!(res[0].id, 2);
+            assert_eq!(res[0].id, 2.into());
         }
         Err(err) => assert!(false, "search failed: {:?}", err),
     }
@@ -73,7 +82,7 @@ fn test_collection_loading() {
 
         let insert_points = CollectionUpdateOperations::PointOperation(
             PointOperations::UpsertPoints(BatchPoints {
-                ids: vec![0, 1, 2, 3, 4],
+                ids: point_ids(&[0, 1, 2, 3, 4]),
                 vectors: vec![
                     vec![1.0, 0.0, 1.0, 1.0],
                     vec![1.0, 0.0, 1.0, 0.0],
@@ -97,7 +106,7 @@ fn test_collection_loading() {
         let assign_payload = CollectionUpdateOperations::PayloadOperation(
             PayloadOps::SetPayload {
                 payload,
-                points: vec![2, 3],
+                points: point_ids(&[2, 3]),
             }
         );
 
@@ -116,12 +125,12 @@ fn test_collection_loading() {
         rt.clone(),
     );
 
-    let retrieved = loaded_collection.retrieve(&vec![1, 2], true, true).unwrap();
+    let retrieved = loaded_collection.retrieve(&point_ids(&[1, 2]), true, true).unwrap();
 
     assert_eq!(retrieved.len(), 2);
 
     for record in retrieved {
-        if record.id == 2 {
+        if record.id == 2.into() {
             let non_empty_payload = record.payload.unwrap();
 
             assert_eq!(non_empty_payload.len(), 1)
@@ -134,7 +143,7 @@ fn test_collection_loading() {
 fn test_deserialization() {
     let insert_points = CollectionUpdateOperations::PointOperation(
         PointOperations::UpsertPoints(BatchPoints {
-            ids: vec![0, 1],
+            ids: point_ids(&[0, 1]),
             vectors: vec![
                 vec![1.0, 0.0, 1.0, 1.0],
                 vec![1.0, 0.0, 1.0, 0.0],
@@ -165,12 +174,12 @@ fn test_deserialization2() {
     let insert_points = CollectionUpdateOperations::PointOperation(
         PointOperations::UpsertPoints(PointsList(vec![
             PointStruct {
-                id: 0,
+                id: 0.into(),
                 vector: vec![1.0, 0.0, 1.0, 1.0],
                 payload: None,
             },
             PointStruct {
-                id: 1,
+                id: 1.into(),
                 vector: vec![1.0, 0.0, 1.0, 0.0],
                 payload: None,
             }
@@ -201,7 +210,7 @@ fn test_recommendation_api() {
 
     let insert_points = CollectionUpdateOperations::PointOperation(
         PointOperations::UpsertPoints(BatchPoints {
-            ids: vec![0, 1, 2, 3, 4, 5, 6, 7, 8],
+            ids: point_ids(&[0, 1, 2, 3, 4, 5, 6, 7, 8]),
             vectors: vec![
                 vec![0.0, 0.0, 1.0, 1.0],
                 vec![1.0, 0.0, 0.0, 0.0],
@@ -220,14 +229,1195 @@ fn test_recommendation_api() {
     collection.update(insert_points, true).unwrap();
 
     let result = collection.recommend(Arc::new(RecommendRequest {
-        positive: vec![0],
-        negative: vec![8],
+        positive: vec![RecommendExample::PointId(0.into())],
+        negative: vec![RecommendExample::PointId(8.into())],
         filter: None,
         params: None,
-        top: 5
+        top: 5,
+        strategy: None,
+        lookup_from: None,
     })).unwrap();
     assert!(result.len() > 0);
     let top1 = result[0];
 
-    assert!(top1.id == 5 || top1.id == 6);
-}
\ No newline at end of file
+    assert!(top1.id == 5.into() || top1.id == 6.into());
+}
+
+
+#[test]
+fn test_recommendation_best_score() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            vectors: vec![
+                vec![0.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let result = collection.recommend(Arc::new(RecommendRequest {
+        positive: vec![RecommendExample::PointId(0.into())],
+        negative: vec![RecommendExample::PointId(8.into())],
+        filter: None,
+        params: None,
+        top: 2,
+        strategy: Some(RecommendStrategy::BestScore),
+        lookup_from: None,
+    })).unwrap();
+
+    let mut found_ids = result.iter().map(|x| x.id).collect::<Vec<_>>();
+    found_ids.sort();
+    assert_eq!(found_ids, point_ids(&[5, 6]));
+
+    // Each point is matched with its own closest example, not with the average
+    let result = collection.recommend(Arc::new(RecommendRequest {
+        positive: vec![RecommendExample::PointId(1.into()), RecommendExample::PointId(3.into())],
+        negative: vec![],
+        filter: None,
+        params: None,
+        top: 2,
+        strategy: Some(RecommendStrategy::BestScore),
+        lookup_from: None,
+    })).unwrap();
+
+    let mut found_ids = result.iter().map(|x| x.id).collect::<Vec<_>>();
+    found_ids.sort();
+    assert_eq!(found_ids, point_ids(&[2, 4]));
+}
+
+
+#[test]
+fn test_recommendation_examples_lookup() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let lookup_dir = TempDir::new("lookup_collection").unwrap();
+    let (_lookup_rt, lookup_collection) = simple_collection_fixture(lookup_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+            ],
+            payloads: None,
+        })
+    );
+    collection.update(insert_points, true).unwrap();
+
+    let insert_lookup_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[2]),
+            vectors: vec![vec![0.0, 1.0, 0.0, 0.0]],
+            payloads: None,
+        })
+    );
+    lookup_collection.update(insert_lookup_points, true).unwrap();
+
+    // Point 2 of the lookup collection is not excluded from the results in current collection
+    let result = collection.recommend_by(Arc::new(RecommendRequest {
+        positive: vec![RecommendExample::PointId(2.into())],
+        negative: vec![],
+        filter: None,
+        params: None,
+        top: 1,
+        strategy: None,
+        lookup_from: Some("lookup".to_string()),
+    }), &lookup_collection).unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, 1.into());
+
+    let result = collection.recommend(Arc::new(RecommendRequest {
+        positive: vec![RecommendExample::PointId(0.into()), RecommendExample::Vector(vec![0.0, 0.0, 0.0, 1.0])],
+        negative: vec![RecommendExample::Vector(vec![1.0, 0.0, 0.0, 0.0])],
+        filter: None,
+        params: None,
+        top: 1,
+        strategy: None,
+        lookup_from: None,
+    })).unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, 3.into());
+
+    // Raw vectors of wrong dimension are rejected, even if averaged with correct ones
+    for (positive, negative) in vec![
+        (vec![0.0, 0.0, 1.0], vec![]),
+        (vec![0.0, 0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0, 0.0, 0.0]),
+    ] {
+        let result = collection.recommend(Arc::new(RecommendRequest {
+            positive: vec![RecommendExample::PointId(0.into()), RecommendExample::Vector(positive)],
+            negative: if negative.is_empty() { vec![] } else { vec![RecommendExample::Vector(negative)] },
+            filter: None,
+            params: None,
+            top: 1,
+            strategy: None,
+            lookup_from: None,
+        }));
+
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn test_uuid_point_ids() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let uuid_a: PointIdType = "4a2b6bd1-4e47-4c3f-8f8c-1d2c3b4a5e6f".parse().unwrap();
+    let uuid_b: PointIdType = "0f3e5d2c-9b8a-4765-8432-10fedcba9876".parse().unwrap();
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: vec![uuid_a, 1.into(), uuid_b],
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.9, 0.1, 0.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+    collection.update(insert_points, true).unwrap();
+
+    let records = collection.retrieve(&vec![uuid_a, 1.into()], true, true).unwrap();
+    assert_eq!(records.len(), 2);
+
+    let result = collection.search(Arc::new(SearchRequest {
+        vector: vec![1.0, 0.0, 0.0, 0.0].into(),
+        filter: None,
+        params: None,
+        top: 2,
+        diversity: None,
+        score_formula: None,
+        explain: None,
+    })).unwrap();
+    assert_eq!(result.iter().map(|x| x.id).collect::<Vec<_>>(), vec![uuid_a, uuid_b]);
+
+    let result = collection.recommend(Arc::new(RecommendRequest {
+        positive: vec![RecommendExample::PointId(uuid_a)],
+        negative: vec![],
+        filter: Some(Filter::new_must_not(Condition::HasId(HasIdCondition { has_id: vec![uuid_b].into_iter().collect() }))),
+        params: None,
+        top: 1,
+        strategy: None,
+        lookup_from: None,
+    })).unwrap();
+    assert_eq!(result[0].id, 1.into());
+
+    // Numeric ids are listed before UUIDs
+    let result = collection.scroll(Arc::new(ScrollRequest {
+        offset: None,
+        limit: Some(10),
+        filter: None,
+        with_payload: Some(false),
+        with_vector: None,
+        order_by: None,
+    })).unwrap();
+    assert_eq!(result.points.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1.into(), uuid_b, uuid_a]);
+
+    let delete_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::DeletePoints { ids: vec![uuid_a] }
+    );
+    collection.update(delete_points, true).unwrap();
+    assert!(collection.retrieve(&vec![uuid_a], false, false).unwrap().is_empty());
+}
+
+
+#[test]
+fn test_read_api() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            vectors: vec![
+                vec![0.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let result = collection.scroll(Arc::new(ScrollRequest {
+        offset: None,
+        limit: Some(2),
+        filter: None,
+        with_payload: Some(true),
+        with_vector: None,
+        order_by: None,
+    })).unwrap();
+
+    assert_eq!(result.next_page_offset, Some(2.into()));
+    assert_eq!(result.points.len(), 2);
+    assert_eq!(result.points[0].id, 0.into());
+    assert_eq!(result.points[1].id, 1.into());
+
+    let result = collection.scroll(Arc::new(ScrollRequest {
+        offset: Some(7.into()),
+        limit: Some(5),
+        filter: None,
+        with_payload: None,
+        with_vector: Some(true),
+        order_by: None,
+    })).unwrap();
+
+    assert_eq!(result.next_page_offset, None);
+    assert_eq!(result.points.len(), 2);
+    assert!(result.points[0].vector.is_some());
+}
+
+#[test]
+fn test_scroll_order_by() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4, 5, 6]),
+            vectors: vec![vec![1.0, 0.0, 0.0, 0.0]; 7],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    // Point 6 has no timestamp and is never listed
+    for (timestamp, point_id) in vec![(30, 0), (10, 1), (20, 2), (20, 3), (50, 4), (40, 5)] {
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "timestamp".to_string(),
+            PayloadInterface::IntShortcut(PayloadVariant::Value(timestamp)),
+        );
+        let assign_payload = CollectionUpdateOperations::PayloadOperation(
+            PayloadOps::SetPayload {
+                payload,
+                points: vec![point_id.into()],
+            }
+        );
+        collection.update(assign_payload, true).unwrap();
+    }
+
+    let newest = collection.scroll(Arc::new(ScrollRequest {
+        offset: None,
+        limit: Some(3),
+        filter: None,
+        with_payload: Some(false),
+        with_vector: None,
+        order_by: Some(OrderBy {
+            key: "timestamp".to_string(),
+            direction: Some(Direction::Desc),
+            start_from: None,
+        }),
+    })).unwrap();
+
+    assert_eq!(newest.points.iter().map(|point| point.id).collect::<Vec<_>>(), point_ids(&[4, 5, 0]));
+    assert_eq!(newest.next_page_start_from, Some(20.0));
+    assert_eq!(newest.next_page_offset, Some(2.into()));
+
+    let next_page = collection.scroll(Arc::new(ScrollRequest {
+        offset: newest.next_page_offset,
+        limit: Some(3),
+        filter: None,
+        with_payload: Some(false),
+        with_vector: None,
+        order_by: Some(OrderBy {
+            key: "timestamp".to_string(),
+            direction: Some(Direction::Desc),
+            start_from: newest.next_page_start_from,
+        }),
+    })).unwrap();
+
+    assert_eq!(next_page.points.iter().map(|point| point.id).collect::<Vec<_>>(), point_ids(&[2, 3, 1]));
+    assert_eq!(next_page.next_page_start_from, None);
+    assert_eq!(next_page.next_page_offset, None);
+
+    let oldest = collection.scroll(Arc::new(ScrollRequest {
+        offset: Some(3.into()),
+        limit: Some(2),
+        filter: None,
+        with_payload: Some(false),
+        with_vector: None,
+        order_by: Some(OrderBy {
+            key: "timestamp".to_string(),
+            direction: None,
+            start_from: Some(20.0),
+        }),
+    })).unwrap();
+
+    assert_eq!(oldest.points.iter().map(|point| point.id).collect::<Vec<_>>(), point_ids(&[3, 0]));
+    assert_eq!(oldest.next_page_start_from, Some(40.0));
+    assert_eq!(oldest.next_page_offset, Some(5.into()));
+}
+
+#[test]
+fn test_order_by_multiple_values() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![vec![1.0, 0.0, 0.0, 0.0]; 4],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    // Point 0 is placed by its first value in the direction only
+    for (timestamps, point_id) in vec![(vec![10, 40], 0), (vec![20], 1), (vec![30], 2)] {
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "timestamp".to_string(),
+            PayloadInterface::IntShortcut(PayloadVariant::List(timestamps)),
+        );
+        let assign_payload = CollectionUpdateOperations::PayloadOperation(
+            PayloadOps::SetPayload {
+                payload,
+                points: vec![point_id.into()],
+            }
+        );
+        collection.update(assign_payload, true).unwrap();
+    }
+
+    let mut scrolled_ids = vec![];
+    let mut offset = None;
+    let mut start_from = None;
+    loop {
+        let page = collection.scroll(Arc::new(ScrollRequest {
+            offset,
+            limit: Some(1),
+            filter: None,
+            with_payload: Some(false),
+            with_vector: None,
+            order_by: Some(OrderBy {
+                key: "timestamp".to_string(),
+                direction: None,
+                start_from,
+            }),
+        })).unwrap();
+        scrolled_ids.extend(page.points.iter().map(|point| point.id));
+        if page.next_page_offset.is_none() {
+            break;
+        }
+        offset = page.next_page_offset;
+        start_from = page.next_page_start_from;
+    }
+    assert_eq!(scrolled_ids, point_ids(&[0, 1, 2]));
+
+    let order_by = OrderBy {
+        key: "timestamp".to_string(),
+        direction: Some(Direction::Desc),
+        start_from: None,
+    };
+    let retrieved = collection.retrieve_ordered(&point_ids(&[1, 3, 2, 0]), &order_by, false, false).unwrap();
+    assert_eq!(retrieved.iter().map(|point| point.id).collect::<Vec<_>>(), point_ids(&[0, 2, 1]));
+    assert!(retrieved[0].payload.is_none());
+}
+
+
+#[test]
+fn test_count_api() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4]),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+
+    payload.insert(
+        "color".to_string(),
+        PayloadInterface::KeywordShortcut(PayloadVariant::Value("red".to_string())),
+    );
+
+    let assign_payload = CollectionUpdateOperations::PayloadOperation(
+        PayloadOps::SetPayload {
+            payload,
+            points: point_ids(&[2, 3]),
+        }
+    );
+
+    collection.update(assign_payload, true).unwrap();
+
+    let red_filter = Filter::new_must(Condition::Field(FieldCondition {
+        key: "color".to_string(),
+        r#match: Some(Match { keyword: Some("red".to_string()), integer: None }),
+        range: None,
+        geo_bounding_box: None,
+        geo_radius: None,
+    }));
+
+    let result = collection.count(Arc::new(CountRequest {
+        filter: None,
+        exact: Some(true),
+    })).unwrap();
+    assert_eq!(result.count, 5);
+
+    let result = collection.count(Arc::new(CountRequest {
+        filter: Some(red_filter.clone()),
+        exact: None,
+    })).unwrap();
+    assert_eq!(result.count, 2);
+    assert_eq!(result.min, None);
+
+    let result = collection.count(Arc::new(CountRequest {
+        filter: Some(red_filter),
+        exact: Some(false),
+    })).unwrap();
+    assert!(result.min.unwrap() <= 2);
+    assert!(result.max.unwrap() >= 2);
+}
+
+
+#[test]
+fn test_search_groups() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4, 5]),
+            vectors: vec![
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 1.0, 0.0],
+                vec![1.0, 1.0, 0.0, 0.0],
+                vec![0.5, 1.0, 1.0, 1.0],
+                vec![0.5, 0.5, 0.0, 0.0],
+                vec![1.0, 1.0, 0.5, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    for (doc, points) in vec![("a", point_ids(&[0, 1, 2])), ("b", point_ids(&[3, 4])), ("c", point_ids(&[5]))] {
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "doc".to_string(),
+            PayloadInterface::KeywordShortcut(PayloadVariant::Value(doc.to_string())),
+        );
+        let assign_payload = CollectionUpdateOperations::PayloadOperation(
+            PayloadOps::SetPayload { payload, points }
+        );
+        collection.update(assign_payload, true).unwrap();
+    }
+
+    let result = collection.search_groups(Arc::new(SearchGroupsRequest {
+        vector: vec![1.0, 1.0, 1.0, 1.0],
+        filter: None,
+        params: None,
+        group_by: "doc".to_string(),
+        group_size: 2,
+        limit: 2,
+    })).unwrap();
+
+    assert_eq!(result.groups.len(), 2);
+
+    assert_eq!(result.groups[0].id, FacetValue::Keyword("a".to_string()));
+    assert_eq!(result.groups[0].hits.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[0, 1]));
+
+    assert_eq!(result.groups[1].id, FacetValue::Keyword("b".to_string()));
+    assert_eq!(result.groups[1].hits.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[3, 4]));
+}
+
+
+#[test]
+fn test_discovery_api() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.9, 0.1, 0.0, 0.0],
+                vec![0.9, 0.0, 0.1, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let result = collection.discover(Arc::new(DiscoverRequest {
+        target: RecommendExample::PointId(0.into()),
+        context: vec![ContextExamplePair {
+            positive: RecommendExample::PointId(4.into()),
+            negative: RecommendExample::PointId(3.into()),
+        }],
+        filter: None,
+        params: None,
+        top: 2,
+        lookup_from: None,
+    })).unwrap();
+
+    assert_eq!(result.len(), 2);
+    // Point 2 is on the positive side of the context pair and closest to the target
+    assert_eq!(result[0].id, 2.into());
+    // Point 1 is closer to the target, but on the negative side of the pair
+    assert_eq!(result[1].id, 1.into());
+}
+
+
+#[test]
+fn test_facet_and_histogram_api() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4]),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    for (color, price, points) in vec![("red", 10, point_ids(&[0, 1, 2])), ("blue", 20, point_ids(&[3])), ("green", 40, point_ids(&[4]))] {
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "color".to_string(),
+            PayloadInterface::KeywordShortcut(PayloadVariant::Value(color.to_string())),
+        );
+        payload.insert(
+            "price".to_string(),
+            PayloadInterface::IntShortcut(PayloadVariant::Value(price)),
+        );
+        let assign_payload = CollectionUpdateOperations::PayloadOperation(
+            PayloadOps::SetPayload { payload, points }
+        );
+        collection.update(assign_payload, true).unwrap();
+    }
+
+    let facet = collection.facet(Arc::new(FacetRequest {
+        key: "color".to_string(),
+        filter: None,
+        limit: Some(2),
+    })).unwrap();
+
+    assert_eq!(facet.hits, vec![
+        FacetHit { value: FacetValue::Keyword("red".to_string()), count: 3 },
+        FacetHit { value: FacetValue::Keyword("blue".to_string()), count: 1 },
+    ]);
+
+    let histogram = collection.histogram(Arc::new(HistogramRequest {
+        key: "price".to_string(),
+        filter: None,
+        buckets: Some(3),
+    })).unwrap();
+
+    assert_eq!(histogram.min, Some(10.0));
+    assert_eq!(histogram.max, Some(40.0));
+    assert_eq!(histogram.buckets.iter().map(|x| x.count).collect::<Vec<_>>(), vec![3, 1, 1]);
+    assert_eq!(histogram.buckets[1].from, 20.0);
+    assert_eq!(histogram.buckets[2].to, 40.0);
+
+    let missing = collection.histogram(Arc::new(HistogramRequest {
+        key: "weight".to_string(),
+        filter: None,
+        buckets: None,
+    })).unwrap();
+
+    assert_eq!(missing.min, None);
+    assert!(missing.buckets.is_empty());
+}
+
+
+#[test]
+fn test_diversified_search() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![1.0, 0.05, 0.0, 0.0],
+                vec![0.4, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let search = |diversity| collection.search(Arc::new(SearchRequest {
+        vector: vec![1.0, 0.5, 0.0, 0.0].into(),
+        filter: None,
+        params: None,
+        top: 2,
+        diversity,
+        score_formula: None,
+        explain: None,
+    }));
+
+    let relevant = search(None).unwrap();
+    assert_eq!(relevant.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[1, 0]));
+
+    // Point 0 is a near-duplicate of point 1, so less relevant point 2 is preferred
+    let diverse = search(Some(0.5)).unwrap();
+    assert_eq!(diverse.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[1, 2]));
+
+    assert!(search(Some(1.5)).is_err());
+}
+
+
+#[test]
+fn test_search_score_formula() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.9, 0.0, 0.0, 0.0],
+                vec![0.8, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    for (rating, point) in vec![(2, 1), (5, 2), (3, 3)] {
+        let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+        payload.insert(
+            "rating".to_string(),
+            PayloadInterface::IntShortcut(PayloadVariant::Value(rating)),
+        );
+        let assign_payload = CollectionUpdateOperations::PayloadOperation(
+            PayloadOps::SetPayload { payload, points: vec![point.into()] }
+        );
+        collection.update(assign_payload, true).unwrap();
+    }
+
+    let search = |terms| collection.search(Arc::new(SearchRequest {
+        vector: vec![1.0, 0.0, 0.0, 0.0].into(),
+        filter: None,
+        params: None,
+        top: 2,
+        diversity: None,
+        score_formula: Some(ScoreFormula { terms, oversampling: None }),
+        explain: None,
+    }));
+
+    let boosted = search(vec![ScoreTerm {
+        weight: 0.1,
+        expression: ScoreTermExpression::Field { key: "rating".to_string() },
+    }]).unwrap();
+
+    // Better rated points overtake the most similar point 0
+    assert_eq!(boosted.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[2, 1]));
+    assert!((boosted[0].score - 1.3).abs() < 1e-5);
+
+    let decayed = search(vec![ScoreTerm {
+        weight: 1.0,
+        expression: ScoreTermExpression::NumericDecay {
+            key: "rating".to_string(),
+            origin: 1.0,
+            scale: 1.0,
+            function: DecayFunction::Gauss,
+        },
+    }]).unwrap();
+
+    assert_eq!(decayed.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[1, 0]));
+
+    let invalid_scale = search(vec![ScoreTerm {
+        weight: 1.0,
+        expression: ScoreTermExpression::NumericDecay {
+            key: "rating".to_string(),
+            origin: 1.0,
+            scale: 0.0,
+            function: DecayFunction::Exp,
+        },
+    }]);
+
+    assert!(invalid_scale.is_err());
+}
+
+
+#[test]
+fn test_search_fusion() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.7, 0.7, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![-0.1, -0.1, 1.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let search = |vector: Vec<f32>| SearchRequest {
+        vector: vector.into(),
+        filter: None,
+        params: None,
+        top: 3,
+        diversity: None,
+        score_formula: None,
+        explain: None,
+    };
+
+    for fusion in vec![None, Some(FusionMethod::Rrf), Some(FusionMethod::ScoreSum)] {
+        let result = collection.search_fusion(Arc::new(FusionSearchRequest {
+            searches: vec![
+                search(vec![1.0, 0.0, 0.0, 0.0]),
+                search(vec![0.6, 0.8, 0.0, 0.0]),
+            ],
+            fusion,
+            top: 2,
+        })).unwrap();
+
+        assert_eq!(result.len(), 2);
+        // Point 1 is close to both queries
+        assert_eq!(result[0].id, 1.into(), "{:?}", fusion);
+    }
+}
+
+
+#[test]
+fn test_search_by_point_id() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.9, 0.1, 0.0, 0.0],
+                vec![0.5, 0.0, 0.5, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let search = |vector: RecommendExample| SearchRequest {
+        vector,
+        filter: None,
+        params: None,
+        top: 2,
+        diversity: None,
+        score_formula: None,
+        explain: None,
+    };
+
+    let result = collection.search(Arc::new(search(RecommendExample::PointId(0.into())))).unwrap();
+
+    // Point itself is not included
+    assert_eq!(result.iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[2, 3]));
+
+    let batch_result = collection.search_batch(Arc::new(SearchRequestBatch {
+        searches: vec![
+            search(RecommendExample::PointId(0.into())),
+            search(RecommendExample::Vector(vec![1.0, 0.0, 0.0, 0.0])),
+        ]
+    })).unwrap();
+
+    assert_eq!(batch_result[0], result);
+    assert_eq!(batch_result[1].iter().map(|x| x.id).collect::<Vec<_>>(), point_ids(&[0, 2]));
+
+    let missing = collection.search(Arc::new(search(RecommendExample::PointId(100.into()))));
+    assert!(missing.is_err());
+}
+
+
+#[test]
+fn test_similar_pairs() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.9, 0.1, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let mut pairs = vec![];
+    collection.similar_pairs(Arc::new(SimilarPairsRequest {
+        threshold: 0.85,
+        filter: None,
+        limit: None,
+    }), |pair| {
+        pairs.push(pair);
+        true
+    }).unwrap();
+    pairs.sort_by_key(|pair| pair.a);
+
+    assert_eq!(pairs, vec![
+        SimilarPair { a: 0.into(), b: 2.into(), score: 1.0 },
+        SimilarPair { a: 1.into(), b: 3.into(), score: 0.9 },
+    ]);
+
+    let mut limited = vec![];
+    collection.similar_pairs(Arc::new(SimilarPairsRequest {
+        threshold: 0.85,
+        filter: None,
+        limit: Some(1),
+    }), |pair| {
+        limited.push(pair);
+        true
+    }).unwrap();
+
+    assert_eq!(limited.len(), 1);
+}
+
+#[test]
+fn test_kmeans_clustering() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3, 4, 5]),
+            vectors: vec![
+                vec![1.0, 0.1, 0.0, 0.0],
+                vec![0.9, 0.0, 0.1, 0.0],
+                vec![1.0, 0.0, 0.0, 0.1],
+                vec![0.0, 0.0, 1.0, 0.1],
+                vec![0.1, 0.0, 0.9, 0.0],
+                vec![0.0, 0.1, 1.0, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let request = ClusterRequest {
+        clusters: 2,
+        filter: None,
+        batch_size: Some(4),
+        iterations: Some(10),
+        payload_key: Some("cluster".to_string()),
+    };
+
+    let result = collection.cluster(Arc::new(request)).unwrap();
+
+    assert_eq!(result.centroids.len(), 2);
+    assert!(result.centroids.iter().all(|centroid| centroid.len() == 4));
+
+    let sizes = result.sizes.unwrap();
+    assert_eq!(sizes.iter().sum::<usize>(), 6);
+
+    let records = collection.retrieve(&point_ids(&[0, 1, 2, 3, 4, 5]), true, false).unwrap();
+    let mut assigned = vec![0; 2];
+    let mut point_clusters = HashMap::new();
+    for record in records {
+        match record.payload.unwrap().get("cluster") {
+            Some(PayloadType::Integer(cluster)) => {
+                assigned[cluster[0] as usize] += 1;
+                point_clusters.insert(record.id, cluster[0]);
+            }
+            payload => panic!("unexpected cluster payload: {:?}", payload),
+        }
+    }
+    assert_eq!(assigned, sizes);
+    assert_eq!(sizes, vec![3, 3]);
+
+    let cluster_of = |point_id: u64| point_clusters[&point_id.into()];
+    assert!([1, 2].iter().all(|point_id| cluster_of(*point_id) == cluster_of(0)));
+    assert!([4, 5].iter().all(|point_id| cluster_of(*point_id) == cluster_of(3)));
+    assert_ne!(cluster_of(0), cluster_of(3));
+
+    let too_many = collection.cluster(Arc::new(ClusterRequest {
+        clusters: 10,
+        filter: None,
+        batch_size: None,
+        iterations: None,
+        payload_key: None,
+    }));
+    assert!(too_many.is_err());
+
+    let too_long = collection.cluster(Arc::new(ClusterRequest {
+        clusters: 2,
+        filter: None,
+        batch_size: None,
+        iterations: Some(usize::MAX),
+        payload_key: None,
+    }));
+    assert!(too_long.is_err());
+}
+
+#[test]
+fn test_distance_matrix() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: point_ids(&[0, 1, 2, 3]),
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.9, 0.2, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.5, 0.4, 0.5, 0.0],
+            ],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let matrix = collection.distance_matrix(Arc::new(DistanceMatrixRequest {
+        sample: 10,
+        limit: Some(2),
+        filter: None,
+    })).unwrap();
+
+    assert_eq!(matrix.sample_ids, point_ids(&[0, 1, 2, 3]));
+    assert_eq!(matrix.offsets, vec![0, 2, 4, 6, 8]);
+    assert_eq!(matrix.ids, point_ids(&[1, 3, 0, 3, 3, 1, 1, 0]));
+    assert!((matrix.scores[0] - 0.9).abs() < 1e-5);
+
+    let sampled = collection.distance_matrix(Arc::new(DistanceMatrixRequest {
+        sample: 2,
+        limit: None,
+        filter: None,
+    })).unwrap();
+
+    assert_eq!(sampled.sample_ids.len(), 2);
+    assert_eq!(sampled.offsets, vec![0, 1, 2]);
+    assert_eq!(sampled.ids, vec![sampled.sample_ids[1], sampled.sample_ids[0]]);
+
+    let too_large = collection.distance_matrix(Arc::new(DistanceMatrixRequest {
+        sample: usize::MAX,
+        limit: None,
+        filter: None,
+    }));
+    assert!(too_large.is_err());
+}
+
+#[test]
+fn test_sample_points() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: (0..10u64).map(PointIdType::from).collect(),
+            vectors: vec![vec![1.0, 0.0, 0.0, 0.0]; 10],
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+    payload.insert(
+        "color".to_string(),
+        PayloadInterface::KeywordShortcut(PayloadVariant::Value("red".to_string())),
+    );
+    let assign_payload = CollectionUpdateOperations::PayloadOperation(
+        PayloadOps::SetPayload {
+            payload,
+            points: point_ids(&[2, 3, 5]),
+        }
+    );
+    collection.update(assign_payload, true).unwrap();
+
+    let red_filter = Filter::new_must(Condition::Field(FieldCondition {
+        key: "color".to_string(),
+        r#match: Some(Match { keyword: Some("red".to_string()), integer: None }),
+        range: None,
+        geo_bounding_box: None,
+        geo_radius: None,
+    }));
+
+    let red_sample = collection.sample(Arc::new(SampleRequest {
+        limit: 2,
+        filter: Some(red_filter),
+        with_payload: None,
+        with_vector: None,
+    })).unwrap();
+
+    assert_eq!(red_sample.len(), 2);
+    assert_ne!(red_sample[0].id, red_sample[1].id);
+    for record in &red_sample {
+        assert!(point_ids(&[2, 3, 5]).contains(&record.id));
+        assert!(record.payload.as_ref().unwrap().contains_key("color"));
+        assert!(record.vector.is_none());
+    }
+
+    let full_sample = collection.sample(Arc::new(SampleRequest {
+        limit: 100,
+        filter: None,
+        with_payload: Some(false),
+        with_vector: Some(true),
+    })).unwrap();
+
+    let mut sampled_ids = full_sample.iter().map(|record| record.id).collect::<Vec<_>>();
+    sampled_ids.sort();
+    assert_eq!(sampled_ids, (0..10u64).map(PointIdType::from).collect::<Vec<_>>());
+    assert!(full_sample[0].vector.is_some());
+
+    let too_many = collection.sample(Arc::new(SampleRequest {
+        limit: 1_000_000,
+        filter: None,
+        with_payload: None,
+        with_vector: None,
+    }));
+    assert!(too_many.is_err());
+}
+
+
+#[test]
+fn test_search_explain() {
+    let collection_dir = TempDir::new("collection").unwrap();
+    let (_rt, collection) = simple_collection_fixture(collection_dir.path());
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::UpsertPoints(BatchPoints {
+            ids: (0..10u64).map(PointIdType::from).collect(),
+            vectors: (0..10).map(|i| vec![1.0, i as f32 / 10.0, 0.0, 0.0]).collect(),
+            payloads: None,
+        })
+    );
+
+    collection.update(insert_points, true).unwrap();
+
+    let mut payload: HashMap<PayloadKeyType, PayloadInterface> = Default::default();
+    payload.insert(
+        "color".to_string(),
+        PayloadInterface::KeywordShortcut(PayloadVariant::Value("red".to_string())),
+    );
+    let assign_payload = CollectionUpdateOperations::PayloadOperation(
+        PayloadOps::SetPayload {
+            payload,
+            points: point_ids(&[2, 3, 5]),
+        }
+    );
+    collection.update(assign_payload, true).unwrap();
+
+    let request = |explain| SearchRequest {
+        vector: RecommendExample::PointId(2.into()),
+        filter: Some(Filter::new_must(Condition::Field(FieldCondition {
+            key: "color".to_string(),
+            r#match: Some(Match { keyword: Some("red".to_string()), integer: None }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        }))),
+        params: None,
+        top: 3,
+        diversity: None,
+        score_formula: None,
+        explain,
+    };
+
+    let result = collection.search(Arc::new(request(None))).unwrap();
+    let explained = collection.search_explain(Arc::new(request(Some(true)))).unwrap();
+
+    // Query point itself is excluded
+    let explained_ids = explained.result.iter().map(|x| x.id).collect::<Vec<_>>();
+    assert_eq!(explained_ids, point_ids(&[5, 3]));
+    assert_eq!(explained_ids, result.iter().map(|x| x.id).collect::<Vec<_>>());
+
+    assert!(!explained.segments.is_empty());
+    for profile in explained.segments.iter() {
+        assert_eq!(profile.search.path, SearchPath::Plain);
+        assert!(profile.search.cardinality.is_some());
+    }
+    // Only points, which satisfy filter, are scored. Query point itself is skipped
+    let scored_points: usize = explained.segments.iter().map(|x| x.search.scored_points).sum();
+    assert_eq!(scored_points, 2);
+
+    // Explained search returns exactly the same points and scores as regular search
+    for vector in vec![RecommendExample::PointId(2.into()), RecommendExample::Vector(vec![1.0, 0.3, 0.0, 0.0])] {
+        for filter in vec![None, request(None).filter] {
+            let request = |explain| SearchRequest {
+                vector: vector.clone(),
+                filter: filter.clone(),
+                params: None,
+                top: 4,
+                diversity: None,
+                score_formula: None,
+                explain,
+            };
+            let result = collection.search(Arc::new(request(None))).unwrap();
+            let explained = collection.search_explain(Arc::new(request(Some(true)))).unwrap();
+
+            let scored = |points: &[ScoredPoint]| points.iter().map(|x| (x.id, x.score)).collect::<Vec<_>>();
+            assert_eq!(scored(&explained.result), scored(&result));
+        }
+    }
+}
@@ -45,6 +45,7 @@ pub fn simple_collection_fixture(collection_path: &Path) -> (Arc<Runtime>, Colle
     let collection_params = CollectionParams {
         vector_size: 4,
         distance: Distance::Dot,
+        payload_storage_type: Default::default(),
     };
 
     let threaded_rt = Arc::new(runtime::Builder::new_multi_thread()
@@ -0,0 +1,165 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::mem::{size_of, size_of_val};
+use std::path::Path;
+
+use memmap::{Mmap, MmapOptions};
+
+use crate::common::error_logging::LogError;
+use crate::entry::entry_point::{OperationError, OperationResult};
+
+const HEADER: &[u8; 8] = b"mmaplayt";
+/// Sections are aligned, so arrays of any primitive type could be read from the mmap in place
+const ALIGNMENT: usize = 8;
+/// Number of elements and size of the section in bytes
+const SECTION_HEADER_SIZE: usize = 2 * size_of::<u64>();
+
+fn as_bytes<T: Copy>(slice: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(slice.as_ptr() as *const u8, size_of_val(slice)) }
+}
+
+fn padding(size: usize) -> usize {
+    (ALIGNMENT - size % ALIGNMENT) % ALIGNMENT
+}
+
+/// Writer of the immutable file, which is read with `MmapLayout`.
+/// File consists of a header with the kind of the stored structure, followed by sections of plain arrays.
+pub struct MmapLayoutWriter {
+    data: Vec<u8>,
+}
+
+impl MmapLayoutWriter {
+    pub fn new(kind: u64) -> Self {
+        let mut data = HEADER.to_vec();
+        data.extend_from_slice(as_bytes(&[kind]));
+        MmapLayoutWriter { data }
+    }
+
+    /// Write section of `len` elements, already encoded into `bytes`
+    pub fn write_section(&mut self, len: usize, bytes: &[u8]) {
+        self.data.extend_from_slice(as_bytes(&[len as u64, bytes.len() as u64]));
+        self.data.extend_from_slice(bytes);
+        self.data.resize(self.data.len() + padding(bytes.len()), 0);
+    }
+
+    /// Write array of primitive values. `T` should not contain padding bytes
+    pub fn write_slice<T: Copy>(&mut self, slice: &[T]) {
+        self.write_section(slice.len(), as_bytes(slice))
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        let mut file = File::create(path)?;
+        file.write_all(&self.data)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Read-only view of the file, written by `MmapLayoutWriter`.
+/// Sections are accessed directly in the mmap without copying
+pub struct MmapLayout {
+    mmap: Mmap,
+    kind: u64,
+    /// Number of elements, offset and size in bytes of each section
+    sections: Vec<(usize, usize, usize)>,
+}
+
+impl MmapLayout {
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(false)
+            .open(path)
+            .describe("Open mmap layout file")?;
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+
+        let header_size = HEADER.len() + size_of::<u64>();
+        if mmap.len() < header_size || &mmap[..HEADER.len()] != HEADER {
+            return Err(OperationError::ServiceError {
+                description: format!("Wrong mmap layout file: {}", path.to_str().unwrap())
+            });
+        }
+
+        let mut layout = MmapLayout {
+            mmap,
+            kind: 0,
+            sections: vec![],
+        };
+        layout.kind = layout.read_u64(HEADER.len());
+
+        let mut offset = header_size;
+        while offset + SECTION_HEADER_SIZE <= layout.mmap.len() {
+            let len = layout.read_u64(offset) as usize;
+            let size = layout.read_u64(offset + size_of::<u64>()) as usize;
+            let data_offset = offset + SECTION_HEADER_SIZE;
+            if data_offset + size > layout.mmap.len() {
+                return Err(OperationError::ServiceError {
+                    description: format!("Truncated mmap layout file: {}", path.to_str().unwrap())
+                });
+            }
+            layout.sections.push((len, data_offset, size));
+            offset = data_offset + size + padding(size);
+        }
+
+        Ok(layout)
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        let mut bytes = [0u8; size_of::<u64>()];
+        bytes.copy_from_slice(&self.mmap[offset..offset + size_of::<u64>()]);
+        u64::from_ne_bytes(bytes)
+    }
+
+    /// Copy file into a new location
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        let mut file = File::create(path)?;
+        file.write_all(&self.mmap)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn kind(&self) -> u64 {
+        self.kind
+    }
+
+    pub fn sections_count(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Array of the section `idx`. `T` should match the type, used for writing
+    pub fn section<T: Copy>(&self, idx: usize) -> &[T] {
+        let (len, offset, size) = self.sections[idx];
+        assert_eq!(len * size_of::<T>(), size, "Wrong type of mmap layout section");
+        // Mmap is page-aligned and sections are aligned to `ALIGNMENT`
+        unsafe { std::slice::from_raw_parts(self.mmap[offset..].as_ptr() as *const T, len) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_layout() {
+        let dir = TempDir::new("mmap_layout_dir").unwrap();
+        let path = dir.path().join("test.dat");
+
+        let mut writer = MmapLayoutWriter::new(42);
+        writer.write_slice(&[1u32, 2, 3]);
+        writer.write_slice::<u64>(&[]);
+        writer.write_slice(&b"abcde".to_vec());
+        writer.write_slice(&[1.5f64, -2.0]);
+        writer.save(&path).unwrap();
+
+        let layout = MmapLayout::open(&path).unwrap();
+        assert_eq!(layout.kind(), 42);
+        assert_eq!(layout.sections_count(), 4);
+        assert_eq!(layout.section::<u32>(0), &[1, 2, 3]);
+        assert!(layout.section::<u64>(1).is_empty());
+        assert_eq!(layout.section::<u8>(2), b"abcde");
+        assert_eq!(layout.section::<f64>(3), &[1.5, -2.0]);
+    }
+}
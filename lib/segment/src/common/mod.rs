@@ -1,3 +1,4 @@
 pub mod file_operations;
 pub mod error_logging;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod mmap_layout;
\ No newline at end of file
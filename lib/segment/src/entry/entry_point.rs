@@ -1,10 +1,12 @@
 use thiserror::Error;
 use std::path::Path;
-use crate::types::{SeqNumberType, VectorElementType, Filter, PointIdType, PayloadKeyType, PayloadType, SearchParams, ScoredPoint, TheMap, SegmentInfo, SegmentConfig, SegmentType};
+use crate::types::{SeqNumberType, VectorElementType, Filter, PointIdType, PayloadKeyType, PayloadType, SearchParams, ScoredPoint, TheMap, SegmentInfo, SegmentConfig, SegmentType, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, SimilarPair, Direction, SegmentSearchProfile};
+use std::collections::HashMap;
 use std::result;
 use std::io::Error as IoError;
 use atomicwrites::Error as AtomicIoError;
 use rocksdb::Error;
+use crate::index::field_index::CardinalityEstimation;
 
 
 /// Trait for versionable & saveable objects.
@@ -76,6 +78,51 @@ pub trait SegmentEntry {
               params: Option<&SearchParams>,
     ) -> OperationResult<Vec<ScoredPoint>>;
 
+    /// Same as `search`, but also returns profiles of all underlying segments
+    fn search_explain(&self,
+                      vector: &[VectorElementType],
+                      filter: Option<&Filter>,
+                      top: usize,
+                      params: Option<&SearchParams>,
+    ) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)>;
+
+    /// Search for multiple vectors with the same filter and params.
+    /// Returns a list of results for each vector in the same order.
+    fn search_batch(&self,
+                    vectors: &[&Vec<VectorElementType>],
+                    filter: Option<&Filter>,
+                    top: usize,
+                    params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<Vec<ScoredPoint>>>;
+
+    /// Search for points, best scored against multiple examples of the query
+    fn search_examples(&self,
+                       query: &ExampleQuery,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>>;
+
+    /// Search for points closest to the stored point. The point itself is excluded from the result
+    fn search_by_point(&self,
+                       point_id: PointIdType,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>>;
+
+    /// Same as `search_by_point`, but also returns profiles of all underlying segments
+    fn search_by_point_explain(&self,
+                               point_id: PointIdType,
+                               filter: Option<&Filter>,
+                               top: usize,
+                               params: Option<&SearchParams>,
+    ) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)>;
+
+    /// Find at most `limit` pairs of points within this segment with similarity not less than `threshold`.
+    /// Candidates are taken from the vector index, so the result could be approximate
+    fn similar_pairs(&self, threshold: ScoreType, filter: Option<&Filter>, limit: usize) -> OperationResult<Vec<SimilarPair>>;
+
     fn upsert_point(&mut self, op_num: SeqNumberType, point_id: PointIdType, vector: &Vec<VectorElementType>) -> OperationResult<bool>;
 
     fn delete_point(&mut self, op_num: SeqNumberType, point_id: PointIdType) -> OperationResult<bool>;
@@ -84,6 +131,15 @@ pub trait SegmentEntry {
 
     fn set_full_payload_with_json(&mut self, op_num: SeqNumberType, point_id: PointIdType, full_payload: &str) -> OperationResult<bool>;
 
+    /// Apply JSON merge patch to the payload of the point and append `append` values to the existing ones
+    fn update_payload(
+        &mut self,
+        op_num: SeqNumberType,
+        point_id: PointIdType,
+        patch: &TheMap<PayloadKeyType, serde_json::Value>,
+        append: &TheMap<PayloadKeyType, serde_json::Value>,
+    ) -> OperationResult<bool>;
+
     fn set_payload(&mut self, op_num: SeqNumberType, point_id: PointIdType, key: &PayloadKeyType, payload: PayloadType) -> OperationResult<bool>;
 
     fn delete_payload(&mut self, op_num: SeqNumberType, point_id: PointIdType, key: &PayloadKeyType) -> OperationResult<bool>;
@@ -96,6 +152,49 @@ pub trait SegmentEntry {
 
     fn iter_points(&self) -> Box<dyn Iterator<Item=PointIdType> + '_>;
 
+    /// Paginate over points which satisfies filtering condition starting with `offset` id including.
+    /// Returns up to `limit` external ids in ascending order.
+    fn read_filtered(&self, offset: PointIdType, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType>;
+
+    /// Select up to `limit` distinct random points which satisfies filtering condition.
+    /// Returns external ids in random order.
+    fn sample_filtered(&self, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType>;
+
+    /// Read vectors of the points which satisfies filtering condition directly from the vector storage.
+    /// Calls `f` with external id and vector of each point, stops once it returns `false`.
+    fn read_vectors(&self, filter: Option<&Filter>, f: &mut dyn FnMut(PointIdType, Vec<VectorElementType>) -> bool);
+
+    /// Select all points which satisfies filtering condition.
+    /// Returns external ids in arbitrary order.
+    fn filtered_points(&self, filter: &Filter) -> Vec<PointIdType>;
+
+    /// Paginate over points which satisfies filtering condition in order of numeric payload field value.
+    /// Starts with `from` pair of value and external id including, ties are ordered by ascending id.
+    /// Point with multiple values is placed by its first value in given direction.
+    /// Returns up to `limit` pairs of value and external id.
+    fn read_ordered(
+        &self,
+        key: &PayloadKeyType,
+        direction: Direction,
+        from: Option<(FloatPayloadType, PointIdType)>,
+        limit: usize,
+        filter: Option<&Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)>;
+
+    /// Estimate amount of points in this segment which satisfies filtering condition.
+    /// If no filter provided - estimation is exact and equals to the number of points.
+    fn estimate_points_count(&self, filter: Option<&Filter>) -> CardinalityEstimation;
+
+    /// Count points for each value of keyword or integer payload field among points, which satisfy filter.
+    /// Point with multiple values is counted for each of them.
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize>;
+
+    /// Min and max of numeric payload field values among points, which satisfy filter.
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)>;
+
+    /// Count numeric payload field values in `buckets` equal-width buckets between `min` and `max`.
+    fn numeric_histogram(&self, key: &PayloadKeyType, filter: Option<&Filter>, min: FloatPayloadType, max: FloatPayloadType, buckets: usize) -> Vec<usize>;
+
     /// Check if there is point with `point_id` in this segment.
     fn has_point(&self, point_id: PointIdType) -> bool;
 
@@ -132,5 +231,8 @@ pub trait SegmentEntry {
 
     /// Get indexed fields
     fn get_indexed_fields(&self) -> Vec<PayloadKeyType>;
+
+    /// Get indexed fields, which indexes are outdated by payload changes and should be re-built
+    fn get_outdated_fields(&self) -> Vec<PayloadKeyType>;
 }
 
@@ -0,0 +1,262 @@
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use memmap::MmapMut;
+use uuid::Uuid;
+
+use crate::common::error_logging::LogError;
+use crate::common::mmap_layout::{MmapLayout, MmapLayoutWriter};
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::id_mapper::id_mapper::IdMapper;
+use crate::types::{PointIdType, PointOffsetType};
+
+const MAPPING_FILE: &str = "mapping.dat";
+const DELETED_FILE: &str = "deleted.dat";
+
+const ID_MAPPING_KIND: u64 = 1;
+/// Sorted external ids
+const EXTERNAL_IDS_SECTION: usize = 0;
+/// Internal ids in the same order as external ones
+const INTERNAL_IDS_SECTION: usize = 1;
+/// External id of each internal id
+const INTERNAL_TO_EXTERNAL_SECTION: usize = 2;
+const SECTIONS_COUNT: usize = 3;
+
+const DELETED_HEADER: &[u8; 4] = b"drop";
+
+const NUM_ID_KIND: u64 = 0;
+const UUID_KIND: u64 = 1;
+
+/// Fixed size representation of the external id, which could be read in place from the mmap.
+/// Numeric ids are stored in big-endian, so the order of stored ids matches the order of `PointIdType`.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+struct StoredPointId {
+    kind: u64,
+    bytes: [u8; 16],
+}
+
+impl From<PointIdType> for StoredPointId {
+    fn from(external_id: PointIdType) -> Self {
+        let mut bytes = [0u8; 16];
+        match external_id {
+            PointIdType::NumId(id) => {
+                bytes[..8].copy_from_slice(&id.to_be_bytes());
+                StoredPointId { kind: NUM_ID_KIND, bytes }
+            }
+            PointIdType::Uuid(uuid) => {
+                bytes.copy_from_slice(uuid.as_bytes());
+                StoredPointId { kind: UUID_KIND, bytes }
+            }
+        }
+    }
+}
+
+impl From<StoredPointId> for PointIdType {
+    fn from(stored: StoredPointId) -> Self {
+        match stored.kind {
+            UUID_KIND => PointIdType::Uuid(Uuid::from_bytes(stored.bytes)),
+            _ => {
+                let mut id_bytes = [0u8; 8];
+                id_bytes.copy_from_slice(&stored.bytes[..8]);
+                PointIdType::NumId(u64::from_be_bytes(id_bytes))
+            }
+        }
+    }
+}
+
+/// Immutable id mapper of non-appendable segments.
+/// Mapping is read directly from the mmap, only points deletion is allowed.
+pub struct MmapIdMapper {
+    mapping: MmapLayout,
+    /// Flag for each internal id, mapping of deleted points is ignored
+    deleted: MmapMut,
+}
+
+impl MmapIdMapper {
+    pub fn exists(path: &Path) -> bool {
+        path.join(MAPPING_FILE).exists()
+    }
+
+    /// Write current mapping of `id_mapper`, so it could be opened as `MmapIdMapper` from the same `path`
+    pub fn save(path: &Path, id_mapper: &dyn IdMapper) -> OperationResult<()> {
+        create_dir_all(path)?;
+
+        let mut pairs: Vec<(PointIdType, PointOffsetType)> = id_mapper
+            .iter_external()
+            .filter_map(|external_id| id_mapper.internal_id(external_id).map(|internal_id| (external_id, internal_id)))
+            .collect();
+        pairs.sort_unstable();
+
+        let internal_count = pairs.iter().map(|(_, internal_id)| *internal_id as usize + 1).max().unwrap_or(0);
+        let mut internal_to_external: Vec<StoredPointId> = vec![StoredPointId::default(); internal_count];
+        // Internal ids without mapping are marked as deleted
+        let mut deleted = DELETED_HEADER.to_vec();
+        deleted.resize(DELETED_HEADER.len() + internal_count, 1);
+        for (external_id, internal_id) in pairs.iter() {
+            internal_to_external[*internal_id as usize] = (*external_id).into();
+            deleted[DELETED_HEADER.len() + *internal_id as usize] = 0;
+        }
+
+        let mut writer = MmapLayoutWriter::new(ID_MAPPING_KIND);
+        writer.write_slice(&pairs.iter().map(|(external_id, _)| StoredPointId::from(*external_id)).collect::<Vec<_>>());
+        writer.write_slice(&pairs.iter().map(|(_, internal_id)| *internal_id).collect::<Vec<_>>());
+        writer.write_slice(&internal_to_external);
+        writer.save(&path.join(MAPPING_FILE))?;
+
+        let mut deleted_file = File::create(path.join(DELETED_FILE))?;
+        deleted_file.write_all(&deleted)?;
+        deleted_file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let mapping = MmapLayout::open(&path.join(MAPPING_FILE))?;
+        if mapping.kind() != ID_MAPPING_KIND || mapping.sections_count() != SECTIONS_COUNT {
+            return Err(OperationError::ServiceError {
+                description: format!("Wrong id mapping file in {}", path.to_str().unwrap())
+            });
+        }
+
+        let deleted_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path.join(DELETED_FILE))
+            .describe("Open deleted flags of id mapper")?;
+        let deleted = unsafe { MmapMut::map_mut(&deleted_file)? };
+
+        Ok(MmapIdMapper {
+            mapping,
+            deleted,
+        })
+    }
+
+    fn external_ids(&self) -> &[StoredPointId] {
+        self.mapping.section(EXTERNAL_IDS_SECTION)
+    }
+
+    fn internal_ids(&self) -> &[PointOffsetType] {
+        self.mapping.section(INTERNAL_IDS_SECTION)
+    }
+
+    fn is_deleted(&self, internal_id: PointOffsetType) -> bool {
+        self.deleted
+            .get(DELETED_HEADER.len() + internal_id as usize)
+            .map(|flag| *flag > 0)
+            .unwrap_or(true)
+    }
+
+    /// Internal id of the point, including deleted ones
+    fn stored_internal_id(&self, external_id: PointIdType) -> Option<PointOffsetType> {
+        self.external_ids()
+            .binary_search(&StoredPointId::from(external_id))
+            .ok()
+            .map(|position| self.internal_ids()[position])
+    }
+}
+
+impl IdMapper for MmapIdMapper {
+    fn internal_id(&self, external_id: PointIdType) -> Option<PointOffsetType> {
+        self.stored_internal_id(external_id)
+            .filter(|internal_id| !self.is_deleted(*internal_id))
+    }
+
+    fn external_id(&self, internal_id: PointOffsetType) -> Option<PointIdType> {
+        if self.is_deleted(internal_id) {
+            return None;
+        }
+        self.mapping
+            .section::<StoredPointId>(INTERNAL_TO_EXTERNAL_SECTION)
+            .get(internal_id as usize)
+            .map(|stored| PointIdType::from(*stored))
+    }
+
+    fn set_link(&mut self, external_id: PointIdType, internal_id: PointOffsetType) -> OperationResult<()> {
+        if self.stored_internal_id(external_id) != Some(internal_id) {
+            return Err(OperationError::ServiceError {
+                description: format!("Can't link point {} in immutable id mapper", external_id)
+            });
+        }
+        self.deleted[DELETED_HEADER.len() + internal_id as usize] = 0;
+        Ok(())
+    }
+
+    fn drop(&mut self, external_id: PointIdType) -> OperationResult<()> {
+        if let Some(internal_id) = self.stored_internal_id(external_id) {
+            self.deleted[DELETED_HEADER.len() + internal_id as usize] = 1;
+        }
+        Ok(())
+    }
+
+    fn iter_external(&self) -> Box<dyn Iterator<Item=PointIdType> + '_> {
+        let iter = self.external_ids()
+            .iter()
+            .zip(self.internal_ids().iter())
+            .filter(move |(_, internal_id)| !self.is_deleted(**internal_id))
+            .map(|(external_id, _)| PointIdType::from(*external_id));
+        Box::new(iter)
+    }
+
+    fn flush(&self) -> OperationResult<()> {
+        self.deleted.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use crate::id_mapper::simple_id_mapper::SimpleIdMapper;
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_id_mapper() {
+        let simple_dir = TempDir::new("simple_id_mapper_dir").unwrap();
+        let mmap_dir = TempDir::new("mmap_id_mapper_dir").unwrap();
+
+        let uuid: PointIdType = Uuid::parse_str("4a2b6bd1-4e47-4c3f-8f8c-1d2c3b4a5e6f").unwrap().into();
+
+        let mut simple_mapper = SimpleIdMapper::open(simple_dir.path()).unwrap();
+        for internal_id in 0..100 {
+            simple_mapper.set_link((1000 - 3 * internal_id as u64).into(), internal_id).unwrap();
+        }
+        simple_mapper.set_link(uuid, 100).unwrap();
+        simple_mapper.drop(1000.into()).unwrap();
+
+        MmapIdMapper::save(mmap_dir.path(), &simple_mapper).unwrap();
+        assert!(MmapIdMapper::exists(mmap_dir.path()));
+
+        {
+            let mut mapper = MmapIdMapper::open(mmap_dir.path()).unwrap();
+            assert_eq!(mapper.internal_id(997.into()), Some(1));
+            assert_eq!(mapper.external_id(1), Some(997.into()));
+            assert_eq!(mapper.internal_id(uuid), Some(100));
+            assert_eq!(mapper.external_id(100), Some(uuid));
+            // Points without mapping are deleted
+            assert_eq!(mapper.internal_id(1000.into()), None);
+            assert_eq!(mapper.external_id(0), None);
+            assert_eq!(mapper.external_id(101), None);
+            assert_eq!(mapper.iter_external().count(), 100);
+
+            mapper.drop(997.into()).unwrap();
+            assert_eq!(mapper.internal_id(997.into()), None);
+            assert_eq!(mapper.external_id(1), None);
+            assert!(mapper.set_link(5.into(), 1).is_err());
+            mapper.flush().unwrap();
+        }
+
+        // Deletions are persisted
+        let mapper = MmapIdMapper::open(mmap_dir.path()).unwrap();
+        assert_eq!(mapper.internal_id(997.into()), None);
+        assert_eq!(mapper.internal_id(994.into()), Some(2));
+
+        let external_ids = mapper.iter_external().collect::<Vec<_>>();
+        assert_eq!(external_ids.len(), 99);
+        assert!(external_ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(external_ids.last(), Some(&uuid));
+    }
+}
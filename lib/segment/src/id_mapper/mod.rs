@@ -1,2 +1,3 @@
 pub mod simple_id_mapper;
+pub mod mmap_id_mapper;
 pub mod id_mapper;
\ No newline at end of file
@@ -5,10 +5,26 @@ use crate::entry::entry_point::OperationResult;
 use bincode;
 use std::path::Path;
 use rocksdb::{Options, DB, IteratorMode};
+use uuid::Uuid;
 
 /// Since sled is used for reading only during the initialization, large read cache is not required
 const DB_CACHE_SIZE: usize = 10 * 1024 * 1024; // 10 mb
 
+/// Numeric ids keep the original 8 bytes encoding, UUIDs are stored as 16 raw bytes
+fn external_id_key(external_id: &PointIdType) -> Vec<u8> {
+    match external_id {
+        PointIdType::NumId(id) => bincode::serialize(id).unwrap(),
+        PointIdType::Uuid(uuid) => uuid.as_bytes().to_vec(),
+    }
+}
+
+fn external_id_from_key(key: &[u8]) -> PointIdType {
+    match key.len() {
+        16 => PointIdType::Uuid(Uuid::from_slice(key).unwrap()),
+        _ => PointIdType::NumId(bincode::deserialize(key).unwrap()),
+    }
+}
+
 pub struct SimpleIdMapper {
     internal_to_external: HashMap<PointOffsetType, PointIdType>,
     external_to_internal: HashMap<PointIdType, PointOffsetType>,
@@ -27,7 +43,7 @@ impl SimpleIdMapper {
         let mut external_to_internal: HashMap<PointIdType, PointOffsetType> = Default::default();
 
         for (key, val) in store.iterator(IteratorMode::Start) {
-            let external_id: PointIdType = bincode::deserialize(&key).unwrap();
+            let external_id = external_id_from_key(&key);
             let internal_id: PointOffsetType = bincode::deserialize(&val).unwrap();
             internal_to_external.insert(internal_id, external_id);
             external_to_internal.insert(external_id, internal_id);
@@ -56,7 +72,7 @@ impl IdMapper for SimpleIdMapper {
         self.internal_to_external.insert(internal_id, external_id);
 
         self.store.put(
-            bincode::serialize(&external_id).unwrap(),
+            external_id_key(&external_id),
             bincode::serialize(&internal_id).unwrap())?;
         Ok(())
     }
@@ -67,7 +83,7 @@ impl IdMapper for SimpleIdMapper {
             Some(x) => self.internal_to_external.remove(&x),
             None => None
         };
-        self.store.delete(bincode::serialize(&external_id).unwrap())?;
+        self.store.delete(external_id_key(&external_id))?;
         Ok(())
     }
 
@@ -80,3 +96,31 @@ impl IdMapper for SimpleIdMapper {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_uuid_external_ids() {
+        let dir = TempDir::new("id_mapper_dir").unwrap();
+        let uuid: PointIdType = Uuid::parse_str("4a2b6bd1-4e47-4c3f-8f8c-1d2c3b4a5e6f").unwrap().into();
+        {
+            let mut mapper = SimpleIdMapper::open(dir.path()).unwrap();
+            mapper.set_link(10.into(), 0).unwrap();
+            mapper.set_link(uuid, 1).unwrap();
+            mapper.set_link(20.into(), 2).unwrap();
+            mapper.drop(20.into()).unwrap();
+            mapper.flush().unwrap();
+        }
+
+        let mapper = SimpleIdMapper::open(dir.path()).unwrap();
+        assert_eq!(mapper.internal_id(10.into()), Some(0));
+        assert_eq!(mapper.internal_id(uuid), Some(1));
+        assert_eq!(mapper.external_id(1), Some(uuid));
+        assert_eq!(mapper.internal_id(20.into()), None);
+        assert_eq!(mapper.iter_external().count(), 2);
+    }
+}
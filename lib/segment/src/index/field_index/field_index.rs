@@ -1,8 +1,14 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
+use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
 use crate::index::field_index::map_index::PersistedMapIndex;
 use crate::index::field_index::numeric_index::PersistedNumericIndex;
-use crate::types::{FieldCondition, FloatPayloadType, IntPayloadType, PayloadType, PointOffsetType, PayloadKeyType};
+use crate::common::mmap_layout::MmapLayout;
+use crate::index::field_index::mmap_map_index::MmapMapIndex;
+use crate::index::field_index::mmap_numeric_index::MmapNumericIndex;
+use crate::types::{FieldCondition, FloatPayloadType, IntPayloadType, PayloadType, PointOffsetType, PayloadKeyType, FacetValue, Direction};
 
 pub trait PayloadFieldIndex {
     /// Get iterator over points fitting given `condition`
@@ -23,12 +29,27 @@ pub trait PayloadFieldIndexBuilder {
 }
 
 
+/// Kinds of indexes in the mmap layout
+const INT_INDEX_KIND: u64 = 1;
+const INT_MAP_INDEX_KIND: u64 = 2;
+const KEYWORD_INDEX_KIND: u64 = 3;
+const FLOAT_INDEX_KIND: u64 = 4;
+
 #[derive(Serialize, Deserialize)]
 pub enum FieldIndex {
     IntIndex(PersistedNumericIndex<IntPayloadType>),
     IntMapIndex(PersistedMapIndex<IntPayloadType>),
     KeywordIndex(PersistedMapIndex<String>),
     FloatIndex(PersistedNumericIndex<FloatPayloadType>),
+    /// Immutable indexes, which are read from the mmap. Stored with `save_mmap` only
+    #[serde(skip)]
+    MmapIntIndex(MmapNumericIndex<IntPayloadType>),
+    #[serde(skip)]
+    MmapIntMapIndex(MmapMapIndex<IntPayloadType>),
+    #[serde(skip)]
+    MmapKeywordIndex(MmapMapIndex<String>),
+    #[serde(skip)]
+    MmapFloatIndex(MmapNumericIndex<FloatPayloadType>),
 }
 
 impl FieldIndex {
@@ -38,6 +59,142 @@ impl FieldIndex {
             FieldIndex::IntMapIndex(payload_field_index) => payload_field_index,
             FieldIndex::KeywordIndex(payload_field_index) => payload_field_index,
             FieldIndex::FloatIndex(payload_field_index) => payload_field_index,
+            FieldIndex::MmapIntIndex(payload_field_index) => payload_field_index,
+            FieldIndex::MmapIntMapIndex(payload_field_index) => payload_field_index,
+            FieldIndex::MmapKeywordIndex(payload_field_index) => payload_field_index,
+            FieldIndex::MmapFloatIndex(payload_field_index) => payload_field_index,
+        }
+    }
+
+    /// Save index in the immutable layout, which is read directly from the mmap
+    pub fn save_mmap(&self, path: &Path) -> OperationResult<()> {
+        match self {
+            FieldIndex::IntIndex(index) => MmapNumericIndex::save(&index.view(), INT_INDEX_KIND, path),
+            FieldIndex::IntMapIndex(index) => MmapMapIndex::save(index, INT_MAP_INDEX_KIND, path),
+            FieldIndex::KeywordIndex(index) => MmapMapIndex::save(index, KEYWORD_INDEX_KIND, path),
+            FieldIndex::FloatIndex(index) => MmapNumericIndex::save(&index.view(), FLOAT_INDEX_KIND, path),
+            FieldIndex::MmapIntIndex(index) => index.layout().save(path),
+            FieldIndex::MmapIntMapIndex(index) => index.layout().save(path),
+            FieldIndex::MmapKeywordIndex(index) => index.layout().save(path),
+            FieldIndex::MmapFloatIndex(index) => index.layout().save(path),
+        }
+    }
+
+    /// Open index, saved with `save_mmap`
+    pub fn open_mmap(path: &Path) -> OperationResult<Self> {
+        let layout = MmapLayout::open(path)?;
+        match layout.kind() {
+            INT_INDEX_KIND => Ok(FieldIndex::MmapIntIndex(MmapNumericIndex::open(layout)?)),
+            INT_MAP_INDEX_KIND => Ok(FieldIndex::MmapIntMapIndex(MmapMapIndex::open(layout)?)),
+            KEYWORD_INDEX_KIND => Ok(FieldIndex::MmapKeywordIndex(MmapMapIndex::open(layout)?)),
+            FLOAT_INDEX_KIND => Ok(FieldIndex::MmapFloatIndex(MmapNumericIndex::open(layout)?)),
+            kind => Err(OperationError::ServiceError {
+                description: format!("Unknown kind of field index: {}", kind)
+            }),
+        }
+    }
+
+    /// Count points for each value of the field. Only map indexes are able to count values
+    pub fn facet_counts(&self, check: &dyn Fn(PointOffsetType) -> bool) -> Option<Vec<(FacetValue, usize)>> {
+        match self {
+            FieldIndex::KeywordIndex(index) => Some(index.values_count(check)
+                .map(|(value, count)| (FacetValue::Keyword(value.clone()), count))
+                .collect()),
+            FieldIndex::IntMapIndex(index) => Some(index.values_count(check)
+                .map(|(value, count)| (FacetValue::Integer(*value), count))
+                .collect()),
+            FieldIndex::MmapKeywordIndex(index) => Some(index.values_count(check)
+                .map(|(value, count)| (FacetValue::Keyword(value), count))
+                .collect()),
+            FieldIndex::MmapIntMapIndex(index) => Some(index.values_count(check)
+                .map(|(value, count)| (FacetValue::Integer(value), count))
+                .collect()),
+            FieldIndex::IntIndex(_) | FieldIndex::FloatIndex(_) => None,
+            FieldIndex::MmapIntIndex(_) | FieldIndex::MmapFloatIndex(_) => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            FieldIndex::IntIndex(_) | FieldIndex::FloatIndex(_) => true,
+            FieldIndex::MmapIntIndex(_) | FieldIndex::MmapFloatIndex(_) => true,
+            FieldIndex::IntMapIndex(_) | FieldIndex::KeywordIndex(_) => false,
+            FieldIndex::MmapIntMapIndex(_) | FieldIndex::MmapKeywordIndex(_) => false,
+        }
+    }
+
+    /// Min and max values of the field. Always `None` for non-numeric indexes
+    pub fn values_range(&self, check: &dyn Fn(PointOffsetType) -> bool) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        match self {
+            FieldIndex::IntIndex(index) => index.values_range(check),
+            FieldIndex::FloatIndex(index) => index.values_range(check),
+            FieldIndex::MmapIntIndex(index) => index.view().values_range(check),
+            FieldIndex::MmapFloatIndex(index) => index.view().values_range(check),
+            FieldIndex::IntMapIndex(_) | FieldIndex::KeywordIndex(_) => None,
+            FieldIndex::MmapIntMapIndex(_) | FieldIndex::MmapKeywordIndex(_) => None,
+        }
+    }
+
+    /// Histogram of the field values. Always empty for non-numeric indexes
+    pub fn histogram(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        min: FloatPayloadType,
+        max: FloatPayloadType,
+        buckets: usize,
+    ) -> Vec<usize> {
+        match self {
+            FieldIndex::IntIndex(index) => index.histogram(check, min, max, buckets),
+            FieldIndex::FloatIndex(index) => index.histogram(check, min, max, buckets),
+            FieldIndex::MmapIntIndex(index) => index.view().histogram(check, min, max, buckets),
+            FieldIndex::MmapFloatIndex(index) => index.view().histogram(check, min, max, buckets),
+            FieldIndex::IntMapIndex(_) | FieldIndex::KeywordIndex(_) => vec![0; buckets],
+            FieldIndex::MmapIntMapIndex(_) | FieldIndex::MmapKeywordIndex(_) => vec![0; buckets],
+        }
+    }
+
+    /// Check if point satisfies condition using index only.
+    /// Returns `None` if the condition can't be checked with this index
+    pub fn check(&self, point_id: PointOffsetType, condition: &FieldCondition) -> Option<bool> {
+        if condition.range.is_some() || condition.geo_bounding_box.is_some() || condition.geo_radius.is_some() {
+            return None;
+        }
+        let match_condition = condition.r#match.as_ref()?;
+        match self {
+            FieldIndex::KeywordIndex(index) => Some(match_condition.keyword
+                .as_ref()
+                .map(|keyword| index.check_value(keyword, point_id))
+                .unwrap_or(false)),
+            FieldIndex::IntMapIndex(index) => Some(match_condition.integer
+                .map(|number| index.check_value(&number, point_id))
+                .unwrap_or(false)),
+            FieldIndex::MmapKeywordIndex(index) => Some(match_condition.keyword
+                .as_ref()
+                .map(|keyword| index.check_value(keyword, point_id))
+                .unwrap_or(false)),
+            FieldIndex::MmapIntMapIndex(index) => Some(match_condition.integer
+                .map(|number| index.check_value(&number, point_id))
+                .unwrap_or(false)),
+            FieldIndex::IntIndex(_) | FieldIndex::FloatIndex(_) => None,
+            FieldIndex::MmapIntIndex(_) | FieldIndex::MmapFloatIndex(_) => None,
+        }
+    }
+
+    /// Visit field values in given `direction`. Visits nothing for non-numeric indexes
+    pub fn walk_ordered(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        direction: Direction,
+        from: Option<FloatPayloadType>,
+        visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool,
+    ) {
+        match self {
+            FieldIndex::IntIndex(index) => index.walk_ordered(check, direction, from, visit),
+            FieldIndex::FloatIndex(index) => index.walk_ordered(check, direction, from, visit),
+            FieldIndex::MmapIntIndex(index) => index.view().walk_ordered(check, direction, from, visit),
+            FieldIndex::MmapFloatIndex(index) => index.view().walk_ordered(check, direction, from, visit),
+            FieldIndex::IntMapIndex(_) | FieldIndex::KeywordIndex(_) => {}
+            FieldIndex::MmapIntMapIndex(_) | FieldIndex::MmapKeywordIndex(_) => {}
         }
     }
 }
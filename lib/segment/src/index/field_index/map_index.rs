@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
 use std::hash::Hash;
 use std::{mem, iter};
 
@@ -6,31 +8,139 @@ use serde::{Deserialize, Serialize};
 
 use crate::index::field_index::{CardinalityEstimation, PrimaryCondition, PayloadBlockCondition};
 use crate::index::field_index::field_index::{FieldIndex, PayloadFieldIndex, PayloadFieldIndexBuilder};
+use crate::index::field_index::sketch::{sketch_rate, PointsSketch};
 use crate::types::{IntPayloadType, PayloadType, PointOffsetType, FieldCondition, PayloadKeyType, Match};
 
+/// Value of the map index, which could be matched by `Match` condition
+pub trait MapIndexKey: Hash + Eq + Clone {
+    /// Value of `Match` condition, applicable to this type of index
+    fn from_match(condition: &Match) -> Option<&Self>;
+
+    fn to_match(&self) -> Match;
+
+    /// Binary representation, used for storing values on disk
+    fn key_bytes(&self) -> Cow<'_, [u8]>;
+
+    fn from_key_bytes(bytes: &[u8]) -> Self;
+}
+
+impl MapIndexKey for String {
+    fn from_match(condition: &Match) -> Option<&Self> {
+        condition.keyword.as_ref()
+    }
+
+    fn to_match(&self) -> Match {
+        Match {
+            keyword: Some(self.to_owned()),
+            integer: None,
+        }
+    }
+
+    fn key_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Self {
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+}
+
+impl MapIndexKey for IntPayloadType {
+    fn from_match(condition: &Match) -> Option<&Self> {
+        condition.integer.as_ref()
+    }
+
+    fn to_match(&self) -> Match {
+        Match {
+            keyword: None,
+            integer: Some(*self),
+        }
+    }
+
+    fn key_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_be_bytes().to_vec())
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Self {
+        IntPayloadType::from_be_bytes(bytes.try_into().unwrap())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PersistedMapIndex<N: Hash + Eq + Clone> {
     map: HashMap<N, Vec<PointOffsetType>>,
+    /// Sampling rate of the sketch, 0 if index was built without sketch
+    #[serde(default)]
+    sketch_rate: usize,
+    /// Sampled points of each value
+    #[serde(default)]
+    sketch: HashMap<N, Vec<PointOffsetType>>,
 }
 
 impl<N: Hash + Eq + Clone> PersistedMapIndex<N> {
     pub fn new() -> PersistedMapIndex<N> {
         PersistedMapIndex {
             map: Default::default(),
+            sketch_rate: 0,
+            sketch: Default::default(),
+        }
+    }
+
+    /// Build index together with the sketch of sampled points.
+    /// Points of each value are sorted to allow checking of a single point
+    fn from_map(mut map: HashMap<N, Vec<PointOffsetType>>) -> Self {
+        for points in map.values_mut() {
+            points.sort_unstable();
+        }
+        let points_count = map.values().flatten().collect::<HashSet<_>>().len();
+        let sketch_rate = sketch_rate(points_count);
+        let sketch = map
+            .iter()
+            .map(|(value, points)| (value.clone(), PointsSketch::new(sketch_rate, points.iter().cloned()).points))
+            .filter(|(_value, points)| !points.is_empty())
+            .collect();
+        PersistedMapIndex {
+            map,
+            sketch_rate,
+            sketch,
         }
     }
 
+    /// Sorted points of each value
+    pub fn iter_points(&self) -> impl Iterator<Item=(&N, &[PointOffsetType])> {
+        self.map.iter().map(|(value, points)| (value, points.as_slice()))
+    }
+
+    pub fn sketch_rate(&self) -> usize {
+        self.sketch_rate
+    }
+
+    /// Sampled points of given value
+    pub fn sketch_points(&self, value: &N) -> &[PointOffsetType] {
+        self.sketch.get(value).map(|points| points.as_slice()).unwrap_or(&[])
+    }
+
     pub fn match_cardinality(&self, value: &N) -> CardinalityEstimation {
         let values_count = match self.map.get(value) {
             None => 0,
             Some(points) => points.len()
         };
 
+        let sketch = if self.sketch_rate > 0 {
+            Some(PointsSketch {
+                rate: self.sketch_rate,
+                points: self.sketch.get(value).cloned().unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
         CardinalityEstimation {
             primary_clauses: vec![],
             min: values_count,
             exp: values_count,
             max: values_count,
+            sketch,
         }
     }
 
@@ -49,6 +159,22 @@ impl<N: Hash + Eq + Clone> PersistedMapIndex<N> {
         }
     }
 
+    /// Check if point has given value
+    pub fn check_value(&self, value: &N, point_id: PointOffsetType) -> bool {
+        self.map
+            .get(value)
+            .map(|points| points.binary_search(&point_id).is_ok())
+            .unwrap_or(false)
+    }
+
+    /// Count points for each value, skipping points which are not accepted by `check`
+    pub fn values_count<'a>(&'a self, check: &'a dyn Fn(PointOffsetType) -> bool) -> impl Iterator<Item=(&'a N, usize)> + 'a {
+        self.map
+            .iter()
+            .map(move |(value, points)| (value, points.iter().filter(|point_id| check(**point_id)).count()))
+            .filter(|(_value, count)| *count > 0)
+    }
+
     fn get_iterator(&self, value: &N) -> Box<dyn Iterator<Item=PointOffsetType> + '_> {
         self.map
             .get(value)
@@ -151,9 +277,7 @@ impl PayloadFieldIndexBuilder for PersistedMapIndex<String> {
     fn build(&mut self) -> FieldIndex {
         let data = mem::replace(&mut self.map, Default::default());
 
-        FieldIndex::KeywordIndex(PersistedMapIndex {
-            map: data,
-        })
+        FieldIndex::KeywordIndex(PersistedMapIndex::from_map(data))
     }
 }
 
@@ -168,8 +292,6 @@ impl PayloadFieldIndexBuilder for PersistedMapIndex<IntPayloadType> {
     fn build(&mut self) -> FieldIndex {
         let data = mem::replace(&mut self.map, Default::default());
 
-        FieldIndex::IntMapIndex(PersistedMapIndex {
-            map: data,
-        })
+        FieldIndex::IntMapIndex(PersistedMapIndex::from_map(data))
     }
 }
@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition, PrimaryCondition};
+use crate::index::field_index::field_index::PayloadFieldIndex;
+use crate::index::field_index::map_index::{MapIndexKey, PersistedMapIndex};
+use crate::common::mmap_layout::{MmapLayout, MmapLayoutWriter};
+use crate::index::field_index::sketch::PointsSketch;
+use crate::types::{FieldCondition, PayloadKeyType, PointOffsetType};
+
+/// Sampling rate of the sketch
+const META_SECTION: usize = 0;
+/// Values are sorted by their binary representation, `offsets` sections have `values count + 1` elements
+const VALUE_OFFSETS_SECTION: usize = 1;
+const VALUE_BYTES_SECTION: usize = 2;
+const POINT_OFFSETS_SECTION: usize = 3;
+const POINTS_SECTION: usize = 4;
+const SKETCH_OFFSETS_SECTION: usize = 5;
+const SKETCH_POINTS_SECTION: usize = 6;
+const SECTIONS_COUNT: usize = 7;
+
+/// Write concatenated arrays together with offsets of each array
+fn write_postings<'a, T: Copy + 'a>(writer: &mut MmapLayoutWriter, postings: impl Iterator<Item=&'a [T]>) {
+    let mut offsets: Vec<u64> = vec![0];
+    let mut data: Vec<T> = vec![];
+    for posting in postings {
+        data.extend_from_slice(posting);
+        offsets.push(data.len() as u64);
+    }
+    writer.write_slice(&offsets);
+    writer.write_slice(&data);
+}
+
+/// Immutable map index, which reads sorted values and their posting lists directly from the mmap
+pub struct MmapMapIndex<N> {
+    layout: MmapLayout,
+    _value: PhantomData<N>,
+}
+
+impl<N: MapIndexKey> MmapMapIndex<N> {
+    pub fn save(index: &PersistedMapIndex<N>, kind: u64, path: &Path) -> OperationResult<()> {
+        let values = index
+            .iter_points()
+            .map(|(value, points)| (value.key_bytes(), value, points))
+            .sorted_by(|(bytes1, _, _), (bytes2, _, _)| bytes1.cmp(bytes2))
+            .collect_vec();
+
+        let mut writer = MmapLayoutWriter::new(kind);
+        writer.write_slice(&[index.sketch_rate() as u64]);
+        write_postings(&mut writer, values.iter().map(|(bytes, _, _)| bytes.as_ref()));
+        write_postings(&mut writer, values.iter().map(|(_, _, points)| *points));
+        write_postings(&mut writer, values.iter().map(|(_, value, _)| index.sketch_points(value)));
+        writer.save(path)
+    }
+
+    pub fn open(layout: MmapLayout) -> OperationResult<Self> {
+        if layout.sections_count() != SECTIONS_COUNT {
+            return Err(OperationError::ServiceError {
+                description: format!("Wrong number of map index sections: {}", layout.sections_count())
+            });
+        }
+        Ok(MmapMapIndex {
+            layout,
+            _value: PhantomData,
+        })
+    }
+
+    pub fn layout(&self) -> &MmapLayout {
+        &self.layout
+    }
+
+    fn posting<T: Copy>(&self, offsets_section: usize, data_section: usize, idx: usize) -> &[T] {
+        let offsets = self.layout.section::<u64>(offsets_section);
+        &self.layout.section::<T>(data_section)[offsets[idx] as usize..offsets[idx + 1] as usize]
+    }
+
+    fn values_len(&self) -> usize {
+        self.layout.section::<u64>(VALUE_OFFSETS_SECTION).len() - 1
+    }
+
+    fn value_bytes(&self, idx: usize) -> &[u8] {
+        self.posting(VALUE_OFFSETS_SECTION, VALUE_BYTES_SECTION, idx)
+    }
+
+    fn points(&self, idx: usize) -> &[PointOffsetType] {
+        self.posting(POINT_OFFSETS_SECTION, POINTS_SECTION, idx)
+    }
+
+    /// Binary search of the value position
+    fn find(&self, value: &N) -> Option<usize> {
+        let key = value.key_bytes();
+        let mut low = 0;
+        let mut high = self.values_len();
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.value_bytes(mid).cmp(key.as_ref()) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    /// Sorted points with given value
+    fn value_points(&self, value: &N) -> &[PointOffsetType] {
+        self.find(value).map(|idx| self.points(idx)).unwrap_or(&[])
+    }
+
+    pub fn match_cardinality(&self, value: &N) -> CardinalityEstimation {
+        let values_count = self.value_points(value).len();
+        let sketch_rate = self.layout.section::<u64>(META_SECTION)[0] as usize;
+
+        let sketch = if sketch_rate > 0 {
+            let sketch_points = self.find(value)
+                .map(|idx| self.posting(SKETCH_OFFSETS_SECTION, SKETCH_POINTS_SECTION, idx).to_vec())
+                .unwrap_or_default();
+            Some(PointsSketch {
+                rate: sketch_rate,
+                points: sketch_points,
+            })
+        } else {
+            None
+        };
+
+        CardinalityEstimation {
+            primary_clauses: vec![],
+            min: values_count,
+            exp: values_count,
+            max: values_count,
+            sketch,
+        }
+    }
+
+    /// Check if point has given value
+    pub fn check_value(&self, value: &N, point_id: PointOffsetType) -> bool {
+        self.value_points(value).binary_search(&point_id).is_ok()
+    }
+
+    /// Count points for each value, skipping points which are not accepted by `check`
+    pub fn values_count<'a>(&'a self, check: &'a dyn Fn(PointOffsetType) -> bool) -> impl Iterator<Item=(N, usize)> + 'a {
+        (0..self.values_len())
+            .map(move |idx| (idx, self.points(idx).iter().filter(|point_id| check(**point_id)).count()))
+            .filter(|(_idx, count)| *count > 0)
+            .map(move |(idx, count)| (N::from_key_bytes(self.value_bytes(idx)), count))
+    }
+}
+
+impl<N: MapIndexKey> PayloadFieldIndex for MmapMapIndex<N> {
+    fn filter(&self, condition: &FieldCondition) -> Option<Box<dyn Iterator<Item=PointOffsetType> + '_>> {
+        condition.r#match.as_ref()
+            .and_then(|match_condition| N::from_match(match_condition))
+            .map(|value| Box::new(self.value_points(value).iter().cloned()) as Box<dyn Iterator<Item=PointOffsetType>>)
+    }
+
+    fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
+        condition.r#match.as_ref()
+            .and_then(|match_condition| N::from_match(match_condition))
+            .map(|value| {
+                let mut estimation = self.match_cardinality(value);
+                estimation.primary_clauses.push(PrimaryCondition::Condition(condition.clone()));
+                estimation
+            })
+    }
+
+    fn payload_blocks(&self, threshold: usize, key: PayloadKeyType) -> Box<dyn Iterator<Item=PayloadBlockCondition> + '_> {
+        let iter = (0..self.values_len())
+            .filter(move |idx| self.points(*idx).len() >= threshold)
+            .map(move |idx| {
+                PayloadBlockCondition {
+                    condition: FieldCondition {
+                        key: key.clone(),
+                        r#match: Some(N::from_key_bytes(self.value_bytes(idx)).to_match()),
+                        range: None,
+                        geo_bounding_box: None,
+                        geo_radius: None,
+                    },
+                    cardinality: self.points(idx).len(),
+                }
+            });
+        Box::new(iter)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    use crate::index::field_index::field_index::{FieldIndex, PayloadFieldIndexBuilder};
+    use crate::types::{FacetValue, Match, PayloadType};
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_map_index() {
+        let dir = TempDir::new("mmap_index_dir").unwrap();
+        let path = dir.path().join("index.dat");
+
+        let mut builder = PersistedMapIndex::<String>::new();
+        for id in 0..100 {
+            let keywords = vec![format!("value_{}", id % 7), format!("other_{}", id % 3)];
+            builder.add(id, &PayloadType::Keyword(keywords));
+        }
+        let index = match builder.build() {
+            FieldIndex::KeywordIndex(index) => index,
+            _ => panic!("Unexpected index type"),
+        };
+        MmapMapIndex::save(&index, 0, &path).unwrap();
+        let mmap_index = MmapMapIndex::<String>::open(MmapLayout::open(&path).unwrap()).unwrap();
+
+        let condition = FieldCondition {
+            key: "kw".to_string(),
+            r#match: Some(Match { keyword: Some("value_3".to_owned()), integer: None }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        };
+
+        let points: Vec<_> = index.filter(&condition).unwrap().collect();
+        let mmap_points: Vec<_> = mmap_index.filter(&condition).unwrap().collect();
+        assert_eq!(points.len(), 14);
+        assert_eq!(points, mmap_points);
+
+        let estimation = mmap_index.estimate_cardinality(&condition).unwrap();
+        assert_eq!(estimation.exp, 14);
+        assert_eq!(estimation.sketch, index.match_cardinality(&"value_3".to_owned()).sketch);
+
+        assert!(mmap_index.check_value(&"value_3".to_owned(), 10));
+        assert!(!mmap_index.check_value(&"value_3".to_owned(), 11));
+        assert!(!mmap_index.check_value(&"missing".to_owned(), 10));
+        assert_eq!(mmap_index.match_cardinality(&"missing".to_owned()).max, 0);
+
+        let counts: HashMap<_, _> = mmap_index.values_count(&|point_id| point_id < 50)
+            .map(|(value, count)| (FacetValue::Keyword(value), count))
+            .collect();
+        let expected_counts: HashMap<_, _> = index.values_count(&|point_id| point_id < 50)
+            .map(|(value, count)| (FacetValue::Keyword(value.clone()), count))
+            .collect();
+        assert_eq!(counts.len(), 10);
+        assert_eq!(counts, expected_counts);
+
+        assert_eq!(mmap_index.payload_blocks(30, "kw".to_string()).count(), 3);
+    }
+}
@@ -0,0 +1,140 @@
+use std::marker::PhantomData;
+use std::mem::{size_of, size_of_val};
+use std::path::Path;
+
+use num_traits::ToPrimitive;
+
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
+use crate::index::field_index::field_index::PayloadFieldIndex;
+use crate::common::mmap_layout::{MmapLayout, MmapLayoutWriter};
+use crate::index::field_index::numeric_index::{Element, NumericIndexView};
+use crate::types::{FieldCondition, PayloadKeyType, PointOffsetType};
+
+/// Number of points and sampling rate of the sketch
+const META_SECTION: usize = 0;
+const ELEMENTS_SECTION: usize = 1;
+const SKETCH_SECTION: usize = 2;
+const SECTIONS_COUNT: usize = 3;
+
+/// Encode elements with zeroed padding, so the array could be read in place as `&[Element<N>]`
+fn elements_bytes<N: Copy>(elements: &[Element<N>]) -> Vec<u8> {
+    let element_size = size_of::<Element<N>>();
+    let value_offset = element_size - size_of::<N>();
+    let mut bytes = vec![0u8; size_of_val(elements)];
+    for (element, chunk) in elements.iter().zip(bytes.chunks_exact_mut(element_size)) {
+        let value_bytes = unsafe {
+            std::slice::from_raw_parts(&element.value as *const N as *const u8, size_of::<N>())
+        };
+        chunk[..size_of::<PointOffsetType>()].copy_from_slice(&element.id.to_ne_bytes());
+        chunk[value_offset..].copy_from_slice(value_bytes);
+    }
+    bytes
+}
+
+/// Immutable numeric index, which reads sorted elements directly from the mmap
+pub struct MmapNumericIndex<N> {
+    layout: MmapLayout,
+    _value: PhantomData<N>,
+}
+
+impl<N: ToPrimitive + Copy> MmapNumericIndex<N> {
+    pub fn save(index: &NumericIndexView<N>, kind: u64, path: &Path) -> OperationResult<()> {
+        let mut writer = MmapLayoutWriter::new(kind);
+        writer.write_slice(&[index.points_count as u64, index.sketch_rate as u64]);
+        writer.write_section(index.elements.len(), &elements_bytes(index.elements));
+        writer.write_section(index.sketch.len(), &elements_bytes(index.sketch));
+        writer.save(path)
+    }
+
+    pub fn open(layout: MmapLayout) -> OperationResult<Self> {
+        if layout.sections_count() != SECTIONS_COUNT {
+            return Err(OperationError::ServiceError {
+                description: format!("Wrong number of numeric index sections: {}", layout.sections_count())
+            });
+        }
+        Ok(MmapNumericIndex {
+            layout,
+            _value: PhantomData,
+        })
+    }
+
+    pub fn layout(&self) -> &MmapLayout {
+        &self.layout
+    }
+
+    pub fn view(&self) -> NumericIndexView<'_, N> {
+        let meta = self.layout.section::<u64>(META_SECTION);
+        NumericIndexView {
+            points_count: meta[0] as usize,
+            elements: self.layout.section(ELEMENTS_SECTION),
+            sketch_rate: meta[1] as usize,
+            sketch: self.layout.section(SKETCH_SECTION),
+        }
+    }
+}
+
+impl<N: ToPrimitive + Copy> PayloadFieldIndex for MmapNumericIndex<N> {
+    fn filter(&self, condition: &FieldCondition) -> Option<Box<dyn Iterator<Item=PointOffsetType> + '_>> {
+        self.view().filter(condition)
+    }
+
+    fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
+        self.view().estimate_cardinality(condition)
+    }
+
+    fn payload_blocks(&self, threshold: usize, key: PayloadKeyType) -> Box<dyn Iterator<Item=PayloadBlockCondition> + '_> {
+        self.view().payload_blocks(threshold, key)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use crate::index::field_index::field_index::{FieldIndex, PayloadFieldIndexBuilder};
+    use crate::index::field_index::numeric_index::PersistedNumericIndex;
+    use crate::types::{FloatPayloadType, PayloadType, Range};
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_numeric_index() {
+        let dir = TempDir::new("mmap_index_dir").unwrap();
+        let path = dir.path().join("index.dat");
+
+        let mut builder = PersistedNumericIndex::<FloatPayloadType>::new();
+        for id in 0..100 {
+            builder.add(id, &PayloadType::Float(vec![id as FloatPayloadType, 0.5 * id as FloatPayloadType]));
+        }
+        let index = match builder.build() {
+            FieldIndex::FloatIndex(index) => index,
+            _ => panic!("Unexpected index type"),
+        };
+        MmapNumericIndex::save(&index.view(), 0, &path).unwrap();
+        let mmap_index = MmapNumericIndex::<FloatPayloadType>::open(MmapLayout::open(&path).unwrap()).unwrap();
+
+        let range = Range { lt: Some(30.0), gt: None, gte: Some(10.0), lte: None };
+        let estimation = index.range_cardinality(&range);
+        let mmap_estimation = mmap_index.view().range_cardinality(&range);
+        assert_eq!(estimation.min, mmap_estimation.min);
+        assert_eq!(estimation.exp, mmap_estimation.exp);
+        assert_eq!(estimation.max, mmap_estimation.max);
+        assert_eq!(estimation.sketch, mmap_estimation.sketch);
+
+        let condition = FieldCondition {
+            key: "num".to_string(),
+            r#match: None,
+            range: Some(range),
+            geo_bounding_box: None,
+            geo_radius: None,
+        };
+        let points: Vec<_> = index.filter(&condition).unwrap().collect();
+        let mmap_points: Vec<_> = mmap_index.filter(&condition).unwrap().collect();
+        assert_eq!(points.len(), 60);
+        assert_eq!(points, mmap_points);
+
+        assert_eq!(index.values_range(&|_id| true), mmap_index.view().values_range(&|_id| true));
+    }
+}
@@ -1,11 +1,15 @@
-use crate::types::{FieldCondition, PointOffsetType};
+use crate::types::{FieldCondition, PointOffsetType, CardinalityInfo, PrimaryClauseInfo};
 use std::collections::HashSet;
+use crate::index::field_index::sketch::PointsSketch;
 
 pub mod numeric_index;
 pub mod geo_index;
 pub mod map_index;
 pub mod field_index;
 pub mod index_selector;
+pub mod sketch;
+pub mod mmap_map_index;
+pub mod mmap_numeric_index;
 
 #[derive(Debug, Clone)]
 pub enum PrimaryCondition {
@@ -29,16 +33,18 @@ pub struct CardinalityEstimation {
     pub exp: usize,
     /// The largest possible number of matched points in a worst case for a query
     pub max: usize,
+    /// Sample of matched points, if all conditions of the query could be sampled from indexes
+    pub sketch: Option<PointsSketch>,
 }
 
 impl CardinalityEstimation {
-    #[allow(dead_code)]
     pub fn exact(count: usize) -> Self {
         CardinalityEstimation {
             primary_clauses: vec![],
             min: count,
             exp: count,
             max: count,
+            sketch: None,
         }
     }
 
@@ -49,6 +55,23 @@ impl CardinalityEstimation {
             min: 0,
             exp: total / 2,
             max: total,
+            sketch: None,
+        }
+    }
+}
+
+impl From<&CardinalityEstimation> for CardinalityInfo {
+    fn from(estimation: &CardinalityEstimation) -> Self {
+        CardinalityInfo {
+            primary_clauses: estimation.primary_clauses.iter()
+                .map(|clause| match clause {
+                    PrimaryCondition::Condition(condition) => PrimaryClauseInfo::Condition(condition.clone()),
+                    PrimaryCondition::Ids(ids) => PrimaryClauseInfo::Ids(ids.len()),
+                })
+                .collect(),
+            min: estimation.min,
+            exp: estimation.exp,
+            max: estimation.max,
         }
     }
 }
\ No newline at end of file
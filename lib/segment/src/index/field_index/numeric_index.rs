@@ -8,9 +8,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::index::field_index::{CardinalityEstimation, PrimaryCondition, PayloadBlockCondition};
 use crate::index::field_index::field_index::{FieldIndex, PayloadFieldIndex, PayloadFieldIndexBuilder};
-use crate::types::{FloatPayloadType, IntPayloadType, PayloadType, PointOffsetType, Range, FieldCondition, PayloadKeyType};
+use crate::types::{FloatPayloadType, IntPayloadType, PayloadType, PointOffsetType, Range, FieldCondition, PayloadKeyType, Direction};
+use crate::index::payload_stats::histogram_bucket;
+use crate::index::field_index::sketch::{is_sketched, sketch_rate, PointsSketch};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[repr(C)]
 pub struct Element<N> {
     pub id: PointOffsetType,
     pub value: N,
@@ -22,6 +25,12 @@ pub struct PersistedNumericIndex<N: ToPrimitive + Clone> {
     /// Each point can have several values
     points_count: usize,
     elements: Vec<Element<N>>,
+    /// Sampling rate of the sketch, 0 if index was built without sketch
+    #[serde(default)]
+    sketch_rate: usize,
+    /// Sorted elements of sampled points
+    #[serde(default)]
+    sketch: Vec<Element<N>>,
 }
 
 
@@ -30,45 +39,92 @@ impl<N: ToPrimitive + Clone> PersistedNumericIndex<N> {
         Self {
             points_count: 0,
             elements: vec![],
+            sketch_rate: 0,
+            sketch: vec![],
         }
     }
 
+    /// Build index from sorted elements together with the sketch of sampled points
+    fn from_sorted(points_count: usize, elements: Vec<Element<N>>) -> Self {
+        let sketch_rate = sketch_rate(points_count);
+        let sketch = elements
+            .iter()
+            .filter(|element| is_sketched(element.id, sketch_rate))
+            .cloned()
+            .collect();
+        Self {
+            points_count,
+            elements,
+            sketch_rate,
+            sketch,
+        }
+    }
+
+    pub fn view(&self) -> NumericIndexView<'_, N> {
+        NumericIndexView {
+            points_count: self.points_count,
+            elements: &self.elements,
+            sketch_rate: self.sketch_rate,
+            sketch: &self.sketch,
+        }
+    }
+
+    #[cfg(test)]
     fn search_range(&self, range: &Range) -> (usize, usize) {
-        let mut lower_index = 0;
-        let mut upper_index = self.elements.len();
-
-        range.gt.map(|thr| {
-            let index = self.elements.binary_search_by(|x| {
-                if x.value.to_f64().unwrap() <= thr { Less } else { Greater }
-            }).err().unwrap();
-            lower_index = max(lower_index, index);
-        });
+        self.view().search_range(range)
+    }
 
-        range.gte.map(|thr| {
-            let index = self.elements.binary_search_by(|x| {
-                if x.value.to_f64().unwrap() < thr { Less } else { Greater }
-            }).err().unwrap();
-            lower_index = max(lower_index, index);
-        });
+    pub fn range_cardinality(&self, range: &Range) -> CardinalityEstimation {
+        self.view().range_cardinality(range)
+    }
 
-        range.lt.map(|thr| {
-            let index = self.elements.binary_search_by(|x| {
-                if x.value.to_f64().unwrap() < thr { Less } else { Greater }
-            }).err().unwrap();
-            upper_index = min(upper_index, index);
-        });
+    fn add_many(&mut self, id: PointOffsetType, values: &[N]) {
+        for value in values.iter().cloned() {
+            self.elements.push(Element { id, value })
+        }
+        self.points_count += 1
+    }
 
-        range.lte.map(|thr| {
-            let index = self.elements.binary_search_by(|x| {
-                if x.value.to_f64().unwrap() <= thr { Less } else { Greater }
-            }).err().unwrap();
-            upper_index = min(upper_index, index);
-        });
-        return if lower_index > upper_index {
-            (0, 0)
-        } else {
-            (lower_index, upper_index)
-        };
+    /// Min and max values of points, accepted by `check`
+    pub fn values_range(&self, check: &dyn Fn(PointOffsetType) -> bool) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        self.view().values_range(check)
+    }
+
+    /// Count values of points, accepted by `check`, in equal-width buckets between `min` and `max`
+    pub fn histogram(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        min: FloatPayloadType,
+        max: FloatPayloadType,
+        buckets: usize,
+    ) -> Vec<usize> {
+        self.view().histogram(check, min, max, buckets)
+    }
+
+    /// Visit values of points, accepted by `check`, in given `direction` starting from `from` value including.
+    /// Stops once `visit` returns false
+    pub fn walk_ordered(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        direction: Direction,
+        from: Option<FloatPayloadType>,
+        visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool,
+    ) {
+        self.view().walk_ordered(check, direction, from, visit)
+    }
+}
+
+/// Sorted elements of the numeric index, regardless of where they are stored
+pub struct NumericIndexView<'a, N> {
+    pub points_count: usize,
+    pub elements: &'a [Element<N>],
+    pub sketch_rate: usize,
+    pub sketch: &'a [Element<N>],
+}
+
+impl<'a, N: ToPrimitive> NumericIndexView<'a, N> {
+    fn search_range(&self, range: &Range) -> (usize, usize) {
+        search_sorted_range(self.elements, range)
     }
 
     pub fn range_cardinality(&self, range: &Range) -> CardinalityEstimation {
@@ -89,36 +145,99 @@ impl<N: ToPrimitive + Clone> PersistedNumericIndex<N> {
         // min = max(1, 500 - (1200 - 1000)) = 300
         // exp = 500 / (1200 / 1000) = 416
         // max = min(1000, 500) = 500
+        let min_estimation = max(min(1, values_count), values_count - (total_values - self.points_count as i64)) as usize;
+        let max_estimation = min(self.points_count as i64, values_count) as usize;
+
+        let sketch = if self.sketch_rate > 0 {
+            let (lower_sketch_index, upper_sketch_index) = search_sorted_range(self.sketch, range);
+            let sketch_points = self.sketch[lower_sketch_index..upper_sketch_index].iter().map(|element| element.id);
+            Some(PointsSketch::new(self.sketch_rate, sketch_points))
+        } else {
+            None
+        };
+
+        let exp_estimation = match &sketch {
+            // Sketch counts distinct points, which is more precise than the average for multi-value fields
+            Some(sketch) if value_per_point > 1.0 => max(min_estimation, min(max_estimation, sketch.estimate())),
+            _ => (values_count as f64 / value_per_point) as usize,
+        };
+
         CardinalityEstimation {
             primary_clauses: vec![],
-            min: max(min(1, values_count), values_count - (total_values - self.points_count as i64)) as usize,
-            exp: (values_count as f64 / value_per_point) as usize,
-            max: min(self.points_count as i64, values_count) as usize,
+            min: min_estimation,
+            exp: exp_estimation,
+            max: max_estimation,
+            sketch,
         }
     }
 
-    fn add_many(&mut self, id: PointOffsetType, values: &Vec<N>) {
-        for value in values.iter().cloned() {
-            self.elements.push(Element { id, value })
+    pub fn values_range(&self, check: &dyn Fn(PointOffsetType) -> bool) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        let min_element = self.elements.iter().find(|element| check(element.id))?;
+        let max_element = self.elements.iter().rev().find(|element| check(element.id))?;
+        Some((min_element.value.to_f64().unwrap(), max_element.value.to_f64().unwrap()))
+    }
+
+    pub fn histogram(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        min: FloatPayloadType,
+        max: FloatPayloadType,
+        buckets: usize,
+    ) -> Vec<usize> {
+        let mut counts = vec![0; buckets];
+        let (lower_index, upper_index) = self.search_range(&Range {
+            lt: None,
+            gt: None,
+            gte: Some(min),
+            lte: Some(max),
+        });
+        for element in &self.elements[lower_index..upper_index] {
+            if !check(element.id) {
+                continue;
+            }
+            if let Some(bucket) = histogram_bucket(element.value.to_f64().unwrap(), min, max, buckets) {
+                counts[bucket] += 1;
+            }
+        }
+        counts
+    }
+
+    pub fn walk_ordered(
+        &self,
+        check: &dyn Fn(PointOffsetType) -> bool,
+        direction: Direction,
+        from: Option<FloatPayloadType>,
+        visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool,
+    ) {
+        let range = match direction {
+            Direction::Asc => Range { lt: None, gt: None, gte: from, lte: None },
+            Direction::Desc => Range { lt: None, gt: None, gte: None, lte: from },
+        };
+        let (lower_index, upper_index) = self.search_range(&range);
+        let elements = &self.elements[lower_index..upper_index];
+        let ordered_elements: Box<dyn Iterator<Item=&Element<N>>> = match direction {
+            Direction::Asc => Box::new(elements.iter()),
+            Direction::Desc => Box::new(elements.iter().rev()),
+        };
+        for element in ordered_elements {
+            if check(element.id) && !visit(element.value.to_f64().unwrap(), element.id) {
+                break;
+            }
         }
-        self.points_count += 1
     }
 
-    fn condition_iter(&self, range: &Range) -> Box<dyn Iterator<Item=PointOffsetType> + '_> {
+    fn condition_iter(&self, range: &Range) -> Box<dyn Iterator<Item=PointOffsetType> + 'a> {
         let (lower_index, upper_index) = self.search_range(range);
         Box::new((&self.elements[lower_index..upper_index]).iter().map(|element| element.id))
     }
-}
-
 
-impl<N: ToPrimitive + Clone> PayloadFieldIndex for PersistedNumericIndex<N> {
-    fn filter(&self, condition: &FieldCondition) -> Option<Box<dyn Iterator<Item=PointOffsetType> + '_>> {
+    pub fn filter(&self, condition: &FieldCondition) -> Option<Box<dyn Iterator<Item=PointOffsetType> + 'a>> {
         condition.range
             .as_ref()
             .map(|range| self.condition_iter(range))
     }
 
-    fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
+    pub fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
         condition.range
             .as_ref()
             .map(|range| {
@@ -128,17 +247,18 @@ impl<N: ToPrimitive + Clone> PayloadFieldIndex for PersistedNumericIndex<N> {
             })
     }
 
-    fn payload_blocks(&self, threshold: usize, key: PayloadKeyType) -> Box<dyn Iterator<Item=PayloadBlockCondition> + '_> {
+    pub fn payload_blocks(&self, threshold: usize, key: PayloadKeyType) -> Box<dyn Iterator<Item=PayloadBlockCondition> + 'a> {
         // Creates half-overlapped ranges of points.
-        let num_elements = self.elements.len();
+        let elements = self.elements;
+        let num_elements = elements.len();
         let value_per_point = num_elements as f64 / self.points_count as f64;
         let effective_threshold = (threshold as f64 * value_per_point) as usize;
 
         let iter = (0..num_elements).step_by(effective_threshold / 2).map(move |init_offset| {
             let upper_index = min(num_elements - 1, init_offset + effective_threshold);
 
-            let upper_value = self.elements[upper_index].value.to_f64();
-            let lower_value = self.elements[init_offset].value.to_f64();
+            let upper_value = elements[upper_index].value.to_f64();
+            let lower_value = elements[init_offset].value.to_f64();
 
             PayloadBlockCondition {
                 condition: FieldCondition {
@@ -161,6 +281,61 @@ impl<N: ToPrimitive + Clone> PayloadFieldIndex for PersistedNumericIndex<N> {
     }
 }
 
+
+/// Bounds of elements, which values are within the range. Elements should be sorted by value
+fn search_sorted_range<N: ToPrimitive>(elements: &[Element<N>], range: &Range) -> (usize, usize) {
+    let mut lower_index = 0;
+    let mut upper_index = elements.len();
+
+    if let Some(thr) = range.gt {
+        let index = elements.binary_search_by(|x| {
+            if x.value.to_f64().unwrap() <= thr { Less } else { Greater }
+        }).err().unwrap();
+        lower_index = max(lower_index, index);
+    }
+
+    if let Some(thr) = range.gte {
+        let index = elements.binary_search_by(|x| {
+            if x.value.to_f64().unwrap() < thr { Less } else { Greater }
+        }).err().unwrap();
+        lower_index = max(lower_index, index);
+    }
+
+    if let Some(thr) = range.lt {
+        let index = elements.binary_search_by(|x| {
+            if x.value.to_f64().unwrap() < thr { Less } else { Greater }
+        }).err().unwrap();
+        upper_index = min(upper_index, index);
+    }
+
+    if let Some(thr) = range.lte {
+        let index = elements.binary_search_by(|x| {
+            if x.value.to_f64().unwrap() <= thr { Less } else { Greater }
+        }).err().unwrap();
+        upper_index = min(upper_index, index);
+    }
+
+    if lower_index > upper_index {
+        (0, 0)
+    } else {
+        (lower_index, upper_index)
+    }
+}
+
+impl<N: ToPrimitive + Clone> PayloadFieldIndex for PersistedNumericIndex<N> {
+    fn filter(&self, condition: &FieldCondition) -> Option<Box<dyn Iterator<Item=PointOffsetType> + '_>> {
+        self.view().filter(condition)
+    }
+
+    fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
+        self.view().estimate_cardinality(condition)
+    }
+
+    fn payload_blocks(&self, threshold: usize, key: PayloadKeyType) -> Box<dyn Iterator<Item=PayloadBlockCondition> + '_> {
+        self.view().payload_blocks(threshold, key)
+    }
+}
+
 impl PayloadFieldIndexBuilder for PersistedNumericIndex<FloatPayloadType> {
     fn add(&mut self, id: PointOffsetType, value: &PayloadType) {
         match value {
@@ -172,10 +347,7 @@ impl PayloadFieldIndexBuilder for PersistedNumericIndex<FloatPayloadType> {
     fn build(&mut self) -> FieldIndex {
         let mut elements = mem::replace(&mut self.elements, vec![]);
         elements.sort_by_key(|el| OrderedFloat(el.value));
-        FieldIndex::FloatIndex(PersistedNumericIndex {
-            points_count: self.points_count,
-            elements,
-        })
+        FieldIndex::FloatIndex(PersistedNumericIndex::from_sorted(self.points_count, elements))
     }
 }
 
@@ -190,10 +362,7 @@ impl PayloadFieldIndexBuilder for PersistedNumericIndex<IntPayloadType> {
     fn build(&mut self) -> FieldIndex {
         let mut elements = mem::replace(&mut self.elements, vec![]);
         elements.sort_by_key(|el| el.value);
-        FieldIndex::IntIndex(PersistedNumericIndex {
-            points_count: self.points_count,
-            elements,
-        })
+        FieldIndex::IntIndex(PersistedNumericIndex::from_sorted(self.points_count, elements))
     }
 }
 
@@ -217,6 +386,8 @@ mod tests {
                 Element { id: 8, value: 30.0 },
                 Element { id: 9, value: 33.0 },
             ],
+            sketch_rate: 0,
+            sketch: vec![],
         };
 
         let res = index.search_range(&Range {
@@ -253,6 +424,8 @@ mod tests {
                 Element { id: 8, value: 30.0 },
                 Element { id: 9, value: 33.0 },
             ],
+            sketch_rate: 0,
+            sketch: vec![],
         };
 
         let estimation = index.range_cardinality(&Range {
@@ -276,6 +449,59 @@ mod tests {
         assert_eq!(estimation.exp, 0);
     }
 
+    #[test]
+    fn test_walk_ordered() {
+        let index = PersistedNumericIndex {
+            points_count: 5,
+            elements: vec![
+                Element { id: 1, value: 1.0 },
+                Element { id: 2, value: 3.0 },
+                Element { id: 3, value: 6.0 },
+                Element { id: 4, value: 9.0 },
+                Element { id: 5, value: 12.0 },
+            ],
+            sketch_rate: 0,
+            sketch: vec![],
+        };
+
+        let mut visited = vec![];
+        index.walk_ordered(&|id| id != 3, Direction::Desc, Some(9.0), &mut |_value, id| {
+            visited.push(id);
+            visited.len() < 3
+        });
+        assert_eq!(visited, vec![4, 2, 1]);
+
+        let mut visited = vec![];
+        index.walk_ordered(&|_id| true, Direction::Asc, Some(5.0), &mut |value, _id| {
+            visited.push(value);
+            true
+        });
+        assert_eq!(visited, vec![6.0, 9.0, 12.0]);
+    }
+
+    #[test]
+    fn test_multi_value_cardinality() {
+        let mut builder = PersistedNumericIndex::<IntPayloadType>::new();
+        for id in 0..100 {
+            let value = id as IntPayloadType;
+            builder.add(id, &PayloadType::Integer(vec![value, value + 1000, value + 2000]));
+        }
+        let index = match builder.build() {
+            FieldIndex::IntIndex(index) => index,
+            _ => panic!("Unexpected index type"),
+        };
+
+        let estimation = index.range_cardinality(&Range {
+            lt: Some(50.0),
+            gt: None,
+            gte: None,
+            lte: None,
+        });
+        // Average number of values per point would give 50 / 3 = 16
+        assert_eq!(estimation.exp, 50);
+        assert_eq!(estimation.sketch.unwrap().points.len(), 50);
+    }
+
     #[test]
     fn test_serde() {
         let index = PersistedNumericIndex {
@@ -284,6 +510,8 @@ mod tests {
                 Element { id: 1, value: 1 },
                 Element { id: 2, value: 3 }
             ],
+            sketch_rate: 0,
+            sketch: vec![],
         };
 
         let json = serde_json::to_string_pretty(&index).unwrap();
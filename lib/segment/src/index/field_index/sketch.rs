@@ -0,0 +1,145 @@
+use std::cmp::max;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::PointOffsetType;
+
+/// Expected number of sampled points in a sketch of the whole field index
+const SKETCH_SIZE: usize = 1024;
+
+/// Choose sampling rate for the index of `points_count` points.
+/// Rate is always a power of two, so sketches with different rates are nested:
+/// point sampled with a higher rate is also sampled with any lower rate.
+pub fn sketch_rate(points_count: usize) -> usize {
+    max(1, points_count / SKETCH_SIZE).next_power_of_two()
+}
+
+/// Deterministic pseudo-random selection of about `1 / rate` of all points
+pub fn is_sketched(point_id: PointOffsetType, rate: usize) -> bool {
+    // Fibonacci hashing, spreads sequential ids uniformly
+    let hash = (point_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+    hash % rate as u64 == 0
+}
+
+/// Uniform sample of points, which satisfy some condition.
+/// Sketches of different conditions are built from the same points,
+/// so the intersection of sketches is a sample of points which satisfy both conditions.
+/// This allows to estimate cardinality of correlated conditions without independence assumption.
+///
+/// Sketches are used instead of separate per-field histograms and value-frequency sketches:
+/// numeric index keeps sampled elements in order of value, which works as an equi-depth histogram of the field,
+/// and map index already stores exact number of points of each value.
+/// Unlike histograms, sketches of different fields could be intersected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointsSketch {
+    /// One of `rate` points is sampled
+    pub rate: usize,
+    /// Sorted unique ids of sampled points
+    pub points: Vec<PointOffsetType>,
+}
+
+impl PointsSketch {
+    pub fn new(rate: usize, points: impl Iterator<Item=PointOffsetType>) -> Self {
+        let mut points: Vec<_> = points.filter(|point_id| is_sketched(*point_id, rate)).collect();
+        points.sort_unstable();
+        points.dedup();
+        PointsSketch { rate, points }
+    }
+
+    /// Expected number of points, represented by this sketch
+    pub fn estimate(&self) -> usize {
+        self.points.len() * self.rate
+    }
+
+    /// Min and max number of points with confidence at about 0.95
+    pub fn confidence_interval(&self) -> (usize, usize) {
+        let delta = (2.0 * (self.points.len() as f64).sqrt() * self.rate as f64) as usize + self.rate;
+        let estimate = self.estimate();
+        (estimate.saturating_sub(delta), estimate + delta)
+    }
+
+    fn with_rate(&self, rate: usize) -> PointsSketch {
+        if rate == self.rate {
+            return self.clone();
+        }
+        PointsSketch::new(rate, self.points.iter().cloned())
+    }
+
+    /// Sample of points, present in both sketches
+    pub fn intersect(&self, other: &PointsSketch) -> PointsSketch {
+        let rate = max(self.rate, other.rate);
+        let (left, right) = (self.with_rate(rate), other.with_rate(rate));
+        let mut points = Vec::with_capacity(left.points.len().min(right.points.len()));
+        let (mut i, mut j) = (0, 0);
+        while i < left.points.len() && j < right.points.len() {
+            if left.points[i] < right.points[j] {
+                i += 1;
+            } else if left.points[i] > right.points[j] {
+                j += 1;
+            } else {
+                points.push(left.points[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+        PointsSketch { rate, points }
+    }
+
+    /// Sample of points, present in any of sketches
+    pub fn union(&self, other: &PointsSketch) -> PointsSketch {
+        let rate = max(self.rate, other.rate);
+        let (left, right) = (self.with_rate(rate), other.with_rate(rate));
+        PointsSketch::new(rate, left.points.into_iter().chain(right.points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sketch_rate() {
+        assert_eq!(sketch_rate(0), 1);
+        assert_eq!(sketch_rate(1000), 1);
+        assert_eq!(sketch_rate(3 * SKETCH_SIZE), 4);
+    }
+
+    #[test]
+    fn test_nested_sampling() {
+        for point_id in 0..10_000 {
+            if is_sketched(point_id, 8) {
+                assert!(is_sketched(point_id, 4));
+                assert!(is_sketched(point_id, 2));
+            }
+        }
+        let sampled = (0..10_000).filter(|point_id| is_sketched(*point_id, 8)).count();
+        assert!(sampled > 1000 && sampled < 1500, "{}", sampled);
+    }
+
+    #[test]
+    fn test_correlated_intersection() {
+        // Second condition matches exactly the same points, so independence assumption would give 25
+        let first = PointsSketch::new(2, 0..50);
+        let second = PointsSketch::new(4, 0..50);
+        let both = first.intersect(&second);
+
+        assert_eq!(both.rate, 4);
+        assert_eq!(both.points, second.points);
+
+        let disjoint = PointsSketch::new(1, 50..100);
+        assert!(first.intersect(&disjoint).points.is_empty());
+        assert_eq!(first.union(&disjoint).rate, 2);
+        assert!(first.union(&disjoint).points.len() > first.points.len());
+    }
+
+    #[test]
+    fn test_confidence_interval() {
+        let sketch = PointsSketch::new(4, 0..10_000);
+        let (min, max) = sketch.confidence_interval();
+        assert!(min < sketch.estimate() && sketch.estimate() < max);
+        assert!(min < 10_000 && 10_000 < max, "{} {}", min, max);
+
+        let empty = PointsSketch::new(4, 0..0);
+        assert_eq!(empty.confidence_interval(), (0, 4));
+    }
+}
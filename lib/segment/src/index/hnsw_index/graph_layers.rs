@@ -99,6 +99,14 @@ impl GraphLayers {
         &self.links_layers[point_id as usize][level]
     }
 
+    /// Get links of the point on the bottom level, which contains all points of the graph
+    pub fn neighbours(&self, point_id: PointOffsetType) -> &[PointOffsetType] {
+        match self.links_layers.get(point_id as usize).and_then(|layers| layers.first()) {
+            Some(links) => links,
+            None => &[],
+        }
+    }
+
     /// Get M based on current level
     fn get_m(&self, level: usize) -> usize {
         return if level == 0 { self.m0 } else { self.m };
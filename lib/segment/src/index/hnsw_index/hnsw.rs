@@ -2,12 +2,16 @@ use crate::entry::entry_point::OperationResult;
 use std::path::{Path, PathBuf};
 use std::fs::create_dir_all;
 use crate::index::index::{VectorIndex, PayloadIndex};
-use crate::types::{SearchParams, Filter, PointOffsetType, VectorElementType, FieldCondition, HnswConfig};
-use crate::vector_storage::vector_storage::{ScoredPointOffset, VectorStorage};
+use crate::types::{SearchParams, Filter, PointOffsetType, VectorElementType, FieldCondition, HnswConfig, ExampleQuery, ScoreType, SearchProfile, SearchPath, CardinalityInfo};
+use crate::vector_storage::vector_storage::{ScoredPointOffset, VectorStorage, RawScorer};
+use crate::vector_storage::counting_scorer::CountingRawScorer;
+use crate::index::field_index::CardinalityEstimation;
+use crate::spaces::tools::peek_top_scores_iterable;
 use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use crate::payload_storage::payload_storage::ConditionChecker;
 use std::cmp::max;
+use std::collections::HashSet;
 use std::ops::Deref;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use rand::thread_rng;
@@ -18,6 +22,7 @@ use crate::types::Condition::Field;
 use crate::index::hnsw_index::build_condition_checker::BuildConditionChecker;
 use crate::index::sample_estimation::sample_check_cardinality;
 use log::debug;
+use itertools::Itertools;
 
 
 const HNSW_USE_HEURISTIC: bool = true;
@@ -136,69 +141,222 @@ impl HNSWIndex {
     }
 
     pub fn search_with_graph(&self, vector: &Vec<VectorElementType>, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer(vector.clone());
+        self.search_with_raw_scorer(raw_scorer.as_ref(), filter, top, params)
+    }
+
+    /// Traverse graph with arbitrary scorer of the query
+    fn search_with_raw_scorer(&self, raw_scorer: &dyn RawScorer, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<ScoredPointOffset> {
         let req_ef = params.and_then(|params| params.hnsw_ef).unwrap_or(self.config.ef);
 
         // ef should always be bigger that required top
         let ef = max(req_ef, top);
 
-        let vector_storage = self.vector_storage.borrow();
-        let raw_scorer = vector_storage.raw_scorer(vector.clone());
         let condition_checker = self.condition_checker.borrow();
 
         let points_scorer = FilteredScorer {
-            raw_scorer: raw_scorer.as_ref(),
+            raw_scorer,
             condition_checker: condition_checker.deref(),
             filter,
         };
 
         self.graph.search(top, ef, &points_scorer)
     }
+
+    /// Decide if it is cheaper to score all points which satisfy filter than to use HNSW graph.
+    /// Depending on the amount of filtered-out points the optimal strategy could be
+    /// - to retrieve possible points and score them after
+    /// - to use HNSW index with filtering condition
+    fn plan_search(&self, query_filter: &Filter) -> SearchPlan {
+        let cardinality = self.payload_index.borrow().estimate_cardinality(query_filter);
+        let threshold = self.config.indexing_threshold;
+
+        let estimated_plain = if cardinality.max < threshold {
+            // if cardinality is small - use plain index
+            Some(true)
+        } else if cardinality.min > threshold {
+            // if cardinality is high enough - use HNSW index
+            Some(false)
+        } else {
+            // Sketch is a persisted sample of matched points, use it if it is confident enough
+            cardinality.sketch.as_ref().and_then(|sketch| {
+                let (min, max) = sketch.confidence_interval();
+                if max < threshold {
+                    Some(true)
+                } else if min > threshold {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+        };
+
+        if let Some(plain) = estimated_plain {
+            return SearchPlan { plain, cardinality, sample_check: false };
+        }
+
+        // Fast cardinality estimation is not enough, do sample estimation of cardinality
+        let vector_storage = self.vector_storage.borrow();
+        let condition_checker = self.condition_checker.borrow();
+        let plain = !sample_check_cardinality(
+            vector_storage.sample_ids(),
+            |idx| condition_checker.check(idx, query_filter),
+            threshold,
+            vector_storage.vector_count(),
+        );
+        SearchPlan { plain, cardinality, sample_check: true }
+    }
+
+    fn prefer_plain_search(&self, query_filter: &Filter) -> bool {
+        self.plan_search(query_filter).plain
+    }
+
+    /// Score points which satisfy filter or traverse graph, whichever is cheaper for the filter.
+    /// Returns the plan of filtered search together with the result
+    fn search_with_plan(&self, raw_scorer: &dyn RawScorer, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> (Vec<ScoredPointOffset>, Option<SearchPlan>) {
+        let plan = filter.map(|query_filter| self.plan_search(query_filter));
+
+        let result = match (filter, &plan) {
+            (Some(query_filter), Some(plan)) if plan.plain => {
+                let payload_index = self.payload_index.borrow();
+                let mut filtered_ids = payload_index.query_points(query_filter);
+                peek_top_scores_iterable(raw_scorer.score_points(&mut filtered_ids), top)
+            }
+            _ => self.search_with_raw_scorer(raw_scorer, filter, top, params)
+        };
+
+        (result, plan)
+    }
+
+    /// Same as `search_with_plan`, but the stored point itself is excluded from the result
+    fn search_by_point_with_plan(&self, raw_scorer: &dyn RawScorer, point_id: PointOffsetType, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> (Vec<ScoredPointOffset>, Option<SearchPlan>) {
+        // The point itself is most likely found first, so one extra result is required
+        let (mut result, plan) = self.search_with_plan(raw_scorer, filter, top.saturating_add(1), params);
+        result.retain(|scored| scored.idx != point_id);
+        result.truncate(top);
+        (result, plan)
+    }
+}
+
+/// Search strategy, chosen for the filter
+struct SearchPlan {
+    plain: bool,
+    cardinality: CardinalityEstimation,
+    /// If cardinality was checked on a random sample of points
+    sample_check: bool,
+}
+
+impl SearchPlan {
+    /// Profile of the search, done according to the plan. No plan means unfiltered graph search
+    fn profile(plan: Option<&SearchPlan>, scored_points: usize) -> SearchProfile {
+        let plain = plan.map(|plan| plan.plain).unwrap_or(false);
+        SearchProfile {
+            cardinality: plan.map(|plan| CardinalityInfo::from(&plan.cardinality)),
+            sample_check: plan.map(|plan| plan.sample_check).unwrap_or(false),
+            path: if plain { SearchPath::Plain } else { SearchPath::Graph },
+            scored_points,
+        }
+    }
 }
 
 
 impl VectorIndex for HNSWIndex {
     fn search(&self, vector: &Vec<VectorElementType>, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<ScoredPointOffset> {
-        match filter {
-            None => self.search_with_graph(vector, None, top, params),
-            Some(query_filter) => {
-                // depending on the amount of filtered-out points the optimal strategy could be
-                // - to retrieve possible points and score them after
-                // - to use HNSW index with filtering condition
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer(vector.clone());
+        let (result, _plan) = self.search_with_plan(raw_scorer.as_ref(), filter, top, params);
+        result
+    }
 
-                let payload_index = self.payload_index.borrow();
-                let query_cardinality = payload_index.estimate_cardinality(query_filter);
+    fn search_explain(&self, vector: &[VectorElementType], filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> (Vec<ScoredPointOffset>, SearchProfile) {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer(vector.to_vec());
+        let counting_scorer = CountingRawScorer::new(raw_scorer.as_ref());
+
+        let (result, plan) = self.search_with_plan(&counting_scorer, filter, top, params);
+        (result, SearchPlan::profile(plan.as_ref(), counting_scorer.scored_points()))
+    }
 
+    fn search_batch(&self, vectors: &[&Vec<VectorElementType>], filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<Vec<ScoredPointOffset>> {
+        match filter {
+            Some(query_filter) if self.prefer_plain_search(query_filter) => {
+                // Filtered points are shared between all vectors of the batch
+                let filtered_ids = self.payload_index.borrow().query_points(query_filter).collect_vec();
                 let vector_storage = self.vector_storage.borrow();
+                vectors.iter()
+                    .map(|vector| vector_storage.score_points(vector, &mut filtered_ids.iter().cloned(), top))
+                    .collect()
+            }
+            _ => vectors.iter()
+                .map(|vector| self.search_with_graph(vector, filter, top, params))
+                .collect()
+        }
+    }
 
-                if query_cardinality.max < self.config.indexing_threshold {
-                    // if cardinality is small - use plain index
-                    let mut filtered_ids = payload_index.query_points(query_filter);
-                    return vector_storage.score_points(vector, &mut filtered_ids, top);
-                }
+    fn search_examples(&self, query: &ExampleQuery, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_examples(query);
+        let (result, _plan) = self.search_with_plan(raw_scorer.as_ref(), filter, top, params);
+        result
+    }
 
-                if query_cardinality.min > self.config.indexing_threshold {
-                    // if cardinality is high enough - use HNSW index
-                    return self.search_with_graph(vector, filter, top, params);
-                }
+    fn search_by_point(&self, point_id: PointOffsetType, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+        let (result, _plan) = self.search_by_point_with_plan(raw_scorer.as_ref(), point_id, filter, top, params);
+        result
+    }
 
-                // Fast cardinality estimation is not enough, do sample estimation of cardinality
-
-                let condition_checker = self.condition_checker.borrow();
-                return if sample_check_cardinality(
-                    vector_storage.sample_ids(),
-                    |idx| condition_checker.check(idx, query_filter),
-                    self.config.indexing_threshold,
-                    vector_storage.vector_count()
-                ) {
-                    // if cardinality is high enough - use HNSW index
-                    self.search_with_graph(vector, filter, top, params)
-                } else {
-                    // if cardinality is small - use plain index
-                    let mut filtered_ids = payload_index.query_points(query_filter);
-                    vector_storage.score_points(vector, &mut filtered_ids, top)
-                };
+    fn search_by_point_explain(&self, point_id: PointOffsetType, filter: Option<&Filter>, top: usize, params: Option<&SearchParams>) -> (Vec<ScoredPointOffset>, SearchProfile) {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+        let counting_scorer = CountingRawScorer::new(raw_scorer.as_ref());
+
+        let (result, plan) = self.search_by_point_with_plan(&counting_scorer, point_id, filter, top, params);
+        (result, SearchPlan::profile(plan.as_ref(), counting_scorer.scored_points()))
+    }
+
+    fn similar_pairs(&self, threshold: ScoreType, filter: Option<&Filter>, limit: usize) -> Vec<(PointOffsetType, PointOffsetType, ScoreType)> {
+        let vector_storage = self.vector_storage.borrow();
+        let condition_checker = self.condition_checker.borrow();
+        let payload_index = self.payload_index.borrow();
+
+        let point_ids = match filter {
+            Some(query_filter) => payload_index.query_points(query_filter),
+            None => vector_storage.iter_ids(),
+        };
+
+        // Graph neighbours are the closest points, so only they are verified as candidates.
+        // Links are not symmetric, so the same pair could be found from both sides
+        let mut seen_pairs: HashSet<(PointOffsetType, PointOffsetType)> = HashSet::new();
+        let mut pairs = vec![];
+        for point_id in point_ids {
+            if pairs.len() >= limit {
+                break;
+            }
+            let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+            for &other_id in self.graph.neighbours(point_id) {
+                if !raw_scorer.check_point(other_id) {
+                    continue;
+                }
+                if let Some(query_filter) = filter {
+                    if !condition_checker.check(other_id, query_filter) {
+                        continue;
+                    }
+                }
+                let pair = (point_id.min(other_id), point_id.max(other_id));
+                if !seen_pairs.insert(pair) {
+                    continue;
+                }
+                let score = raw_scorer.score_internal(point_id, other_id);
+                if score >= threshold {
+                    pairs.push((pair.0, pair.1, score));
+                }
             }
         }
+        pairs.truncate(limit);
+        pairs
     }
 
     fn build_index(&mut self) -> OperationResult<()> {
@@ -1,4 +1,5 @@
-use crate::types::{Filter, PointOffsetType, VectorElementType, SearchParams, PayloadKeyType};
+use crate::types::{Filter, PointOffsetType, VectorElementType, SearchParams, PayloadKeyType, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, Direction, SearchProfile, FieldCondition};
+use std::collections::HashMap;
 use crate::vector_storage::vector_storage::ScoredPointOffset;
 use crate::entry::entry_point::OperationResult;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
@@ -13,6 +14,55 @@ pub trait VectorIndex {
               params: Option<&SearchParams>,
     ) -> Vec<ScoredPointOffset>;
 
+    /// Same as `search`, but also returns details of how the query was processed
+    fn search_explain(&self,
+                      vector: &[VectorElementType],
+                      filter: Option<&Filter>,
+                      top: usize,
+                      params: Option<&SearchParams>,
+    ) -> (Vec<ScoredPointOffset>, SearchProfile);
+
+    /// Search multiple vectors with the same filter and params at once.
+    /// Filter-dependent preparations are done only once for the whole batch.
+    fn search_batch(&self,
+                    vectors: &[&Vec<VectorElementType>],
+                    filter: Option<&Filter>,
+                    top: usize,
+                    params: Option<&SearchParams>,
+    ) -> Vec<Vec<ScoredPointOffset>>;
+
+    /// Return list of Ids, best scored against multiple examples of the query
+    fn search_examples(&self,
+                       query: &ExampleQuery,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> Vec<ScoredPointOffset>;
+
+    /// Return list of Ids closest to the stored point, excluding the point itself.
+    /// Stored vector is used as is, without repeated pre-processing
+    fn search_by_point(&self,
+                       point_id: PointOffsetType,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> Vec<ScoredPointOffset>;
+
+    /// Same as `search_by_point`, but also returns details of how the query was processed
+    fn search_by_point_explain(&self,
+                               point_id: PointOffsetType,
+                               filter: Option<&Filter>,
+                               top: usize,
+                               params: Option<&SearchParams>,
+    ) -> (Vec<ScoredPointOffset>, SearchProfile);
+
+    /// Find at most `limit` pairs of points, which satisfy filter and have similarity not less than `threshold`.
+    /// Each pair is returned once, with smaller id first
+    fn similar_pairs(&self,
+                     threshold: ScoreType,
+                     filter: Option<&Filter>,
+                     limit: usize,
+    ) -> Vec<(PointOffsetType, PointOffsetType, ScoreType)>;
 
     /// Force internal index rebuild.
     fn build_index(&mut self) -> OperationResult<()>;
@@ -28,13 +78,52 @@ pub trait PayloadIndex {
     /// Remove index
     fn drop_index(&mut self, field: &PayloadKeyType) -> OperationResult<()>;
 
+    /// Mark indexes of `fields` as outdated after payload of some points was changed.
+    /// Outdated indexes are not used until they are re-built
+    fn fields_updated(&mut self, fields: &[PayloadKeyType]) -> OperationResult<()>;
+
+    /// Get indexed fields, which indexes are outdated by payload changes
+    fn outdated_fields(&self) -> Vec<PayloadKeyType>;
+
     /// Estimate amount of points (min, max) which satisfies filtering condition.
     fn estimate_cardinality(&self, query: &Filter) -> CardinalityEstimation;
 
+    /// Check if point satisfies field condition using field indexes only.
+    /// Returns `None` if the condition can't be checked without payload
+    fn check_field_condition(&self, point_id: PointOffsetType, condition: &FieldCondition) -> Option<bool>;
+
     /// Return list of all point ids, which satisfy filtering criteria
     fn query_points<'a>(&'a self, query: &'a Filter) -> Box<dyn Iterator<Item=PointOffsetType> + 'a>;
 
     /// Iterate conditions for payload blocks with minimum size of `threshold`
     /// Required for building HNSW index
     fn payload_blocks(&self, threshold: usize) -> Box<dyn Iterator<Item=PayloadBlockCondition> + '_>;
+
+    /// Count points for each keyword or integer value of the field among points, which satisfy filter
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize>;
+
+    /// Min and max of numeric field values among points, which satisfy filter
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)>;
+
+    /// Count numeric field values in `buckets` equal-width buckets between `min` and `max`
+    fn numeric_histogram(
+        &self,
+        key: &PayloadKeyType,
+        filter: Option<&Filter>,
+        min: FloatPayloadType,
+        max: FloatPayloadType,
+        buckets: usize,
+    ) -> Vec<usize>;
+
+    /// Visit numeric field values of points, which satisfy filter, ordered in given `direction`.
+    /// Walking starts from `from` value including and stops once `visit` returns false.
+    /// Point with multiple values is visited for each of them.
+    fn walk_ordered(
+        &self,
+        key: &PayloadKeyType,
+        direction: Direction,
+        from: Option<FloatPayloadType>,
+        filter: Option<&Filter>,
+        visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool,
+    );
 }
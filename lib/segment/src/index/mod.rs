@@ -3,7 +3,8 @@ pub mod index;
 pub mod struct_payload_index;
 pub mod query_estimator;
 pub mod hnsw_index;
-mod field_index;
+pub mod field_index;
+pub mod payload_stats;
 mod payload_config;
 mod visited_pool;
 mod sample_estimation;
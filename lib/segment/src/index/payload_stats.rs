@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::iter;
+
+use crate::payload_storage::payload_storage::PayloadStorage;
+use crate::types::{Direction, FacetValue, FloatPayloadType, PayloadKeyType, PayloadType, PointOffsetType};
+
+/// Index of the equal-width histogram bucket between `min` and `max` for the `value`.
+/// Returns `None` if value is out of range.
+pub fn histogram_bucket(
+    value: FloatPayloadType,
+    min: FloatPayloadType,
+    max: FloatPayloadType,
+    buckets: usize,
+) -> Option<usize> {
+    if buckets == 0 || value < min || value > max {
+        return None;
+    }
+    if max <= min {
+        return Some(0);
+    }
+    let bucket = ((value - min) / (max - min) * buckets as FloatPayloadType) as usize;
+    Some(bucket.min(buckets - 1))
+}
+
+/// Count points for each keyword or integer value of the field by reading payload of each point
+pub fn scan_facet_counts(
+    points: impl Iterator<Item=PointOffsetType>,
+    payload: &dyn PayloadStorage,
+    key: &PayloadKeyType,
+) -> HashMap<FacetValue, usize> {
+    let mut counts: HashMap<FacetValue, usize> = HashMap::new();
+    for point_id in points {
+        if let Some(value) = payload.payload(point_id).get(key) {
+            for facet_value in FacetValue::from_payload(value) {
+                *counts.entry(facet_value).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Read all numeric values of the field by reading payload of each point
+pub fn scan_numeric_values(
+    points: impl Iterator<Item=PointOffsetType>,
+    payload: &dyn PayloadStorage,
+    key: &PayloadKeyType,
+) -> Vec<FloatPayloadType> {
+    let mut values = vec![];
+    for point_id in points {
+        match payload.payload(point_id).get(key) {
+            Some(PayloadType::Integer(numbers)) => values.extend(numbers.iter().map(|x| *x as FloatPayloadType)),
+            Some(PayloadType::Float(numbers)) => values.extend(numbers.iter().cloned()),
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Read numeric values of the field together with point ids, ordered by value in given `direction`.
+/// Values before `from` are skipped.
+pub fn scan_ordered_values(
+    points: impl Iterator<Item=PointOffsetType>,
+    payload: &dyn PayloadStorage,
+    key: &PayloadKeyType,
+    direction: Direction,
+    from: Option<FloatPayloadType>,
+) -> Vec<(FloatPayloadType, PointOffsetType)> {
+    let mut values = vec![];
+    for point_id in points {
+        let point_values = scan_numeric_values(iter::once(point_id), payload, key);
+        values.extend(point_values.into_iter().map(|value| (value, point_id)));
+    }
+    values.retain(|(value, _)| match (from, direction) {
+        (None, _) => true,
+        (Some(from), Direction::Asc) => *value >= from,
+        (Some(from), Direction::Desc) => *value <= from,
+    });
+    values.sort_by(|(value_a, _), (value_b, _)| direction.compare(*value_a, *value_b));
+    values
+}
+
+/// Min and max of given values
+pub fn values_range(values: &[FloatPayloadType]) -> Option<(FloatPayloadType, FloatPayloadType)> {
+    values.iter().fold(None, |range, value| match range {
+        None => Some((*value, *value)),
+        Some((min, max)) => Some((min.min(*value), max.max(*value))),
+    })
+}
+
+/// Count given values in equal-width buckets between `min` and `max`
+pub fn values_histogram(
+    values: &[FloatPayloadType],
+    min: FloatPayloadType,
+    max: FloatPayloadType,
+    buckets: usize,
+) -> Vec<usize> {
+    let mut counts = vec![0; buckets];
+    for value in values {
+        if let Some(bucket) = histogram_bucket(*value, min, max, buckets) {
+            counts[bucket] += 1;
+        }
+    }
+    counts
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let values = vec![0.0, 1.0, 2.5, 5.0, 9.9, 10.0, 11.0];
+        let (min, max) = values_range(&values[..6]).unwrap();
+        assert_eq!((min, max), (0.0, 10.0));
+
+        let histogram = values_histogram(&values, min, max, 2);
+        // 11.0 is out of range, max value is included into the last bucket
+        assert_eq!(histogram, vec![3, 3]);
+
+        assert_eq!(histogram_bucket(3.0, 3.0, 3.0, 5), Some(0));
+        assert_eq!(histogram_bucket(1.0, 0.0, 10.0, 0), None);
+    }
+}
@@ -1,20 +1,30 @@
-use crate::vector_storage::vector_storage::{ScoredPointOffset, VectorStorage};
+use crate::vector_storage::vector_storage::{ScoredPointOffset, VectorStorage, RawScorer};
+use crate::vector_storage::counting_scorer::CountingRawScorer;
 use crate::index::index::{VectorIndex, PayloadIndex};
-use crate::types::{Filter, VectorElementType, SearchParams, PointOffsetType, PayloadKeyType};
-use crate::payload_storage::payload_storage::{ConditionChecker};
+use crate::types::{Filter, VectorElementType, SearchParams, PointOffsetType, PayloadKeyType, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, Direction, SearchProfile, SearchPath, CardinalityInfo, FieldCondition};
+use crate::spaces::tools::peek_top_scores_iterable;
+use crate::payload_storage::payload_storage::{ConditionChecker, PayloadStorage};
+use crate::index::payload_stats::{scan_facet_counts, scan_numeric_values, scan_ordered_values, values_range, values_histogram};
+use std::collections::{HashMap, HashSet};
 
 use std::sync::Arc;
+use std::ops::Deref;
 use atomic_refcell::AtomicRefCell;
 use crate::entry::entry_point::OperationResult;
 use crate::index::payload_config::PayloadConfig;
 use std::path::{Path, PathBuf};
 use std::fs::create_dir_all;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
+use itertools::Itertools;
+
+/// Number of closest points verified as similar pair candidates of each point
+const SIMILAR_PAIRS_CANDIDATES: usize = 16;
 
 
 pub struct PlainPayloadIndex {
     condition_checker: Arc<AtomicRefCell<dyn ConditionChecker>>,
     vector_storage: Arc<AtomicRefCell<dyn VectorStorage>>,
+    payload: Arc<AtomicRefCell<dyn PayloadStorage>>,
     config: PayloadConfig,
     path: PathBuf
 }
@@ -31,9 +41,18 @@ impl PlainPayloadIndex {
         self.config.save(&config_path)
     }
 
+    fn numeric_values(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Vec<FloatPayloadType> {
+        let payload = self.payload.borrow();
+        match filter {
+            Some(filter) => scan_numeric_values(self.query_points(filter), payload.deref(), key),
+            None => scan_numeric_values(self.vector_storage.borrow().iter_ids(), payload.deref(), key),
+        }
+    }
+
     pub fn open(
         condition_checker: Arc<AtomicRefCell<dyn ConditionChecker>>,
         vector_storage: Arc<AtomicRefCell<dyn VectorStorage>>,
+        payload: Arc<AtomicRefCell<dyn PayloadStorage>>,
         path: &Path,
     ) -> OperationResult<Self> {
         create_dir_all(path)?;
@@ -48,6 +67,7 @@ impl PlainPayloadIndex {
         let index = PlainPayloadIndex {
             condition_checker,
             vector_storage,
+            payload,
             config,
             path: path.to_owned()
         };
@@ -78,16 +98,29 @@ impl PayloadIndex for PlainPayloadIndex {
         self.save_config()
     }
 
+    fn fields_updated(&mut self, _fields: &[PayloadKeyType]) -> OperationResult<()> {
+        Ok(())
+    }
+
+    fn outdated_fields(&self) -> Vec<PayloadKeyType> {
+        vec![]
+    }
+
     fn estimate_cardinality(&self, _query: &Filter) -> CardinalityEstimation {
         let total_points = self.vector_storage.borrow().vector_count();
         CardinalityEstimation {
             primary_clauses: vec![],
             min: 0,
             exp: total_points / 2,
-            max: total_points
+            max: total_points,
+            sketch: None,
         }
     }
 
+    fn check_field_condition(&self, _point_id: PointOffsetType, _condition: &FieldCondition) -> Option<bool> {
+        None
+    }
+
     fn query_points<'a>(&'a self, query: &'a Filter) -> Box<dyn Iterator<Item=PointOffsetType> + 'a> {
         let mut matched_points = vec![];
         let condition_checker = self.condition_checker.borrow();
@@ -103,6 +136,37 @@ impl PayloadIndex for PlainPayloadIndex {
         // No blocks for un-indexed payload
         Box::new(vec![].into_iter())
     }
+
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize> {
+        let payload = self.payload.borrow();
+        match filter {
+            Some(filter) => scan_facet_counts(self.query_points(filter), payload.deref(), key),
+            None => scan_facet_counts(self.vector_storage.borrow().iter_ids(), payload.deref(), key),
+        }
+    }
+
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        values_range(&self.numeric_values(key, filter))
+    }
+
+    fn numeric_histogram(&self, key: &PayloadKeyType, filter: Option<&Filter>, min: FloatPayloadType, max: FloatPayloadType, buckets: usize) -> Vec<usize> {
+        values_histogram(&self.numeric_values(key, filter), min, max, buckets)
+    }
+
+    fn walk_ordered(&self, key: &PayloadKeyType, direction: Direction, from: Option<FloatPayloadType>, filter: Option<&Filter>, visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool) {
+        let values = {
+            let payload = self.payload.borrow();
+            match filter {
+                Some(filter) => scan_ordered_values(self.query_points(filter), payload.deref(), key, direction, from),
+                None => scan_ordered_values(self.vector_storage.borrow().iter_ids(), payload.deref(), key, direction, from),
+            }
+        };
+        for (value, point_id) in values {
+            if !visit(value, point_id) {
+                break;
+            }
+        }
+    }
 }
 
 
@@ -121,6 +185,35 @@ impl PlainIndex {
             payload_index
         };
     }
+
+    /// Score all points which satisfy filter, except `exclude_id`, and count scored points
+    fn search_explain_with_scorer(
+        &self,
+        raw_scorer: &dyn RawScorer,
+        filter: Option<&Filter>,
+        top: usize,
+        exclude_id: Option<PointOffsetType>,
+    ) -> (Vec<ScoredPointOffset>, SearchProfile) {
+        let vector_storage = self.vector_storage.borrow();
+        let counting_scorer = CountingRawScorer::new(raw_scorer);
+        let payload_index = self.payload_index.borrow();
+        let (ids, cardinality) = match filter {
+            Some(filter) => (
+                payload_index.query_points(filter),
+                Some(CardinalityInfo::from(&payload_index.estimate_cardinality(filter))),
+            ),
+            None => (vector_storage.iter_ids(), None),
+        };
+        let mut ids = ids.filter(|idx| Some(*idx) != exclude_id);
+        let result = peek_top_scores_iterable(counting_scorer.score_points(&mut ids), top);
+        let profile = SearchProfile {
+            cardinality,
+            sample_check: false,
+            path: SearchPath::Plain,
+            scored_points: counting_scorer.scored_points(),
+        };
+        (result, profile)
+    }
 }
 
 
@@ -142,6 +235,125 @@ impl VectorIndex for PlainIndex {
         }
     }
 
+    fn search_explain(
+        &self,
+        vector: &[VectorElementType],
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> (Vec<ScoredPointOffset>, SearchProfile) {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer(vector.to_vec());
+        self.search_explain_with_scorer(raw_scorer.as_ref(), filter, top, None)
+    }
+
+    fn search_batch(
+        &self,
+        vectors: &[&Vec<VectorElementType>],
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> Vec<Vec<ScoredPointOffset>> {
+        let vector_storage = self.vector_storage.borrow();
+        match filter {
+            Some(filter) => {
+                let filtered_ids = self.payload_index.borrow().query_points(filter).collect_vec();
+                vectors.iter()
+                    .map(|vector| vector_storage.score_points(vector, &mut filtered_ids.iter().cloned(), top))
+                    .collect()
+            }
+            None => vectors.iter()
+                .map(|vector| vector_storage.score_all(vector, top))
+                .collect()
+        }
+    }
+
+    fn search_examples(
+        &self,
+        query: &ExampleQuery,
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_examples(query);
+        match filter {
+            Some(filter) => {
+                let payload_index = self.payload_index.borrow();
+                let mut filtered_ids = payload_index.query_points(filter);
+                peek_top_scores_iterable(raw_scorer.score_points(&mut filtered_ids), top)
+            }
+            None => {
+                let mut ids = vector_storage.iter_ids();
+                peek_top_scores_iterable(raw_scorer.score_points(&mut ids), top)
+            }
+        }
+    }
+
+    fn search_by_point(
+        &self,
+        point_id: PointOffsetType,
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> Vec<ScoredPointOffset> {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+        let payload_index = self.payload_index.borrow();
+        let ids = match filter {
+            Some(filter) => payload_index.query_points(filter),
+            None => vector_storage.iter_ids(),
+        };
+        let mut other_ids = ids.filter(|idx| *idx != point_id);
+        peek_top_scores_iterable(raw_scorer.score_points(&mut other_ids), top)
+    }
+
+    fn search_by_point_explain(
+        &self,
+        point_id: PointOffsetType,
+        filter: Option<&Filter>,
+        top: usize,
+        _params: Option<&SearchParams>,
+    ) -> (Vec<ScoredPointOffset>, SearchProfile) {
+        let vector_storage = self.vector_storage.borrow();
+        let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+        self.search_explain_with_scorer(raw_scorer.as_ref(), filter, top, Some(point_id))
+    }
+
+    fn similar_pairs(
+        &self,
+        threshold: ScoreType,
+        filter: Option<&Filter>,
+        limit: usize,
+    ) -> Vec<(PointOffsetType, PointOffsetType, ScoreType)> {
+        let vector_storage = self.vector_storage.borrow();
+        let ids = match filter {
+            Some(filter) => self.payload_index.borrow().query_points(filter).collect_vec(),
+            None => vector_storage.iter_ids().collect_vec(),
+        };
+
+        // No graph to select candidates from, so only the closest points are verified as candidates.
+        // Same pair could be found from both sides
+        let mut seen_pairs: HashSet<(PointOffsetType, PointOffsetType)> = HashSet::new();
+        let mut pairs = vec![];
+        for point_id in ids.iter().cloned() {
+            if pairs.len() >= limit {
+                break;
+            }
+            let raw_scorer = vector_storage.raw_scorer_internal(point_id);
+            let mut other_ids = ids.iter().cloned().filter(|idx| *idx != point_id);
+            let candidates = peek_top_scores_iterable(raw_scorer.score_points(&mut other_ids), SIMILAR_PAIRS_CANDIDATES);
+            for scored in candidates.into_iter().filter(|scored| scored.score >= threshold) {
+                let pair = (point_id.min(scored.idx), point_id.max(scored.idx));
+                if seen_pairs.insert(pair) {
+                    pairs.push((pair.0, pair.1, scored.score));
+                }
+            }
+        }
+        pairs.truncate(limit);
+        pairs
+    }
+
     fn build_index(&mut self) -> OperationResult<()> {
         Ok(())
     }
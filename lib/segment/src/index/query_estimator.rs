@@ -13,11 +13,28 @@ fn combine_must_estimations(estimations: &Vec<CardinalityEstimation>, total: usi
         .map(|x| x.max).min()
         .unwrap_or(total);
 
+    // Sketches are sampled from the same points, so their intersection accounts for correlated conditions.
+    // Conditions without sketch are assumed to be independent.
+    let joint_sketch = estimations.iter()
+        .filter_map(|x| x.sketch.clone())
+        .reduce(|joint, sketch| joint.intersect(&sketch));
+    let sketched_count = estimations.iter().filter(|x| x.sketch.is_some()).count();
+    let use_sketch = sketched_count > 1;
+
     let exp_estimation_prob: f64 = estimations.iter()
+        .filter(|x| !use_sketch || x.sketch.is_none())
         .map(|x| (x.exp as f64) / (total as f64))
         .product();
 
-    let exp_estimation = (exp_estimation_prob * (total as f64)) as usize;
+    let exp_estimation = match &joint_sketch {
+        Some(sketch) if use_sketch => {
+            let exp = (exp_estimation_prob * sketch.estimate() as f64) as usize;
+            max(min_estimation, min(max_estimation, exp))
+        }
+        _ => (exp_estimation_prob * (total as f64)) as usize,
+    };
+
+    let sketch = if sketched_count == estimations.len() { joint_sketch } else { None };
 
     let clauses = estimations.iter()
         .filter(|x| !x.primary_clauses.is_empty())
@@ -30,6 +47,7 @@ fn combine_must_estimations(estimations: &Vec<CardinalityEstimation>, total: usi
         min: min_estimation,
         exp: exp_estimation,
         max: max_estimation,
+        sketch,
     }
 }
 
@@ -89,12 +107,28 @@ fn estimate_should<F>(estimator: &F, conditions: &Vec<Condition>, total: usize)
         .map(|x| (total - x.exp) as f64 / (total as f64))
         .product();
     let element_hit_prob = 1.0 - element_not_hit_prob;
-    let expected_count = (element_hit_prob * (total as f64)) as usize;
+    let min_estimation = should_estimations.iter().map(|x| x.min).max().unwrap_or(0);
+    let max_estimation = min(should_estimations.iter().map(|x| x.max).sum(), total);
+
+    // If all branches are sketched, union of sketches accounts for overlapping branches
+    let sketch = if should_estimations.iter().all(|x| x.sketch.is_some()) {
+        should_estimations.iter()
+            .filter_map(|x| x.sketch.clone())
+            .reduce(|union, sketch| union.union(&sketch))
+    } else {
+        None
+    };
+
+    let expected_count = match &sketch {
+        Some(sketch) => max(min_estimation, min(max_estimation, sketch.estimate())),
+        None => (element_hit_prob * (total as f64)) as usize,
+    };
     CardinalityEstimation {
         primary_clauses: clauses,
-        min: should_estimations.iter().map(|x| x.min).max().unwrap_or(0),
+        min: min_estimation,
         exp: expected_count,
-        max: min(should_estimations.iter().map(|x| x.max).sum(), total),
+        max: max_estimation,
+        sketch,
     }
 }
 
@@ -112,6 +146,7 @@ fn invert_estimation(estimation: &CardinalityEstimation, total: usize) -> Cardin
         min: total - estimation.max,
         exp: total - estimation.exp,
         max: total - estimation.min,
+        sketch: None,
     }
 }
 
@@ -126,7 +161,8 @@ fn estimate_must_not<F>(estimator: &F, conditions: &Vec<Condition>, total: usize
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{FieldCondition, HasIdCondition, PointOffsetType};
+    use crate::types::{FieldCondition, HasIdCondition, PointIdType, PointOffsetType};
+    use crate::index::field_index::sketch::PointsSketch;
 
     const TOTAL: usize = 1000;
 
@@ -149,26 +185,33 @@ mod tests {
                     min: 100,
                     exp: 200,
                     max: 300,
+                    sketch: None,
                 },
                 "size" => CardinalityEstimation {
                     primary_clauses: vec![PrimaryCondition::Condition(field.clone())],
                     min: 100,
                     exp: 100,
                     max: 100,
+                    sketch: None,
                 },
                 "price" => CardinalityEstimation {
                     primary_clauses: vec![PrimaryCondition::Condition(field.clone())],
                     min: 10,
                     exp: 15,
                     max: 20,
+                    sketch: None,
                 },
                 _ => CardinalityEstimation::unknown(TOTAL)
             },
             Condition::HasId(has_id) => CardinalityEstimation {
-                primary_clauses: vec![PrimaryCondition::Ids(has_id.has_id.iter().map(|x| *x as PointOffsetType).collect())],
+                primary_clauses: vec![PrimaryCondition::Ids(has_id.has_id.iter().filter_map(|x| match x {
+                    PointIdType::NumId(id) => Some(*id as PointOffsetType),
+                    PointIdType::Uuid(_) => None,
+                }).collect())],
                 min: has_id.has_id.len(),
                 exp: has_id.has_id.len(),
                 max: has_id.has_id.len(),
+                sketch: None,
             }
         }
     }
@@ -265,7 +308,7 @@ mod tests {
             ]),
             must: None,
             must_not: Some(vec![
-                Condition::HasId(HasIdCondition { has_id: vec![1, 2, 3, 4, 5].iter().cloned().collect() })
+                Condition::HasId(HasIdCondition { has_id: vec![1u64, 2, 3, 4, 5].into_iter().map(PointIdType::from).collect() })
             ]),
         };
 
@@ -299,7 +342,7 @@ mod tests {
                 }),
             ]),
             must_not: Some(vec![
-                Condition::HasId(HasIdCondition { has_id: vec![1, 2, 3, 4, 5].iter().cloned().collect() })
+                Condition::HasId(HasIdCondition { has_id: vec![1u64, 2, 3, 4, 5].into_iter().map(PointIdType::from).collect() })
             ]),
         };
 
@@ -316,4 +359,52 @@ mod tests {
         assert!(estimation.exp <= estimation.max);
         assert!(estimation.min <= estimation.exp);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn correlated_must_estimation_test() {
+        // Both fields match the same 200 points
+        let sketched_estimator = |condition: &Condition| match condition {
+            Condition::Field(field) => CardinalityEstimation {
+                primary_clauses: vec![PrimaryCondition::Condition(field.clone())],
+                min: 200,
+                exp: 200,
+                max: 200,
+                sketch: Some(PointsSketch::new(2, 0..200)),
+            },
+            _ => panic!("unexpected condition"),
+        };
+
+        let query = Filter {
+            should: None,
+            must: Some(vec![
+                test_condition("color".to_owned()),
+                test_condition("brand".to_owned()),
+            ]),
+            must_not: None,
+        };
+
+        let estimation = estimate_filter(&sketched_estimator, &query, TOTAL);
+        // Independent conditions would give 200 * 200 / 1000 = 40 points
+        assert!(estimation.exp > 150, "{:#?}", estimation);
+        assert!(estimation.exp <= estimation.max);
+        assert!(estimation.sketch.is_some());
+
+        let query = Filter {
+            should: None,
+            must: Some(vec![
+                test_condition("color".to_owned()),
+                test_condition("brand".to_owned()),
+            ]),
+            must_not: Some(vec![
+                Condition::HasId(HasIdCondition { has_id: vec![1u64, 2, 3].into_iter().map(PointIdType::from).collect() })
+            ]),
+        };
+
+        let estimation = estimate_filter(&|condition: &Condition| match condition {
+            Condition::HasId(_) => test_estimator(condition),
+            _ => sketched_estimator(condition),
+        }, &query, TOTAL);
+        assert!(estimation.exp > 150, "{:#?}", estimation);
+        assert!(estimation.sketch.is_none());
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::{create_dir_all, File, remove_file};
+use std::fs::{create_dir_all, File, remove_dir_all, remove_file};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -13,8 +13,11 @@ use crate::index::field_index::index_selector::index_selector;
 use crate::index::index::PayloadIndex;
 use crate::index::payload_config::PayloadConfig;
 use crate::payload_storage::payload_storage::{ConditionChecker, PayloadStorage};
-use crate::types::{Filter, PayloadKeyType, FieldCondition, Condition, PointOffsetType};
+use crate::types::{Filter, PayloadKeyType, FieldCondition, Condition, PointOffsetType, FacetValue, FloatPayloadType, Direction};
+use crate::index::payload_stats::{scan_facet_counts, scan_numeric_values, scan_ordered_values, values_range, values_histogram};
+use std::ops::Deref;
 use crate::index::field_index::{CardinalityEstimation, PrimaryCondition, PayloadBlockCondition};
+use crate::index::field_index::sketch::PointsSketch;
 use crate::index::query_estimator::{estimate_filter};
 use crate::vector_storage::vector_storage::VectorStorage;
 use crate::id_mapper::id_mapper::IdMapper;
@@ -33,6 +36,8 @@ pub struct StructPayloadIndex {
     config: PayloadConfig,
     path: PathBuf,
     visited_pool: VisitedPool,
+    /// Store field indexes in the immutable mmap layout instead of keeping them in memory
+    use_mmap: bool,
 }
 
 impl StructPayloadIndex {
@@ -80,25 +85,76 @@ impl StructPayloadIndex {
         Self::get_field_index_dir(path).join(format!("{}.idx", field))
     }
 
-    fn save_field_index(&self, field: &PayloadKeyType) -> OperationResult<()> {
+    fn get_mmap_field_index_dir(path: &Path, field: &PayloadKeyType) -> PathBuf {
+        Self::get_field_index_dir(path).join(format!("{}.mmap", field))
+    }
+
+    fn save_field_index(&self, field: &PayloadKeyType, indexes: &[FieldIndex]) -> OperationResult<()> {
         let field_index_dir = Self::get_field_index_dir(&self.path);
-        let field_index_path = Self::get_field_index_path(&self.path, field);
         create_dir_all(field_index_dir)?;
 
-        match self.field_indexes.get(field) {
-            None => {}
-            Some(indexes) => {
-                let file = File::create(field_index_path.as_path())?;
-                serde_cbor::to_writer(file, indexes)
-                    .map_err(|err| OperationError::ServiceError { description: format!("Unable to save index: {:?}", err) })?;
+        if self.use_mmap {
+            let mmap_dir = Self::get_mmap_field_index_dir(&self.path, field);
+            if mmap_dir.exists() {
+                remove_dir_all(&mmap_dir)?;
             }
+            create_dir_all(&mmap_dir)?;
+            for (idx, index) in indexes.iter().enumerate() {
+                index.save_mmap(&mmap_dir.join(format!("{}.dat", idx)))?;
+            }
+        } else {
+            let field_index_path = Self::get_field_index_path(&self.path, field);
+            let file = File::create(field_index_path.as_path())?;
+            serde_cbor::to_writer(file, &indexes)
+                .map_err(|err| OperationError::ServiceError { description: format!("Unable to save index: {:?}", err) })?;
         }
         Ok(())
     }
 
-    fn load_or_build_field_index(&self, field: &PayloadKeyType) -> OperationResult<Vec<FieldIndex>> {
+    fn remove_field_index_files(&self, field: &PayloadKeyType) -> OperationResult<()> {
         let field_index_path = Self::get_field_index_path(&self.path, field);
         if field_index_path.exists() {
+            remove_file(&field_index_path)?;
+        }
+
+        let mmap_field_index_dir = Self::get_mmap_field_index_dir(&self.path, field);
+        if mmap_field_index_dir.exists() {
+            remove_dir_all(&mmap_field_index_dir)?;
+        }
+        Ok(())
+    }
+
+    fn load_mmap_field_index(&self, field: &PayloadKeyType) -> OperationResult<Vec<FieldIndex>> {
+        let mmap_dir = Self::get_mmap_field_index_dir(&self.path, field);
+        let mut field_indexes = vec![];
+        loop {
+            let index_path = mmap_dir.join(format!("{}.dat", field_indexes.len()));
+            if !index_path.exists() {
+                break;
+            }
+            field_indexes.push(FieldIndex::open_mmap(&index_path)?);
+        }
+        Ok(field_indexes)
+    }
+
+    /// Save built indexes and replace them with the mmap ones if required
+    fn save_built_field_index(&self, field: &PayloadKeyType, field_indexes: Vec<FieldIndex>) -> OperationResult<Vec<FieldIndex>> {
+        self.save_field_index(field, &field_indexes)?;
+        if self.use_mmap {
+            // Built indexes are dropped, so the index takes no heap memory
+            self.load_mmap_field_index(field)
+        } else {
+            Ok(field_indexes)
+        }
+    }
+
+    fn load_or_build_field_index(&self, field: &PayloadKeyType) -> OperationResult<Vec<FieldIndex>> {
+        let field_index_path = Self::get_field_index_path(&self.path, field);
+        let mmap_field_index_dir = Self::get_mmap_field_index_dir(&self.path, field);
+        if mmap_field_index_dir.exists() {
+            debug!("Loading field `{}` mmap index from {}", field, mmap_field_index_dir.to_str().unwrap());
+            self.load_mmap_field_index(field)
+        } else if field_index_path.exists() {
             debug!("Loading field `{}` index from {}", field, field_index_path.to_str().unwrap());
             let file = File::open(field_index_path)?;
             let field_indexes: Vec<FieldIndex> = serde_cbor::from_reader(file)
@@ -108,8 +164,7 @@ impl StructPayloadIndex {
         } else {
             debug!("Index for field `{}` not found in {}, building now", field, field_index_path.to_str().unwrap());
             let res = self.build_field_index(field)?;
-            self.save_field_index(field)?;
-            Ok(res)
+            self.save_built_field_index(field, res)
         }
     }
 
@@ -129,6 +184,7 @@ impl StructPayloadIndex {
                 payload: Arc<AtomicRefCell<dyn PayloadStorage>>,
                 id_mapper: Arc<AtomicRefCell<dyn IdMapper>>,
                 path: &Path,
+                use_mmap: bool,
     ) -> OperationResult<Self> {
         create_dir_all(path)?;
         let config_path = PayloadConfig::get_config_path(path);
@@ -147,6 +203,7 @@ impl StructPayloadIndex {
             config,
             path: path.to_owned(),
             visited_pool: Default::default(),
+            use_mmap,
         };
 
         if !index.config_path().exists() {
@@ -199,19 +256,50 @@ impl StructPayloadIndex {
         }
 
         let field_indexes = self.build_field_index(field)?;
+        let field_indexes = self.save_built_field_index(field, field_indexes)?;
         self.field_indexes.insert(
             field.clone(),
             field_indexes,
         );
 
-        self.save_field_index(field)?;
-
         Ok(())
     }
 
     pub fn total_points(&self) -> usize {
         self.vector_storage.borrow().vector_count()
     }
+
+    /// Run `action` with a check, which accepts only existing points satisfying `filter`
+    fn with_points_check<T>(&self, filter: Option<&Filter>, action: impl FnOnce(&dyn Fn(PointOffsetType) -> bool) -> T) -> T {
+        let vector_storage = self.vector_storage.borrow();
+        match filter {
+            None => action(&|point_id| !vector_storage.is_deleted(point_id)),
+            Some(filter) => {
+                let mut visited_list = self.visited_pool.get(vector_storage.total_vector_count());
+                for point_id in self.query_points(filter) {
+                    visited_list.check_and_update_visited(point_id);
+                }
+                let result = action(&|point_id| visited_list.check(point_id));
+                self.visited_pool.return_back(visited_list);
+                result
+            }
+        }
+    }
+
+    fn numeric_field_index(&self, key: &PayloadKeyType) -> Option<&FieldIndex> {
+        self.field_indexes
+            .get(key)
+            .and_then(|indexes| indexes.iter().find(|index| index.is_numeric()))
+    }
+
+    /// Fallback for not indexed fields: read values from payload storage
+    fn scan_numeric_values(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Vec<FloatPayloadType> {
+        let payload = self.payload.borrow();
+        match filter {
+            Some(filter) => scan_numeric_values(self.query_points(filter), payload.deref(), key),
+            None => scan_numeric_values(self.vector_storage.borrow().iter_ids(), payload.deref(), key),
+        }
+    }
 }
 
 
@@ -233,16 +321,29 @@ impl PayloadIndex for StructPayloadIndex {
         self.config.indexed_fields = self.config.indexed_fields.iter().cloned().filter(|x| x != field).collect();
         self.save_config()?;
         self.field_indexes.remove(field);
+        self.remove_field_index_files(field)
+    }
 
-        let field_index_path = Self::get_field_index_path(&self.path, field);
-
-        if field_index_path.exists() {
-            remove_file(&field_index_path)?;
+    fn fields_updated(&mut self, fields: &[PayloadKeyType]) -> OperationResult<()> {
+        // Field indexes are immutable and re-building them on each update is too expensive.
+        // Outdated indexes are dropped, so conditions are checked with payload storage,
+        // until the segment is re-built by the optimizer or indexes are re-built on the next load
+        for field in fields.iter().unique() {
+            if self.field_indexes.remove(field).is_some() {
+                self.remove_field_index_files(field)?;
+            }
         }
-
         Ok(())
     }
 
+    fn outdated_fields(&self) -> Vec<PayloadKeyType> {
+        self.config.indexed_fields
+            .iter()
+            .filter(|field| !self.field_indexes.contains_key(*field))
+            .cloned()
+            .collect()
+    }
+
     fn estimate_cardinality(&self, query: &Filter) -> CardinalityEstimation {
         let total_points = self.total_points();
 
@@ -255,11 +356,14 @@ impl PayloadIndex for StructPayloadIndex {
                         .filter_map(|external_id| id_mapper_ref.internal_id(*external_id))
                         .collect();
                     let num_ids = mapped_ids.len();
+                    // Exact sketch, which includes every selected point
+                    let sketch = PointsSketch::new(1, mapped_ids.iter().cloned());
                     CardinalityEstimation {
                         primary_clauses: vec![PrimaryCondition::Ids(mapped_ids)],
                         min: num_ids,
                         exp: num_ids,
                         max: num_ids,
+                        sketch: Some(sketch),
                     }
                 }
                 Condition::Field(field_condition) => self
@@ -284,6 +388,67 @@ impl PayloadIndex for StructPayloadIndex {
         Box::new(iter)
     }
 
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize> {
+        let indexed_counts = self.with_points_check(filter, |check| {
+            self.field_indexes
+                .get(key)
+                .and_then(|indexes| indexes.iter().filter_map(|index| index.facet_counts(check)).next())
+        });
+
+        match indexed_counts {
+            Some(counts) => counts.into_iter().collect(),
+            None => {
+                // There is no suitable index, read values from payload storage
+                let payload = self.payload.borrow();
+                match filter {
+                    Some(filter) => scan_facet_counts(self.query_points(filter), payload.deref(), key),
+                    None => scan_facet_counts(self.vector_storage.borrow().iter_ids(), payload.deref(), key),
+                }
+            }
+        }
+    }
+
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        match self.numeric_field_index(key) {
+            Some(field_index) => self.with_points_check(filter, |check| field_index.values_range(check)),
+            None => values_range(&self.scan_numeric_values(key, filter)),
+        }
+    }
+
+    fn numeric_histogram(&self, key: &PayloadKeyType, filter: Option<&Filter>, min: FloatPayloadType, max: FloatPayloadType, buckets: usize) -> Vec<usize> {
+        match self.numeric_field_index(key) {
+            Some(field_index) => self.with_points_check(filter, |check| field_index.histogram(check, min, max, buckets)),
+            None => values_histogram(&self.scan_numeric_values(key, filter), min, max, buckets),
+        }
+    }
+
+    fn walk_ordered(&self, key: &PayloadKeyType, direction: Direction, from: Option<FloatPayloadType>, filter: Option<&Filter>, visit: &mut dyn FnMut(FloatPayloadType, PointOffsetType) -> bool) {
+        if let Some(field_index) = self.numeric_field_index(key) {
+            self.with_points_check(filter, |check| field_index.walk_ordered(check, direction, from, visit));
+            return;
+        }
+
+        // There is no numeric index, read and sort values from payload storage
+        let values = {
+            let payload = self.payload.borrow();
+            match filter {
+                Some(filter) => scan_ordered_values(self.query_points(filter), payload.deref(), key, direction, from),
+                None => scan_ordered_values(self.vector_storage.borrow().iter_ids(), payload.deref(), key, direction, from),
+            }
+        };
+        for (value, point_id) in values {
+            if !visit(value, point_id) {
+                break;
+            }
+        }
+    }
+
+    fn check_field_condition(&self, point_id: PointOffsetType, condition: &FieldCondition) -> Option<bool> {
+        self.field_indexes
+            .get(&condition.key)
+            .and_then(|indexes| indexes.iter().find_map(|index| index.check(point_id, condition)))
+    }
+
     fn query_points<'a>(&'a self, query: &'a Filter) -> Box<dyn Iterator<Item=PointOffsetType> + 'a> {
         // Assume query is already estimated to be small enough so we can iterate over all matched ids
         let vector_storage_ref = self.vector_storage.borrow();
@@ -1,4 +1,4 @@
-use crate::types::{PayloadType, Match, Range, GeoBoundingBox, GeoRadius};
+use crate::types::{PayloadType, Match, Range, GeoBoundingBox, GeoRadius, GeoPoint};
 use geo::Point;
 use geo::algorithm::haversine_distance::HaversineDistance;
 
@@ -51,23 +51,20 @@ pub fn match_geo(
     };
 }
 
+/// Distance between geo points in meters
+pub fn geo_distance(a: &GeoPoint, b: &GeoPoint) -> f64 {
+    Point::new(a.lon, a.lat).haversine_distance(&Point::new(b.lon, b.lat))
+}
+
 pub fn match_geo_radius(
     payload: &PayloadType,
     geo_radius_query: &GeoRadius,
 ) -> bool {
     return match payload {
         PayloadType::Geo(geo_points) => {
-            let query_center = Point::new(
-                geo_radius_query.center.lon,
-                geo_radius_query.center.lat);
-
             geo_points
                 .iter()
-                .any(|geo_point|
-                    query_center.haversine_distance(
-                        &Point::new(geo_point.lon, geo_point.lat)
-                    ) < geo_radius_query.radius
-                )
+                .any(|geo_point| geo_distance(&geo_radius_query.center, geo_point) < geo_radius_query.radius)
         }
         _ => false,
     };
@@ -1,5 +1,6 @@
 pub mod query_checker;
 pub mod simple_payload_storage;
+pub mod on_disk_payload_storage;
 pub mod payload_storage;
 pub mod condition_checker;
 
@@ -0,0 +1,218 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::Path;
+
+use log::error;
+use lru::LruCache;
+use parking_lot::Mutex;
+use rocksdb::{DB, IteratorMode, Options};
+
+use crate::entry::entry_point::OperationResult;
+use crate::payload_storage::payload_storage::PayloadStorage;
+use crate::payload_storage::simple_payload_storage::{DB_CACHE_SIZE, DB_NAME, SimplePayloadStorage};
+use crate::types::{PayloadKeyType, PayloadSchemaType, PayloadType, PointOffsetType, TheMap};
+
+/// Number of recently used payloads, kept in memory
+const PAYLOAD_CACHE_SIZE: usize = 10_000;
+
+/// Payload storage, which reads payloads from RocksDB on demand.
+/// Only ids of points with payload, schema and a bounded number of recently used payloads are kept in memory.
+/// Uses the same data layout as `SimplePayloadStorage`.
+pub struct OnDiskPayloadStorage {
+    point_ids: HashSet<PointOffsetType>,
+    schema: TheMap<PayloadKeyType, PayloadSchemaType>,
+    store: DB,
+    cache: Mutex<LruCache<PointOffsetType, TheMap<PayloadKeyType, PayloadType>>>,
+}
+
+impl OnDiskPayloadStorage {
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let mut options: Options = Options::default();
+        options.set_write_buffer_size(DB_CACHE_SIZE);
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let store = DB::open_cf(&options, path, vec![DB_NAME])?;
+
+        let mut point_ids: HashSet<PointOffsetType> = Default::default();
+        let mut schema: TheMap<PayloadKeyType, PayloadSchemaType> = Default::default();
+
+        // Payloads are only read once to restore the schema
+        let cf_handle = store.cf_handle(DB_NAME).unwrap();
+        for (key, val) in store.iterator_cf(cf_handle, IteratorMode::Start) {
+            let point_id: PointOffsetType = serde_cbor::from_slice(&key).unwrap();
+            let payload: TheMap<PayloadKeyType, PayloadType> = serde_cbor::from_slice(&val).unwrap();
+            SimplePayloadStorage::update_schema(&mut schema, &payload)?;
+            point_ids.insert(point_id);
+        }
+
+        Ok(OnDiskPayloadStorage {
+            point_ids,
+            schema,
+            store,
+            cache: Mutex::new(LruCache::new(PAYLOAD_CACHE_SIZE)),
+        })
+    }
+
+    fn read_payload(&self, point_id: PointOffsetType) -> OperationResult<Option<TheMap<PayloadKeyType, PayloadType>>> {
+        if !self.point_ids.contains(&point_id) {
+            return Ok(None);
+        }
+
+        if let Some(payload) = self.cache.lock().get(&point_id) {
+            return Ok(Some(payload.clone()));
+        }
+
+        // Cache is not locked during the disk read, so concurrent readers are not blocked by it
+        let cf_handle = self.store.cf_handle(DB_NAME).unwrap();
+        let payload: Option<TheMap<PayloadKeyType, PayloadType>> = self.store
+            .get_cf(cf_handle, serde_cbor::to_vec(&point_id).unwrap())?
+            .map(|val| serde_cbor::from_slice(&val).unwrap());
+
+        if let Some(payload) = &payload {
+            self.cache.lock().put(point_id, payload.clone());
+        }
+        Ok(payload)
+    }
+
+    /// Infallible reading for the storage interface: payload, which could not be read, is logged and treated as missing
+    fn read_payload_or_log(&self, point_id: PointOffsetType) -> Option<TheMap<PayloadKeyType, PayloadType>> {
+        match self.read_payload(point_id) {
+            Ok(payload) => payload,
+            Err(err) => {
+                error!("Failed to read payload of point {}: {}", point_id, err);
+                None
+            }
+        }
+    }
+
+    fn write_payload(&mut self, point_id: PointOffsetType, payload: Option<TheMap<PayloadKeyType, PayloadType>>) -> OperationResult<()> {
+        let cf_handle = self.store.cf_handle(DB_NAME).unwrap();
+        let cache = self.cache.get_mut();
+        match payload {
+            None => {
+                self.store.delete_cf(cf_handle, serde_cbor::to_vec(&point_id).unwrap())?;
+                self.point_ids.remove(&point_id);
+                cache.pop(&point_id);
+            }
+            Some(payload) => {
+                self.store.put_cf(
+                    cf_handle,
+                    serde_cbor::to_vec(&point_id).unwrap(),
+                    serde_cbor::to_vec(&payload).unwrap(),
+                )?;
+                self.point_ids.insert(point_id);
+                cache.put(point_id, payload);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PayloadStorage for OnDiskPayloadStorage {
+    fn assign(&mut self, point_id: PointOffsetType, key: &PayloadKeyType, payload: PayloadType) -> OperationResult<()> {
+        SimplePayloadStorage::update_schema_value(&mut self.schema, key, &payload)?;
+        let mut point_payload = self.read_payload(point_id)?.unwrap_or_default();
+        point_payload.insert(key.to_owned(), payload);
+        self.write_payload(point_id, Some(point_payload))
+    }
+
+    fn payload(&self, point_id: PointOffsetType) -> TheMap<PayloadKeyType, PayloadType> {
+        self.read_payload_or_log(point_id).unwrap_or_default()
+    }
+
+    fn payload_ref(&self, point_id: PointOffsetType) -> Option<Cow<'_, TheMap<PayloadKeyType, PayloadType>>> {
+        self.read_payload_or_log(point_id).map(Cow::Owned)
+    }
+
+    fn delete(&mut self, point_id: PointOffsetType, key: &PayloadKeyType) -> OperationResult<Option<PayloadType>> {
+        match self.read_payload(point_id)? {
+            None => Ok(None),
+            Some(mut point_payload) => {
+                let res = point_payload.remove(key);
+                self.write_payload(point_id, Some(point_payload))?;
+                Ok(res)
+            }
+        }
+    }
+
+    fn drop(&mut self, point_id: PointOffsetType) -> OperationResult<Option<TheMap<PayloadKeyType, PayloadType>>> {
+        let res = self.read_payload(point_id)?;
+        self.write_payload(point_id, None)?;
+        Ok(res)
+    }
+
+    fn wipe(&mut self) -> OperationResult<()> {
+        self.point_ids = HashSet::new();
+        self.cache.get_mut().clear();
+        self.store.drop_cf(DB_NAME)?;
+        let mut options: Options = Options::default();
+        options.set_write_buffer_size(DB_CACHE_SIZE);
+        options.create_if_missing(true);
+        self.store.create_cf(DB_NAME, &options)?;
+        self.schema = TheMap::new();
+        Ok(())
+    }
+
+    fn flush(&self) -> OperationResult<()> {
+        let cf_handle = self.store.cf_handle(DB_NAME).unwrap();
+        Ok(self.store.flush_cf(cf_handle)?)
+    }
+
+    fn schema(&self) -> TheMap<PayloadKeyType, PayloadSchemaType> {
+        self.schema.clone()
+    }
+
+    fn iter_ids(&self) -> Box<dyn Iterator<Item=PointOffsetType> + '_> {
+        Box::new(self.point_ids.iter().cloned())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_on_disk_payload() {
+        let dir = TempDir::new("storage_dir").unwrap();
+        let key = "key".to_owned();
+        {
+            let mut storage = OnDiskPayloadStorage::open(dir.path()).unwrap();
+            storage.assign(100, &key, PayloadType::Integer(vec![1, 2, 3])).unwrap();
+            storage.assign(200, &key, PayloadType::Integer(vec![4])).unwrap();
+            storage.assign(200, &"other".to_owned(), PayloadType::Keyword(vec!["a".to_owned()])).unwrap();
+            storage.delete(100, &key).unwrap();
+            storage.drop(300).unwrap();
+            storage.flush().unwrap();
+        }
+
+        // Payloads are read from disk after re-opening
+        let storage = OnDiskPayloadStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.payload(100).len(), 0);
+        assert_eq!(storage.payload(200).len(), 2);
+        match &storage.payload(200)[&key] {
+            PayloadType::Integer(x) => assert_eq!(x, &vec![4]),
+            _ => assert!(false)
+        }
+        assert!(storage.payload_ref(300).is_none());
+        assert_eq!(storage.schema().len(), 2);
+
+        let mut ids = storage.iter_ids().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![100, 200]);
+    }
+
+    #[test]
+    fn test_on_disk_wipe() {
+        let dir = TempDir::new("storage_dir").unwrap();
+        let mut storage = OnDiskPayloadStorage::open(dir.path()).unwrap();
+        let key = "key".to_owned();
+        storage.assign(100, &key, PayloadType::Integer(vec![1, 2, 3])).unwrap();
+        storage.wipe().unwrap();
+        assert_eq!(storage.payload(100).len(), 0);
+        storage.assign(100, &key, PayloadType::Integer(vec![1, 2, 3])).unwrap();
+        assert!(storage.payload(100).len() > 0);
+    }
+}
@@ -1,7 +1,8 @@
 
 use crate::types::{PointOffsetType, PayloadKeyType, PayloadType, Filter, TheMap, PayloadSchemaType, PayloadInterface};
-use crate::entry::entry_point::OperationResult;
+use crate::entry::entry_point::{OperationResult, OperationError};
 use serde_json::value::Value;
+use std::borrow::Cow;
 
 /// Trait for payload data storage. Should allow filter checks
 pub trait PayloadStorage {
@@ -38,6 +39,138 @@ pub trait PayloadStorage {
         Ok(())
     }
 
+    /// Apply JSON merge patch (RFC 7396) to the payload of the point, then append values of `append` to the point values.
+    /// Merge patch replaces arrays as a whole, so `append` is the way to add values to the existing ones.
+    /// Nested objects address flattened `parent__child` keys, `null` removes the key with all its nested keys.
+    /// All values are checked against the schema before the payload is changed.
+    fn merge_with_value(
+        &mut self,
+        point_id: PointOffsetType,
+        patch: &TheMap<PayloadKeyType, Value>,
+        append: &TheMap<PayloadKeyType, Value>,
+    ) -> OperationResult<()> {
+
+        #[derive(Default)]
+        struct FlatPatch {
+            removed_keys: Vec<PayloadKeyType>,
+            removed_prefixes: Vec<PayloadKeyType>,
+            assigned: Vec<(PayloadKeyType, PayloadType)>,
+        }
+
+        fn _flatten_patch<'a, I>(patch: I, prefix_key: Option<&PayloadKeyType>, flat_patch: &mut FlatPatch) -> OperationResult<()>
+            where I: Iterator<Item=(&'a PayloadKeyType, &'a Value)> {
+            for (k, v) in patch {
+                let key = match prefix_key {
+                    None => k.to_string(),
+                    Some(_k) => _k.to_owned() + "__" + k,
+                };
+                let nested_prefix = key.to_owned() + "__";
+
+                if v.is_null() {
+                    flat_patch.removed_keys.push(key);
+                    flat_patch.removed_prefixes.push(nested_prefix);
+                    continue;
+                }
+
+                let opt_payload_interface: Result<PayloadInterface, _> = serde_json::from_value(v.to_owned());
+                match opt_payload_interface {
+                    Ok(payload_interface) => {
+                        // Plain value replaces nested object, if any
+                        flat_patch.removed_prefixes.push(nested_prefix);
+                        flat_patch.assigned.push((key, PayloadType::from(&payload_interface)));
+                    }
+                    Err(_) => match v {
+                        Value::Object(ref x) => {
+                            // Nested object replaces plain value, if any
+                            flat_patch.removed_keys.push(key.to_owned());
+                            _flatten_patch(x.iter(), Some(&key), flat_patch)?;
+                        }
+                        _ => return Err(OperationError::TypeError {
+                            field_name: key,
+                            expected_type: "payload value or object".to_string(),
+                        }),
+                    },
+                }
+            }
+            Ok(())
+        }
+
+        fn _flatten_append<'a, I>(append: I, prefix_key: Option<&PayloadKeyType>, appended: &mut Vec<(PayloadKeyType, PayloadType)>) -> OperationResult<()>
+            where I: Iterator<Item=(&'a PayloadKeyType, &'a Value)> {
+            for (k, v) in append {
+                let key = match prefix_key {
+                    None => k.to_string(),
+                    Some(_k) => _k.to_owned() + "__" + k,
+                };
+
+                let opt_payload_interface: Result<PayloadInterface, _> = serde_json::from_value(v.to_owned());
+                match opt_payload_interface {
+                    Ok(payload_interface) => appended.push((key, PayloadType::from(&payload_interface))),
+                    Err(_) => match v {
+                        Value::Object(ref x) => _flatten_append(x.iter(), Some(&key), appended)?,
+                        _ => return Err(OperationError::TypeError {
+                            field_name: key,
+                            expected_type: "payload value or object".to_string(),
+                        }),
+                    },
+                }
+            }
+            Ok(())
+        }
+
+        fn _append_values(key: &PayloadKeyType, existing: PayloadType, appended: PayloadType) -> OperationResult<PayloadType> {
+            match (existing, appended) {
+                (PayloadType::Keyword(mut x), PayloadType::Keyword(y)) => { x.extend(y); Ok(PayloadType::Keyword(x)) }
+                (PayloadType::Integer(mut x), PayloadType::Integer(y)) => { x.extend(y); Ok(PayloadType::Integer(x)) }
+                (PayloadType::Float(mut x), PayloadType::Float(y)) => { x.extend(y); Ok(PayloadType::Float(x)) }
+                (PayloadType::Geo(mut x), PayloadType::Geo(y)) => { x.extend(y); Ok(PayloadType::Geo(x)) }
+                (existing, _) => Err(OperationError::TypeError {
+                    field_name: key.to_owned(),
+                    expected_type: format!("{:?}", PayloadSchemaType::from(&existing)),
+                }),
+            }
+        }
+
+        let mut flat_patch = FlatPatch::default();
+        _flatten_patch(patch.iter(), None, &mut flat_patch)?;
+
+        let mut appended = vec![];
+        _flatten_append(append.iter(), None, &mut appended)?;
+
+        let schema = self.schema();
+        for (key, value) in flat_patch.assigned.iter().chain(appended.iter()) {
+            match schema.get(key) {
+                Some(schema_type) if schema_type != &PayloadSchemaType::from(value) => {
+                    return Err(OperationError::TypeError {
+                        field_name: key.to_owned(),
+                        expected_type: format!("{:?}", schema_type),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let removed_keys = self.payload(point_id)
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| flat_patch.removed_keys.contains(key)
+                || flat_patch.removed_prefixes.iter().any(|prefix| key.starts_with(prefix)));
+        for key in removed_keys {
+            self.delete(point_id, &key)?;
+        }
+        for (key, value) in flat_patch.assigned {
+            self.assign(point_id, &key, value)?;
+        }
+        for (key, value) in appended {
+            let value = match self.payload(point_id).remove(&key) {
+                None => value,
+                Some(existing) => _append_values(&key, existing, value)?,
+            };
+            self.assign(point_id, &key, value)?;
+        }
+        Ok(())
+    }
+
     /// Assign same payload to each given point
     fn assign_all(&mut self, point_id: PointOffsetType, payload: TheMap<PayloadKeyType, PayloadType>) -> OperationResult<()> {
         self.drop(point_id)?;
@@ -54,6 +187,9 @@ pub trait PayloadStorage {
     /// Get payload for point
     fn payload(&self, point_id: PointOffsetType) -> TheMap<PayloadKeyType, PayloadType>;
 
+    /// Get payload for point without copying, if storage keeps it in memory
+    fn payload_ref(&self, point_id: PointOffsetType) -> Option<Cow<'_, TheMap<PayloadKeyType, PayloadType>>>;
+
     /// Delete payload by key
     fn delete(&mut self, point_id: PointOffsetType, key: &PayloadKeyType) -> OperationResult<Option<PayloadType>>;
 
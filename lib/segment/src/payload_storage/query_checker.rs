@@ -1,9 +1,11 @@
-use crate::payload_storage::payload_storage::{ConditionChecker};
-use crate::types::{Filter, PayloadKeyType, PayloadType, Condition, TheMap, PointOffsetType};
-use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
-use std::sync::Arc;
+use crate::payload_storage::payload_storage::{ConditionChecker, PayloadStorage};
+use crate::types::{Filter, PayloadKeyType, PayloadType, Condition, TheMap, PointOffsetType, FieldCondition};
+use std::sync::{Arc, Weak};
+use std::borrow::Cow;
+use std::cell::RefCell;
 use atomic_refcell::AtomicRefCell;
 use crate::id_mapper::id_mapper::IdMapper;
+use crate::index::index::PayloadIndex;
 use crate::payload_storage::condition_checker::{match_payload, match_range, match_geo_radius, match_geo};
 
 
@@ -52,49 +54,67 @@ fn check_must_not<F>(checker: &F, must: &Option<Vec<Condition>>) -> bool
 
 
 pub struct SimpleConditionChecker {
-    payload_storage: Arc<AtomicRefCell<SimplePayloadStorage>>,
+    payload_storage: Arc<AtomicRefCell<dyn PayloadStorage>>,
     id_mapper: Arc<AtomicRefCell<dyn IdMapper>>,
+    /// Field indexes are preferred over reading the payload, if set.
+    /// Weak reference is used, because payload index holds the checker itself
+    payload_index: Option<Weak<AtomicRefCell<dyn PayloadIndex>>>,
 }
 
 impl SimpleConditionChecker {
-    pub fn new(payload_storage: Arc<AtomicRefCell<SimplePayloadStorage>>,
+    pub fn new(payload_storage: Arc<AtomicRefCell<dyn PayloadStorage>>,
                id_mapper: Arc<AtomicRefCell<dyn IdMapper>>) -> Self {
         SimpleConditionChecker {
             payload_storage,
             id_mapper,
+            payload_index: None,
         }
     }
+
+    /// Check field conditions with indexes of `payload_index` where possible.
+    /// Indexes should be consistent with the payload storage
+    pub fn use_payload_index(&mut self, payload_index: &Arc<AtomicRefCell<dyn PayloadIndex>>) {
+        self.payload_index = Some(Arc::downgrade(payload_index));
+    }
 }
 
-// Uncomment when stabilized
-// const EMPTY_PAYLOAD: TheMap<PayloadKeyType, PayloadType> = TheMap::new();
+fn check_field_payload(payload: &TheMap<PayloadKeyType, PayloadType>, field_condition: &FieldCondition) -> bool {
+    payload.get(&field_condition.key).map(|p| {
+        let mut res = false;
+        // ToDo: Convert onto iterator over checkers, so it would be impossible to forget a condition
+        res = res || field_condition.r#match.as_ref().map(|condition| match_payload(p, condition)).unwrap_or(false);
+        res = res || field_condition.range.as_ref().map(|condition| match_range(p, condition)).unwrap_or(false);
+        res = res || field_condition.geo_radius.as_ref().map(|condition| match_geo_radius(p, condition)).unwrap_or(false);
+        res = res || field_condition.geo_bounding_box.as_ref().map(|condition| match_geo(p, condition)).unwrap_or(false);
+        res
+    }).unwrap_or(false)
+}
 
 impl ConditionChecker for SimpleConditionChecker
 {
     fn check(&self, point_id: PointOffsetType, query: &Filter) -> bool {
-        let empty_map: TheMap<PayloadKeyType, PayloadType> = TheMap::new();
-
         let payload_storage_guard = self.payload_storage.borrow();
-        let payload_ptr = payload_storage_guard.payload_ptr(point_id);
-
-        let payload = match payload_ptr {
-            None => &empty_map,
-            Some(x) => x
+        let payload_index_ptr = self.payload_index.as_ref().and_then(|payload_index| payload_index.upgrade());
+        let payload_index = payload_index_ptr.as_ref().map(|payload_index| payload_index.borrow());
+
+        // Payload is read only once and only if some condition can't be checked with field indexes
+        let payload: RefCell<Option<Cow<TheMap<PayloadKeyType, PayloadType>>>> = RefCell::new(None);
+
+        let check_payload = |field_condition: &FieldCondition| {
+            let mut payload = payload.borrow_mut();
+            let point_payload = payload.get_or_insert_with(|| payload_storage_guard
+                .payload_ref(point_id)
+                .unwrap_or_else(|| Cow::Owned(TheMap::new()))
+            );
+            check_field_payload(point_payload, field_condition)
         };
 
         let checker = |condition: &Condition| {
             match condition {
-                Condition::Field(field_condition) => {
-                    payload.get(&field_condition.key).map(|p| {
-                        let mut res = false;
-                        // ToDo: Convert onto iterator over checkers, so it would be impossible to forget a condition
-                        res = res || field_condition.r#match.as_ref().map(|condition| match_payload(p, condition)).unwrap_or(false);
-                        res = res || field_condition.range.as_ref().map(|condition| match_range(p, condition)).unwrap_or(false);
-                        res = res || field_condition.geo_radius.as_ref().map(|condition| match_geo_radius(p, condition)).unwrap_or(false);
-                        res = res || field_condition.geo_bounding_box.as_ref().map(|condition| match_geo(p, condition)).unwrap_or(false);
-                        res
-                    }).unwrap_or(false)
-                }
+                Condition::Field(field_condition) => payload_index
+                    .as_ref()
+                    .and_then(|payload_index| payload_index.check_field_condition(point_id, field_condition))
+                    .unwrap_or_else(|| check_payload(field_condition)),
                 Condition::HasId(has_id) => {
                     let external_id = match self.id_mapper.borrow().external_id(point_id) {
                         None => return false,
@@ -113,11 +133,12 @@ impl ConditionChecker for SimpleConditionChecker
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{PayloadType, FieldCondition, Match, GeoBoundingBox, Range};
+    use crate::types::{PayloadType, FieldCondition, Match, GeoBoundingBox, Range, PointIdType};
     use crate::types::GeoPoint;
     use std::collections::HashSet;
     use tempdir::TempDir;
     use crate::payload_storage::payload_storage::PayloadStorage;
+    use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
     use crate::id_mapper::simple_id_mapper::SimpleIdMapper;
 
     #[test]
@@ -137,10 +158,10 @@ mod tests {
         let mut payload_storage = SimplePayloadStorage::open(dir.path()).unwrap();
         let mut id_mapper = SimpleIdMapper::open(dir_id_mapper.path()).unwrap();
 
-        id_mapper.set_link(0, 0).unwrap();
-        id_mapper.set_link(1, 1).unwrap();
-        id_mapper.set_link(2, 2).unwrap();
-        id_mapper.set_link(10, 10).unwrap();
+        id_mapper.set_link(0.into(), 0).unwrap();
+        id_mapper.set_link(1.into(), 1).unwrap();
+        id_mapper.set_link(2.into(), 2).unwrap();
+        id_mapper.set_link(10.into(), 10).unwrap();
         payload_storage.assign_all(0, payload).unwrap();
 
         let payload_checker = SimpleConditionChecker::new(
@@ -303,7 +324,7 @@ mod tests {
         assert!(!payload_checker.check(0, &query));
 
 
-        let ids: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+        let ids: HashSet<PointIdType> = vec![1, 2, 3].into_iter().map(|x: u64| x.into()).collect();
 
 
         let query = Filter {
@@ -313,7 +334,7 @@ mod tests {
         };
         assert!(!payload_checker.check(2, &query));
 
-        let ids: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+        let ids: HashSet<PointIdType> = vec![1, 2, 3].into_iter().map(|x: u64| x.into()).collect();
 
 
         let query = Filter {
@@ -323,7 +344,7 @@ mod tests {
         };
         assert!(payload_checker.check(10, &query));
 
-        let ids: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+        let ids: HashSet<PointIdType> = vec![1, 2, 3].into_iter().map(|x: u64| x.into()).collect();
 
         let query = Filter {
             should: None,
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::Path;
+use std::borrow::Cow;
 use crate::types::{PayloadKeyType, PayloadType, PointOffsetType, TheMap, PayloadSchemaType};
 
 use rocksdb::{DB, IteratorMode, Options};
@@ -8,9 +9,9 @@ use crate::entry::entry_point::{OperationResult, OperationError};
 use crate::payload_storage::payload_storage::PayloadStorage;
 
 /// Since sled is used for reading only during the initialization, large read cache is not required
-const DB_CACHE_SIZE: usize = 10 * 1024 * 1024;
+pub const DB_CACHE_SIZE: usize = 10 * 1024 * 1024;
 // 10 mb
-const DB_NAME: &'static str = "payload";
+pub const DB_NAME: &str = "payload";
 
 
 pub struct SimplePayloadStorage {
@@ -46,7 +47,7 @@ impl SimplePayloadStorage {
         })
     }
 
-    fn update_schema_value(
+    pub fn update_schema_value(
         schema: &mut TheMap<PayloadKeyType, PayloadSchemaType>,
         key: &PayloadKeyType,
         value: &PayloadType
@@ -64,7 +65,7 @@ impl SimplePayloadStorage {
         }
     }
 
-    fn update_schema(
+    pub fn update_schema(
         schema: &mut TheMap<PayloadKeyType, PayloadSchemaType>,
         payload: &TheMap<PayloadKeyType, PayloadType>) -> OperationResult<()> {
         for (key, value) in payload.iter() {
@@ -115,6 +116,10 @@ impl PayloadStorage for SimplePayloadStorage {
         }
     }
 
+    fn payload_ref(&self, point_id: PointOffsetType) -> Option<Cow<'_, TheMap<PayloadKeyType, PayloadType>>> {
+        self.payload.get(&point_id).map(Cow::Borrowed)
+    }
+
     fn delete(&mut self, point_id: PointOffsetType, key: &PayloadKeyType) -> OperationResult<Option<PayloadType>> {
         let point_payload = self.payload.get_mut(&point_id).unwrap();
         let res = point_payload.remove(key);
@@ -339,4 +344,75 @@ mod tests {
         let mut storage = SimplePayloadStorage::open(dir.path()).unwrap();
         storage.assign_all_with_value(100, v).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_with_value() {
+        let data = r#"
+        {
+            "name": "John Doe",
+            "tags": ["a", "b"],
+            "metadata": {
+                "height": 50,
+                "nested": {
+                    "feature": 30.5
+                }
+            }
+        }"#;
+
+        let dir = TempDir::new("storage_dir").unwrap();
+        let mut storage = SimplePayloadStorage::open(dir.path()).unwrap();
+        storage.assign_all_with_value(100, serde_json::from_str(data).unwrap()).unwrap();
+
+        let patch = r#"
+        {
+            "tags": ["a", "b", "c"],
+            "age": 43,
+            "metadata": {
+                "height": 55,
+                "nested": null
+            }
+        }"#;
+        storage.merge_with_value(100, &serde_json::from_str(patch).unwrap(), &TheMap::new()).unwrap();
+
+        let pload = storage.payload(100);
+        let keys: Vec<_> = pload.keys().cloned().collect();
+        assert_eq!(keys, vec!["age", "metadata__height", "name", "tags"]);
+        match &pload["tags"] {
+            PayloadType::Keyword(x) => assert_eq!(x, &vec!["a", "b", "c"]),
+            _ => assert!(false)
+        }
+        match &pload["metadata__height"] {
+            PayloadType::Integer(x) => assert_eq!(x, &vec![55]),
+            _ => assert!(false)
+        }
+
+        // Plain value replaces nested object
+        let patch = r#"{"metadata": "none"}"#;
+        storage.merge_with_value(100, &serde_json::from_str(patch).unwrap(), &TheMap::new()).unwrap();
+        let keys: Vec<_> = storage.payload(100).keys().cloned().collect();
+        assert_eq!(keys, vec!["age", "metadata", "name", "tags"]);
+
+        // Type mismatch rejects the whole patch
+        let patch = r#"{"name": null, "age": "old"}"#;
+        let res = storage.merge_with_value(100, &serde_json::from_str(patch).unwrap(), &TheMap::new());
+        assert!(res.is_err());
+        assert_eq!(storage.payload(100).len(), 4);
+
+        // Appended values are added to the existing ones instead of replacing them
+        let append = r#"{"tags": ["d"], "metadata": {"width": 10}}"#;
+        storage.merge_with_value(100, &TheMap::new(), &serde_json::from_str(append).unwrap()).unwrap();
+        let pload = storage.payload(100);
+        match &pload["tags"] {
+            PayloadType::Keyword(x) => assert_eq!(x, &vec!["a", "b", "c", "d"]),
+            _ => assert!(false)
+        }
+        match &pload["metadata__width"] {
+            PayloadType::Integer(x) => assert_eq!(x, &vec![10]),
+            _ => assert!(false)
+        }
+
+        let append = r#"{"age": "old"}"#;
+        let res = storage.merge_with_value(100, &TheMap::new(), &serde_json::from_str(append).unwrap());
+        assert!(res.is_err());
+    }
+}
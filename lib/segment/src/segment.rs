@@ -1,16 +1,22 @@
 use crate::id_mapper::id_mapper::IdMapper;
-use crate::vector_storage::vector_storage::VectorStorage;
+use crate::vector_storage::vector_storage::{VectorStorage, ScoredPointOffset};
 use crate::payload_storage::payload_storage::{PayloadStorage, ConditionChecker};
 use crate::entry::entry_point::{SegmentEntry, OperationResult, OperationError};
-use crate::types::{Filter, PayloadKeyType, PayloadType, SeqNumberType, VectorElementType, PointIdType, PointOffsetType, SearchParams, ScoredPoint, TheMap, SegmentInfo, SegmentType, SegmentConfig, SegmentState, PayloadSchemaInfo};
+use crate::types::{Filter, PayloadKeyType, PayloadType, SeqNumberType, VectorElementType, PointIdType, PointOffsetType, SearchParams, ScoredPoint, TheMap, SegmentInfo, SegmentType, SegmentConfig, SegmentState, PayloadSchemaInfo, ExampleQuery, FacetValue, FloatPayloadType, ScoreType, SimilarPair, Direction, SegmentSearchProfile};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use std::collections::{HashMap, HashSet};
 use atomic_refcell::{AtomicRefCell};
 use std::path::PathBuf;
 use std::fs::{remove_dir_all};
 use std::io::Write;
+use std::slice;
 use atomicwrites::{AtomicFile, AllowOverwrite};
 use crate::index::index::{PayloadIndex, VectorIndex};
+use crate::index::field_index::CardinalityEstimation;
 use crate::spaces::tools::mertic_object;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
 
 
 pub const SEGMENT_STATE_FILE: &str = "segment.json";
@@ -45,9 +51,13 @@ impl Segment {
         if new_internal_index != old_internal_id {
             let payload = self.payload_storage.borrow_mut().drop(old_internal_id)?;
             match payload {
-                Some(payload) => self.payload_storage
-                    .borrow_mut()
-                    .assign_all(new_internal_index, payload)?,
+                Some(payload) => {
+                    let moved_fields = payload.keys().cloned().collect_vec();
+                    self.payload_storage
+                        .borrow_mut()
+                        .assign_all(new_internal_index, payload)?;
+                    self.payload_index.borrow_mut().fields_updated(&moved_fields)?;
+                }
                 None => ()
             }
         }
@@ -55,6 +65,42 @@ impl Segment {
         Ok(new_internal_index)
     }
 
+    /// Indexed fields, which are present in the payload of the point.
+    /// Indexes of these fields should be updated if the payload is replaced
+    fn indexed_payload_fields(&self, internal_id: PointOffsetType) -> Vec<PayloadKeyType> {
+        let indexed_fields = self.payload_index.borrow().indexed_fields();
+        if indexed_fields.is_empty() {
+            return vec![];
+        }
+        let payload = self.payload_storage.borrow().payload(internal_id);
+        indexed_fields.into_iter().filter(|field| payload.contains_key(field)).collect()
+    }
+
+    fn check_vector_dim(&self, vector: &[VectorElementType]) -> OperationResult<()> {
+        let expected_vector_dim = self.vector_storage.borrow().vector_dim();
+        if expected_vector_dim != vector.len() {
+            return Err(OperationError::WrongVector {
+                expected_dim: expected_vector_dim,
+                received_dim: vector.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Convert internal ids of search result into external ones
+    fn process_search_result(&self, internal_result: &[ScoredPointOffset]) -> Vec<ScoredPoint> {
+        let id_mapper = self.id_mapper.borrow();
+        internal_result.iter()
+            .map(|&scored_point_offset|
+                ScoredPoint {
+                    id: id_mapper
+                        .external_id(scored_point_offset.idx)
+                        .unwrap_or_else(|| panic!("Corrupter id_mapper, no external value for {}", scored_point_offset.idx)),
+                    score: scored_point_offset.score,
+                }
+            ).collect()
+    }
+
     fn skip_by_version(&mut self, op_num: SeqNumberType) -> bool {
         return if self.version > op_num {
             true
@@ -104,30 +150,109 @@ impl SegmentEntry for Segment {
               top: usize,
               params: Option<&SearchParams>,
     ) -> OperationResult<Vec<ScoredPoint>> {
-        let expected_vector_dim = self.vector_storage.borrow().vector_dim();
-        if expected_vector_dim != vector.len() {
-            return Err(OperationError::WrongVector {
-                expected_dim: expected_vector_dim,
-                received_dim: vector.len(),
-            });
-        }
+        self.check_vector_dim(vector)?;
 
         let internal_result = self.vector_index.borrow().search(vector, filter, top, params);
 
+        Ok(self.process_search_result(&internal_result))
+    }
+
+    fn search_explain(&self,
+                      vector: &[VectorElementType],
+                      filter: Option<&Filter>,
+                      top: usize,
+                      params: Option<&SearchParams>,
+    ) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)> {
+        self.check_vector_dim(vector)?;
 
+        let start = Instant::now();
+        let (internal_result, search_profile) = self.vector_index.borrow().search_explain(vector, filter, top, params);
+        let result = self.process_search_result(&internal_result);
+
+        let profile = SegmentSearchProfile {
+            segment_type: self.segment_type,
+            search: search_profile,
+            elapsed: start.elapsed().as_secs_f64(),
+        };
+        Ok((result, vec![profile]))
+    }
+
+    fn search_batch(&self,
+                    vectors: &[&Vec<VectorElementType>],
+                    filter: Option<&Filter>,
+                    top: usize,
+                    params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<Vec<ScoredPoint>>> {
+        for vector in vectors {
+            self.check_vector_dim(vector)?;
+        }
+
+        let internal_results = self.vector_index.borrow().search_batch(vectors, filter, top, params);
+
+        Ok(internal_results.iter()
+            .map(|internal_result| self.process_search_result(internal_result))
+            .collect())
+    }
+
+    fn search_examples(&self,
+                       query: &ExampleQuery,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        for vector in query.vectors() {
+            self.check_vector_dim(vector)?;
+        }
+
+        let internal_result = self.vector_index.borrow().search_examples(query, filter, top, params);
+
+        Ok(self.process_search_result(&internal_result))
+    }
+
+    fn search_by_point(&self,
+                       point_id: PointIdType,
+                       filter: Option<&Filter>,
+                       top: usize,
+                       params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        let internal_id = self.lookup_internal_id(point_id)?;
+
+        let internal_result = self.vector_index.borrow().search_by_point(internal_id, filter, top, params);
+
+        Ok(self.process_search_result(&internal_result))
+    }
+
+    fn search_by_point_explain(&self,
+                               point_id: PointIdType,
+                               filter: Option<&Filter>,
+                               top: usize,
+                               params: Option<&SearchParams>,
+    ) -> OperationResult<(Vec<ScoredPoint>, Vec<SegmentSearchProfile>)> {
+        let internal_id = self.lookup_internal_id(point_id)?;
+
+        let start = Instant::now();
+        let (internal_result, search_profile) = self.vector_index.borrow().search_by_point_explain(internal_id, filter, top, params);
+        let result = self.process_search_result(&internal_result);
+
+        let profile = SegmentSearchProfile {
+            segment_type: self.segment_type,
+            search: search_profile,
+            elapsed: start.elapsed().as_secs_f64(),
+        };
+        Ok((result, vec![profile]))
+    }
+
+    fn similar_pairs(&self, threshold: ScoreType, filter: Option<&Filter>, limit: usize) -> OperationResult<Vec<SimilarPair>> {
+        let internal_pairs = self.vector_index.borrow().similar_pairs(threshold, filter, limit);
         let id_mapper = self.id_mapper.borrow();
-        let res = internal_result.iter()
-            .map(|&scored_point_offset|
-                (
-                    ScoredPoint {
-                        id: id_mapper
-                            .external_id(scored_point_offset.idx)
-                            .unwrap_or_else(|| panic!("Corrupter id_mapper, no external value for {}", scored_point_offset.idx)),
-                        score: scored_point_offset.score,
-                    }
-                )
-            ).collect();
-        return Ok(res);
+        let external_id = |internal_id| id_mapper
+            .external_id(internal_id)
+            .unwrap_or_else(|| panic!("Corrupter id_mapper, no external value for {}", internal_id));
+
+        Ok(internal_pairs
+            .into_iter()
+            .map(|(point_a, point_b, score)| SimilarPair::new(external_id(point_a), external_id(point_b), score))
+            .collect())
     }
 
     fn upsert_point(&mut self, op_num: SeqNumberType, point_id: PointIdType, vector: &Vec<VectorElementType>,
@@ -179,7 +304,10 @@ impl SegmentEntry for Segment {
     ) -> OperationResult<bool> {
         if self.skip_by_version(op_num) { return Ok(false); };
         let internal_id = self.lookup_internal_id(point_id)?;
+        let mut updated_fields = self.indexed_payload_fields(internal_id);
         self.payload_storage.borrow_mut().assign_all(internal_id, full_payload)?;
+        updated_fields.extend(self.indexed_payload_fields(internal_id));
+        self.payload_index.borrow_mut().fields_updated(&updated_fields)?;
         Ok(true)
     }
 
@@ -191,7 +319,25 @@ impl SegmentEntry for Segment {
         if self.skip_by_version(op_num) { return Ok(false); };
         let internal_id = self.lookup_internal_id(point_id)?;
         let payload: TheMap<PayloadKeyType, serde_json::value::Value> = serde_json::from_str(full_payload)?;
+        let mut updated_fields = self.indexed_payload_fields(internal_id);
         self.payload_storage.borrow_mut().assign_all_with_value(internal_id, payload)?;
+        updated_fields.extend(self.indexed_payload_fields(internal_id));
+        self.payload_index.borrow_mut().fields_updated(&updated_fields)?;
+        Ok(true)
+    }
+
+    fn update_payload(&mut self,
+                      op_num: SeqNumberType,
+                      point_id: PointIdType,
+                      patch: &TheMap<PayloadKeyType, serde_json::Value>,
+                      append: &TheMap<PayloadKeyType, serde_json::Value>,
+    ) -> OperationResult<bool> {
+        if self.skip_by_version(op_num) { return Ok(false); };
+        let internal_id = self.lookup_internal_id(point_id)?;
+        let mut updated_fields = self.indexed_payload_fields(internal_id);
+        self.payload_storage.borrow_mut().merge_with_value(internal_id, patch, append)?;
+        updated_fields.extend(self.indexed_payload_fields(internal_id));
+        self.payload_index.borrow_mut().fields_updated(&updated_fields)?;
         Ok(true)
     }
 
@@ -204,6 +350,7 @@ impl SegmentEntry for Segment {
         if self.skip_by_version(op_num) { return Ok(false); };
         let internal_id = self.lookup_internal_id(point_id)?;
         self.payload_storage.borrow_mut().assign(internal_id, key, payload)?;
+        self.payload_index.borrow_mut().fields_updated(slice::from_ref(key))?;
         Ok(true)
     }
 
@@ -211,13 +358,17 @@ impl SegmentEntry for Segment {
         if self.skip_by_version(op_num) { return Ok(false); };
         let internal_id = self.lookup_internal_id(point_id)?;
         self.payload_storage.borrow_mut().delete(internal_id, key)?;
+        self.payload_index.borrow_mut().fields_updated(slice::from_ref(key))?;
         Ok(true)
     }
 
     fn clear_payload(&mut self, op_num: SeqNumberType, point_id: PointIdType) -> OperationResult<bool> {
         if self.skip_by_version(op_num) { return Ok(false); };
         let internal_id = self.lookup_internal_id(point_id)?;
-        self.payload_storage.borrow_mut().drop(internal_id)?;
+        let payload = self.payload_storage.borrow_mut().drop(internal_id)?;
+        if let Some(payload) = payload {
+            self.payload_index.borrow_mut().fields_updated(&payload.keys().cloned().collect_vec())?;
+        }
         Ok(true)
     }
 
@@ -239,6 +390,157 @@ impl SegmentEntry for Segment {
         unsafe { self.id_mapper.as_ptr().as_ref().unwrap().iter_external() }
     }
 
+    fn read_filtered(&self, offset: PointIdType, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType> {
+        match filter {
+            None => self.iter_points()
+                .filter(|id| *id >= offset)
+                .sorted()
+                .take(limit)
+                .collect(),
+            Some(condition) => {
+                let payload_index = self.payload_index.borrow();
+                let id_mapper = self.id_mapper.borrow();
+                payload_index.query_points(condition)
+                    .filter_map(|internal_id| id_mapper.external_id(internal_id))
+                    .filter(|id| *id >= offset)
+                    .sorted()
+                    .take(limit)
+                    .collect()
+            }
+        }
+    }
+
+    fn read_vectors(&self, filter: Option<&Filter>, f: &mut dyn FnMut(PointIdType, Vec<VectorElementType>) -> bool) {
+        let vector_storage = self.vector_storage.borrow();
+        let condition_checker = self.condition_checker.borrow();
+        let id_mapper = self.id_mapper.borrow();
+
+        for internal_id in vector_storage.iter_ids() {
+            if !filter.map_or(true, |filter| condition_checker.check(internal_id, filter)) {
+                continue;
+            }
+            let external_id = match id_mapper.external_id(internal_id) {
+                Some(external_id) => external_id,
+                None => continue,
+            };
+            if !f(external_id, vector_storage.get_vector(internal_id).unwrap()) {
+                break;
+            }
+        }
+    }
+
+    fn filtered_points(&self, filter: &Filter) -> Vec<PointIdType> {
+        let payload_index = self.payload_index.borrow();
+        let id_mapper = self.id_mapper.borrow();
+        payload_index.query_points(filter)
+            .filter_map(|internal_id| id_mapper.external_id(internal_id))
+            .collect()
+    }
+
+    fn sample_filtered(&self, limit: usize, filter: Option<&Filter>) -> Vec<PointIdType> {
+        let vector_storage = self.vector_storage.borrow();
+        let condition_checker = self.condition_checker.borrow();
+        let id_mapper = self.id_mapper.borrow();
+
+        let is_matched = |internal_id: PointOffsetType| filter
+            .map_or(true, |filter| condition_checker.check(internal_id, filter));
+
+        let mut sampled_ids: HashSet<PointOffsetType> = HashSet::new();
+        for internal_id in vector_storage.sample_ids() {
+            if sampled_ids.len() >= limit {
+                break;
+            }
+            if is_matched(internal_id) {
+                sampled_ids.insert(internal_id);
+            }
+        }
+
+        if sampled_ids.len() < limit {
+            // Random draws are exhausted, e.g. because of restrictive filter.
+            // Complete the sample with uniformly selected rest of the matched points
+            let rest_ids = match filter {
+                Some(filter) => self.payload_index.borrow().query_points(filter)
+                    .filter(|internal_id| !sampled_ids.contains(internal_id))
+                    .collect_vec(),
+                None => vector_storage.iter_ids()
+                    .filter(|internal_id| !sampled_ids.contains(internal_id))
+                    .collect_vec(),
+            };
+            let rest_limit = limit - sampled_ids.len();
+            sampled_ids.extend(rest_ids.choose_multiple(&mut rand::thread_rng(), rest_limit));
+        }
+
+        let mut sample = sampled_ids
+            .into_iter()
+            .filter_map(|internal_id| id_mapper.external_id(internal_id))
+            .collect_vec();
+        // Hash set iteration order is not random enough to be used as a random order
+        sample.shuffle(&mut rand::thread_rng());
+        sample
+    }
+
+    fn read_ordered(
+        &self,
+        key: &PayloadKeyType,
+        direction: Direction,
+        from: Option<(FloatPayloadType, PointIdType)>,
+        limit: usize,
+        filter: Option<&Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        let id_mapper = self.id_mapper.borrow();
+        let payload_storage = self.payload_storage.borrow();
+        let mut visited_points: HashSet<PointOffsetType> = HashSet::new();
+        let mut points: Vec<(FloatPayloadType, PointIdType)> = vec![];
+
+        let from_value = from.map(|(value, _)| value);
+        self.payload_index.borrow().walk_ordered(key, direction, from_value, filter, &mut |value, internal_id| {
+            // Points with the same value as the last one are still required to order ties by id
+            let is_complete = points.len() >= limit && points.last().map_or(true, |(last_value, _)| *last_value != value);
+            if is_complete {
+                return false;
+            }
+            if !visited_points.insert(internal_id) {
+                return true;
+            }
+            if let Some(external_id) = id_mapper.external_id(internal_id) {
+                let is_before_offset = from.map_or(false, |(from_value, from_id)| value == from_value && external_id < from_id);
+                // Point with several values is placed by the first of them.
+                // If it is not the current value, the point is placed before `from` and was already read
+                let is_placed_before = from.is_some() && payload_storage
+                    .payload(internal_id)
+                    .get(key)
+                    .and_then(|payload| direction.first_value(payload)) != Some(value);
+                if !is_before_offset && !is_placed_before {
+                    points.push((value, external_id));
+                }
+            }
+            true
+        });
+
+        points.sort_by(|(value_a, id_a), (value_b, id_b)| direction.compare(*value_a, *value_b).then(id_a.cmp(id_b)));
+        points.truncate(limit);
+        points
+    }
+
+    fn estimate_points_count(&self, filter: Option<&Filter>) -> CardinalityEstimation {
+        match filter {
+            None => CardinalityEstimation::exact(self.vectors_count()),
+            Some(condition) => self.payload_index.borrow().estimate_cardinality(condition),
+        }
+    }
+
+    fn facet_counts(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> HashMap<FacetValue, usize> {
+        self.payload_index.borrow().facet_counts(key, filter)
+    }
+
+    fn numeric_range(&self, key: &PayloadKeyType, filter: Option<&Filter>) -> Option<(FloatPayloadType, FloatPayloadType)> {
+        self.payload_index.borrow().numeric_range(key, filter)
+    }
+
+    fn numeric_histogram(&self, key: &PayloadKeyType, filter: Option<&Filter>, min: FloatPayloadType, max: FloatPayloadType, buckets: usize) -> Vec<usize> {
+        self.payload_index.borrow().numeric_histogram(key, filter, min, max, buckets)
+    }
+
     fn has_point(&self, point_id: PointIdType) -> bool {
         self.id_mapper.borrow().internal_id(point_id).is_some()
     }
@@ -323,6 +625,10 @@ impl SegmentEntry for Segment {
     fn get_indexed_fields(&self) -> Vec<PayloadKeyType> {
         self.payload_index.borrow().indexed_fields()
     }
+
+    fn get_outdated_fields(&self) -> Vec<PayloadKeyType> {
+        self.payload_index.borrow().outdated_fields()
+    }
 }
 
 #[cfg(test)]
@@ -364,13 +670,14 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance: Distance::Dot,
         };
 
         let mut segment = build_segment(dir.path(), &config).unwrap();
-        segment.upsert_point(0, 0, &vec![1.0 as f32, 1.0 as f32]).unwrap();
-        segment.set_full_payload_with_json(0, 0, &data.to_string()).unwrap();
-        let payload = segment.payload(0).unwrap();
+        segment.upsert_point(0, 0.into(), &vec![1.0 as f32, 1.0 as f32]).unwrap();
+        segment.set_full_payload_with_json(0, 0.into(), &data.to_string()).unwrap();
+        let payload = segment.payload(0.into()).unwrap();
         let keys: Vec<PayloadKeyType> = payload.keys().cloned().collect();
         assert!(keys.contains(&"geo_data".to_string()));
         assert!(keys.contains(&"name".to_string()));
@@ -511,17 +818,18 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance: Distance::Dot,
         };
 
         let mut segment = build_segment(dir.path(), &config).unwrap();
-        segment.upsert_point(0, 0, &vec![1.0 as f32, 1.0 as f32]).unwrap();
-        let result1 = segment.set_full_payload_with_json(0, 0, &data1.to_string());
+        segment.upsert_point(0, 0.into(), &vec![1.0 as f32, 1.0 as f32]).unwrap();
+        let result1 = segment.set_full_payload_with_json(0, 0.into(), &data1.to_string());
         match result1 {
             Ok(_) => assert!(false),
             Err(_) => assert!(true)
         }
-        let result2 = segment.set_full_payload_with_json(0, 0, &data2.to_string());
+        let result2 = segment.set_full_payload_with_json(0, 0.into(), &data2.to_string());
         match result2 {
             Ok(_) => assert!(false),
             Err(_) => assert!(true)
@@ -547,12 +855,13 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance: Distance::Dot,
         };
 
         let mut segment = build_segment(dir.path(), &config).unwrap();
-        segment.upsert_point(0, 0, &vec![1.0 as f32, 1.0 as f32]).unwrap();
-        segment.set_full_payload_with_json(0, 0, &data.to_string()).unwrap();
+        segment.upsert_point(0, 0.into(), &vec![1.0 as f32, 1.0 as f32]).unwrap();
+        segment.set_full_payload_with_json(0, 0.into(), &data.to_string()).unwrap();
 
         let filter_valid_str = r#"
         {
@@ -582,7 +891,7 @@ mod tests {
         let filter_invalid: Filter = serde_json::from_str(filter_invalid_str).unwrap();
         let results_with_valid_filter = segment.search(&vec![1.0 as f32, 1.0 as f32], Some(&filter_valid), 1, None).unwrap();
         assert_eq!(results_with_valid_filter.len(), 1);
-        assert_eq!(results_with_valid_filter.first().unwrap().id, 0);
+        assert_eq!(results_with_valid_filter.first().unwrap().id, 0.into());
         let results_with_invalid_filter = segment.search(&vec![1.0 as f32, 1.0 as f32], Some(&filter_invalid), 1, None).unwrap();
         assert!(results_with_invalid_filter.is_empty());
     }
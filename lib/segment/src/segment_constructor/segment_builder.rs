@@ -4,7 +4,8 @@ use core::cmp;
 use crate::types::{PayloadKeyType, SegmentConfig};
 use std::collections::HashSet;
 use std::convert::TryInto;
-use crate::segment_constructor::segment_constructor::{build_segment, load_segment};
+use crate::segment_constructor::segment_constructor::{build_segment, load_segment, ID_MAPPER_PATH, MMAP_ID_MAPPER_PATH};
+use crate::id_mapper::mmap_id_mapper::MmapIdMapper;
 use std::path::{Path, PathBuf};
 use std::fs;
 use crate::common::error_logging::LogError;
@@ -71,7 +72,7 @@ impl TryInto<Segment> for SegmentBuilder {
     type Error = OperationError;
 
     fn try_into(mut self) -> Result<Segment, Self::Error> {
-        {
+        let is_appendable = {
             let mut segment = self.segment.ok_or(OperationError::ServiceError {
                 description: "Segment building error: created segment not found".to_owned()
             })?;
@@ -84,7 +85,18 @@ impl TryInto<Segment> for SegmentBuilder {
             segment.vector_index.borrow_mut().build_index()?;
 
             segment.flush()?;
+
+            if !segment.appendable_flag {
+                // Points of non-appendable segment are never added, so compact immutable mapping is used
+                MmapIdMapper::save(&self.temp_path.join(MMAP_ID_MAPPER_PATH), &*segment.id_mapper.borrow())?;
+            }
+
             // Now segment is going to be evicted from RAM
+            segment.appendable_flag
+        };
+
+        if !is_appendable {
+            fs::remove_dir_all(self.temp_path.join(ID_MAPPER_PATH)).describe("Removing replaced id mapper")?;
         }
 
         // Move fully constructed segment into collection directory and load back to RAM
@@ -1,9 +1,13 @@
 use crate::segment::{Segment, SEGMENT_STATE_FILE};
 use crate::id_mapper::simple_id_mapper::SimpleIdMapper;
+use crate::id_mapper::mmap_id_mapper::MmapIdMapper;
+use crate::id_mapper::id_mapper::IdMapper;
 use crate::vector_storage::simple_vector_storage::SimpleVectorStorage;
 use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
+use crate::payload_storage::on_disk_payload_storage::OnDiskPayloadStorage;
+use crate::payload_storage::payload_storage::PayloadStorage;
 use crate::index::plain_payload_index::{PlainPayloadIndex, PlainIndex};
-use crate::types::{SegmentType, SegmentConfig, Indexes, SegmentState, SeqNumberType, StorageType, PayloadIndexType};
+use crate::types::{SegmentType, SegmentConfig, Indexes, SegmentState, SeqNumberType, StorageType, PayloadIndexType, PayloadStorageType};
 use std::sync::{Arc, Mutex};
 use atomic_refcell::AtomicRefCell;
 use crate::payload_storage::query_checker::SimpleConditionChecker;
@@ -19,17 +23,27 @@ use crate::index::index::{PayloadIndex, VectorIndex};
 use crate::index::hnsw_index::hnsw::HNSWIndex;
 
 
+pub const ID_MAPPER_PATH: &str = "id_mapper";
+pub const MMAP_ID_MAPPER_PATH: &str = "mmap_id_mapper";
+
+
 fn sp<T>(t: T) -> Arc<AtomicRefCell<T>> { Arc::new(AtomicRefCell::new(t)) }
 
 
 fn create_segment(version: SeqNumberType, segment_path: &Path, config: &SegmentConfig) -> OperationResult<Segment> {
-    let mapper_path = segment_path.join("id_mapper");
+    let mapper_path = segment_path.join(ID_MAPPER_PATH);
     let payload_storage_path = segment_path.join("payload_storage");
     let payload_index_path = segment_path.join("payload_index");
     let vector_storage_path = segment_path.join("vector_storage");
     let vector_index_path = segment_path.join("vector_index");
+    let mmap_mapper_path = segment_path.join(MMAP_ID_MAPPER_PATH);
 
-    let id_mapper = sp(SimpleIdMapper::open(mapper_path.as_path())?);
+    // Compact mapping is written by `SegmentBuilder` for non-appendable segments only
+    let id_mapper: Arc<AtomicRefCell<dyn IdMapper>> = if MmapIdMapper::exists(&mmap_mapper_path) {
+        sp(MmapIdMapper::open(&mmap_mapper_path)?)
+    } else {
+        sp(SimpleIdMapper::open(mapper_path.as_path())?)
+    };
 
 
     let vector_storage: Arc<AtomicRefCell<dyn VectorStorage>> = match config.storage_type {
@@ -45,7 +59,10 @@ fn create_segment(version: SeqNumberType, segment_path: &Path, config: &SegmentC
         )?),
     };
 
-    let payload_storage = sp(SimplePayloadStorage::open(payload_storage_path.as_path())?);
+    let payload_storage: Arc<AtomicRefCell<dyn PayloadStorage>> = match config.payload_storage_type {
+        PayloadStorageType::InMemory => sp(SimplePayloadStorage::open(payload_storage_path.as_path())?),
+        PayloadStorageType::OnDisk => sp(OnDiskPayloadStorage::open(payload_storage_path.as_path())?),
+    };
 
 
     let condition_checker = sp(SimpleConditionChecker::new(
@@ -57,15 +74,22 @@ fn create_segment(version: SeqNumberType, segment_path: &Path, config: &SegmentC
         PayloadIndexType::Plain => sp(PlainPayloadIndex::open(
             condition_checker.clone(),
             vector_storage.clone(),
+            payload_storage.clone(),
             &payload_index_path)?),
         PayloadIndexType::Struct => sp(StructPayloadIndex::open(
             condition_checker.clone(),
             vector_storage.clone(),
             payload_storage.clone(),
             id_mapper.clone(),
-            &payload_index_path)?),
+            &payload_index_path,
+            config.storage_type == StorageType::Mmap)?),
     };
 
+    // Reading payload from disk is expensive, so field indexes are used for condition checks instead
+    if config.payload_storage_type == PayloadStorageType::OnDisk {
+        condition_checker.borrow_mut().use_payload_index(&payload_index);
+    }
+
     let vector_index: Arc<AtomicRefCell<dyn VectorIndex>> = match config.index {
         Indexes::Plain { .. } => sp(PlainIndex::new(
             vector_storage.clone(),
@@ -3,9 +3,12 @@ use schemars::{JsonSchema};
 use std::cmp::{Ordering};
 use ordered_float::OrderedFloat;
 use std::collections::{BTreeMap, HashSet, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
 
-pub type PointIdType = u64;
 /// Type of point index across all segments
+pub type PointIdType = ExtendedPointId;
 pub type PointOffsetType = u32;
 /// Type of point index inside a segment
 pub type PayloadKeyType = String;
@@ -21,6 +24,47 @@ pub type FloatPayloadType = f64;
 /// Type of integer point payload
 pub type IntPayloadType = i64;
 
+/// Point id, either an unsigned integer or an UUID
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[serde(untagged)]
+pub enum ExtendedPointId {
+    NumId(u64),
+    Uuid(Uuid),
+}
+
+impl From<u64> for ExtendedPointId {
+    fn from(id: u64) -> Self {
+        ExtendedPointId::NumId(id)
+    }
+}
+
+impl From<Uuid> for ExtendedPointId {
+    fn from(uuid: Uuid) -> Self {
+        ExtendedPointId::Uuid(uuid)
+    }
+}
+
+impl Display for ExtendedPointId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtendedPointId::NumId(id) => id.fmt(f),
+            ExtendedPointId::Uuid(uuid) => uuid.fmt(f),
+        }
+    }
+}
+
+impl FromStr for ExtendedPointId {
+    type Err = String;
+
+    /// Numeric ids take precedence, anything else is parsed as UUID
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<u64>()
+            .map(ExtendedPointId::NumId)
+            .or_else(|_| s.parse::<Uuid>().map(ExtendedPointId::Uuid))
+            .map_err(|_| format!("Invalid point id: {}", s))
+    }
+}
+
 /// Type of internal tags, build from payload
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy)]
 /// Distance function types used to compare vectors
@@ -61,6 +105,54 @@ impl PartialOrd for ScoredPoint {
     }
 }
 
+#[derive(Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Debug)]
+/// Pair of points with similar vectors
+pub struct SimilarPair {
+    /// Point with smaller id
+    pub a: PointIdType,
+    /// Point with larger id
+    pub b: PointIdType,
+    /// Similarity of the points vectors
+    pub score: ScoreType,
+}
+
+impl SimilarPair {
+    pub fn new(point_a: PointIdType, point_b: PointIdType, score: ScoreType) -> Self {
+        SimilarPair { a: point_a.min(point_b), b: point_a.max(point_b), score }
+    }
+}
+
+/// Query, defined by multiple example vectors instead of a single one
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExampleQuery {
+    /// Each point is scored against every example.
+    /// The best positive similarity wins unless some negative example is closer.
+    BestScore {
+        positive: Vec<Vec<VectorElementType>>,
+        negative: Vec<Vec<VectorElementType>>,
+    },
+    /// Points are ranked by the number of context pairs, in which they are closer to positive example.
+    /// Similarity to the target breaks ties.
+    Discovery {
+        target: Vec<VectorElementType>,
+        /// Pairs of (positive, negative) examples
+        context: Vec<(Vec<VectorElementType>, Vec<VectorElementType>)>,
+    },
+}
+
+impl ExampleQuery {
+    /// Iterate over all example vectors of the query
+    pub fn vectors(&self) -> Box<dyn Iterator<Item=&Vec<VectorElementType>> + '_> {
+        match self {
+            ExampleQuery::BestScore { positive, negative } => Box::new(positive.iter().chain(negative.iter())),
+            ExampleQuery::Discovery { target, context } => Box::new(
+                std::iter::once(target)
+                    .chain(context.iter().flat_map(|(positive, negative)| vec![positive, negative]))
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum SegmentType {
@@ -185,6 +277,23 @@ impl Default for StorageType {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "type", content = "options")]
+/// Type of payload storage
+pub enum PayloadStorageType {
+    /// Keep all payloads in memory and use persistence storage only if payload is changed
+    InMemory,
+    /// Read payloads from persistence storage on demand, keeping only recently used ones in memory
+    OnDisk,
+}
+
+impl Default for PayloadStorageType {
+    fn default() -> Self {
+        PayloadStorageType::InMemory
+    }
+}
+
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -199,6 +308,9 @@ pub struct SegmentConfig {
     pub payload_index: Option<PayloadIndexType>,
     /// Type of vector storage
     pub storage_type: StorageType,
+    /// Type of payload storage
+    #[serde(default)]
+    pub payload_storage_type: PayloadStorageType,
 }
 
 /// Default value based on https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md
@@ -211,7 +323,7 @@ pub struct SegmentState {
     pub config: SegmentConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct GeoPoint {
     pub lon: f64,
@@ -228,6 +340,115 @@ pub enum PayloadType {
     Geo(Vec<GeoPoint>),
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[serde(untagged)]
+/// Single value of keyword or integer payload field, used to build facets and groups
+pub enum FacetValue {
+    Keyword(String),
+    Integer(IntPayloadType),
+}
+
+impl FacetValue {
+    /// List all facet values of the payload. Float and geo payloads have no facet values
+    pub fn from_payload(payload: &PayloadType) -> Vec<FacetValue> {
+        match payload {
+            PayloadType::Keyword(values) => values.iter().cloned().map(FacetValue::Keyword).collect(),
+            PayloadType::Integer(values) => values.iter().cloned().map(FacetValue::Integer).collect(),
+            PayloadType::Float(_) | PayloadType::Geo(_) => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Direction of ordering by payload field value
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::Asc
+    }
+}
+
+impl Direction {
+    /// Compare field values in this direction
+    pub fn compare(&self, value_a: FloatPayloadType, value_b: FloatPayloadType) -> Ordering {
+        let ordering = OrderedFloat(value_a).cmp(&OrderedFloat(value_b));
+        match self {
+            Direction::Asc => ordering,
+            Direction::Desc => ordering.reverse(),
+        }
+    }
+
+    /// First of the numeric payload values in this direction, `None` for non-numeric payload.
+    /// Point with several values is ordered by this value
+    pub fn first_value(&self, payload: &PayloadType) -> Option<FloatPayloadType> {
+        let values: Vec<FloatPayloadType> = match payload {
+            PayloadType::Integer(numbers) => numbers.iter().map(|x| *x as FloatPayloadType).collect(),
+            PayloadType::Float(numbers) => numbers.clone(),
+            _ => return None,
+        };
+        values.into_iter().min_by(|value_a, value_b| self.compare(*value_a, *value_b))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Way of the query processing, chosen by the vector index
+pub enum SearchPath {
+    /// Points, which satisfy filter, are retrieved from payload index and scored one by one
+    Plain,
+    /// HNSW graph is traversed with filtering condition
+    Graph,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Condition, which could be used to make a primary selection of points
+pub enum PrimaryClauseInfo {
+    Condition(FieldCondition),
+    /// Number of ids in the selection
+    Ids(usize),
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Estimation of the number of points, which satisfy filter
+pub struct CardinalityInfo {
+    pub primary_clauses: Vec<PrimaryClauseInfo>,
+    pub min: usize,
+    pub exp: usize,
+    pub max: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Details of the search query processing by the vector index
+pub struct SearchProfile {
+    /// Cardinality estimation of the filter, if any
+    pub cardinality: Option<CardinalityInfo>,
+    /// If cardinality was checked on a random sample of points
+    pub sample_check: bool,
+    pub path: SearchPath,
+    /// Number of points, for which score was calculated
+    pub scored_points: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Details of the search query processing by a single segment
+pub struct SegmentSearchProfile {
+    pub segment_type: SegmentType,
+    #[serde(flatten)]
+    pub search: SearchProfile,
+    /// Time spent on search in segment, seconds
+    pub elapsed: f64,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "type", content = "value")]
@@ -315,7 +536,7 @@ impl From<&PayloadInterface> for PayloadType {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Match {
     /// Keyword value to match
@@ -324,7 +545,7 @@ pub struct Match {
     pub integer: Option<IntPayloadType>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Range {
     /// point.key < range.lt
@@ -337,7 +558,7 @@ pub struct Range {
     pub lte: Option<FloatPayloadType>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct GeoBoundingBox {
     /// Coordinates of the top left point of the area rectangle
@@ -346,7 +567,7 @@ pub struct GeoBoundingBox {
     pub bottom_right: GeoPoint,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct GeoRadius {
     /// Coordinates of the top left point of the area rectangle
@@ -355,7 +576,7 @@ pub struct GeoRadius {
     pub radius: f64,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct FieldCondition {
     pub key: PayloadKeyType,
@@ -369,7 +590,7 @@ pub struct FieldCondition {
     pub geo_radius: Option<GeoRadius>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 pub struct HasIdCondition {
     pub has_id: HashSet<PointIdType>
 }
@@ -382,7 +603,7 @@ impl Into<HasIdCondition> for HashSet<PointIdType> {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Condition {
     /// Check if field satisfies provided condition
@@ -393,7 +614,7 @@ pub enum Condition {
     Filter(Filter),
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
 pub struct Filter {
@@ -570,6 +791,18 @@ mod tests {
         assert!(filter.is_err())
     }
 
+    #[test]
+    fn test_point_id_parse() {
+        let ids: Vec<PointIdType> = serde_json::from_str(r#"[42, "4a2b6bd1-4e47-4c3f-8f8c-1d2c3b4a5e6f"]"#).unwrap();
+        assert_eq!(ids[0], PointIdType::NumId(42));
+        assert!(matches!(ids[1], PointIdType::Uuid(_)));
+        assert_eq!(serde_json::to_string(&ids[0]).unwrap(), "42");
+
+        assert_eq!("42".parse::<PointIdType>().unwrap(), ids[0]);
+        assert_eq!(ids[1].to_string().parse::<PointIdType>().unwrap(), ids[1]);
+        assert!("not-an-id".parse::<PointIdType>().is_err());
+    }
+
     #[test]
     fn test_payload_query_parse() {
         let query1 = r#"
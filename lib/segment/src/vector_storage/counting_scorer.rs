@@ -0,0 +1,72 @@
+use std::cell::Cell;
+
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::vector_storage::{RawScorer, ScoredPointOffset};
+
+/// Wrapper of the raw scorer, which counts the number of score calculations.
+/// Used to profile search queries.
+pub struct CountingRawScorer<'a> {
+    raw_scorer: &'a dyn RawScorer,
+    scored: Cell<usize>,
+}
+
+impl<'a> CountingRawScorer<'a> {
+    pub fn new(raw_scorer: &'a dyn RawScorer) -> Self {
+        CountingRawScorer { raw_scorer, scored: Cell::new(0) }
+    }
+
+    /// Number of points scored so far
+    pub fn scored_points(&self) -> usize {
+        self.scored.get()
+    }
+
+    fn count(&self, points: usize) {
+        self.scored.set(self.scored.get() + points)
+    }
+}
+
+impl RawScorer for CountingRawScorer<'_> {
+    fn score_points<'b>(&'b self, points: &'b mut dyn Iterator<Item=PointOffsetType>) -> Box<dyn Iterator<Item=ScoredPointOffset> + 'b> {
+        Box::new(self.raw_scorer.score_points(points).inspect(move |_| self.count(1)))
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        self.raw_scorer.check_point(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        self.count(1);
+        self.raw_scorer.score_point(point)
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        self.count(1);
+        self.raw_scorer.score_internal(point_a, point_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Distance;
+    use crate::vector_storage::simple_vector_storage::SimpleVectorStorage;
+    use crate::vector_storage::vector_storage::VectorStorage;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_counting_scorer() {
+        let dir = TempDir::new("storage_dir").unwrap();
+        let mut storage = SimpleVectorStorage::open(dir.path(), 2, Distance::Dot).unwrap();
+        for vector in vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]] {
+            storage.put_vector(vector).unwrap();
+        }
+        storage.delete(1).unwrap();
+
+        let raw_scorer = storage.raw_scorer(vec![1.0, 0.0]);
+        let counting_scorer = CountingRawScorer::new(raw_scorer.as_ref());
+        let scored = counting_scorer.score_points(&mut storage.iter_ids()).count();
+        assert_eq!(scored, 2);
+        counting_scorer.score_point(0);
+        assert_eq!(counting_scorer.scored_points(), 3);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::vector_storage::{ScoredPointOffset, RawScorer};
+
+
+/// Fast sigmoid, maps any similarity into (0, 1) range preserving order
+fn scale_score(score: ScoreType) -> ScoreType {
+    0.5 * (score / (1.0 + score.abs()) + 1.0)
+}
+
+/// Scores points against multiple positive and negative examples.
+/// Points closer to some positive example than to any negative one are scored in (0, 1) range
+/// by the best positive similarity. Other points are scored in (-1, 0) range, closer to negative is worse.
+pub struct BestScoreRawScorer<'a> {
+    /// Scorers of positive examples, at least one is required
+    pub positive: Vec<Box<dyn RawScorer + 'a>>,
+    pub negative: Vec<Box<dyn RawScorer + 'a>>,
+}
+
+impl BestScoreRawScorer<'_> {
+    fn max_score(scorers: &[Box<dyn RawScorer + '_>], point: PointOffsetType) -> ScoreType {
+        scorers.iter()
+            .map(|scorer| scorer.score_point(point))
+            .fold(ScoreType::NEG_INFINITY, ScoreType::max)
+    }
+}
+
+impl RawScorer for BestScoreRawScorer<'_> {
+    fn score_points<'a>(&'a self, points: &'a mut dyn Iterator<Item=PointOffsetType>) -> Box<dyn Iterator<Item=ScoredPointOffset> + 'a> {
+        let res_iter = points
+            .filter(move |point| self.check_point(*point))
+            .map(move |point| ScoredPointOffset {
+                idx: point,
+                score: self.score_point(point),
+            });
+        Box::new(res_iter)
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        self.positive[0].check_point(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        let max_positive = Self::max_score(&self.positive, point);
+        let max_negative = Self::max_score(&self.negative, point);
+        if max_positive > max_negative {
+            scale_score(max_positive)
+        } else {
+            -scale_score(max_negative)
+        }
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        // Distance between stored points does not depend on the query
+        self.positive[0].score_internal(point_a, point_b)
+    }
+}
+
+/// Ranks points by the number of context pairs, in which point is closer to the positive example.
+/// Each pair adds +1 to the rank if point is on the positive side, and -1 otherwise.
+/// Target similarity is scaled into (0, 1) range and added to the rank, so it only breaks ties.
+pub struct DiscoveryRawScorer<'a> {
+    pub target: Box<dyn RawScorer + 'a>,
+    /// Scorers of (positive, negative) context pairs
+    pub context: Vec<(Box<dyn RawScorer + 'a>, Box<dyn RawScorer + 'a>)>,
+}
+
+impl RawScorer for DiscoveryRawScorer<'_> {
+    fn score_points<'a>(&'a self, points: &'a mut dyn Iterator<Item=PointOffsetType>) -> Box<dyn Iterator<Item=ScoredPointOffset> + 'a> {
+        let res_iter = points
+            .filter(move |point| self.check_point(*point))
+            .map(move |point| ScoredPointOffset {
+                idx: point,
+                score: self.score_point(point),
+            });
+        Box::new(res_iter)
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        self.target.check_point(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        let rank: i32 = self.context.iter()
+            .map(|(positive, negative)| {
+                if positive.score_point(point) > negative.score_point(point) { 1 } else { -1 }
+            })
+            .sum();
+        rank as ScoreType + scale_score(self.target.score_point(point))
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        self.target.score_internal(point_a, point_b)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::vector_storage::simple_vector_storage::SimpleVectorStorage;
+    use crate::vector_storage::vector_storage::VectorStorage;
+    use crate::types::{Distance, ExampleQuery};
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_best_score_scorer() {
+        let dir = TempDir::new("storage_dir").unwrap();
+        let mut storage = SimpleVectorStorage::open(dir.path(), 2, Distance::Dot).unwrap();
+
+        storage.put_vector(vec![1.0, 0.0]).unwrap();
+        storage.put_vector(vec![0.0, 1.0]).unwrap();
+        storage.put_vector(vec![0.9, 0.1]).unwrap();
+        storage.put_vector(vec![-1.0, 0.0]).unwrap();
+
+        let query = ExampleQuery::BestScore {
+            positive: vec![vec![1.0, 0.0], vec![-1.0, 0.0]],
+            negative: vec![vec![0.0, 1.0]],
+        };
+
+        let scorer = storage.raw_scorer_examples(&query);
+
+        // Point 1 is closer to negative example
+        assert!(scorer.score_point(1) < 0.0);
+        assert!(scorer.score_point(0) > 0.0);
+        // Point 3 is an exact match of the second positive example
+        assert_eq!(scorer.score_point(0), scorer.score_point(3));
+        assert!(scorer.score_point(0) > scorer.score_point(2));
+    }
+
+    #[test]
+    fn test_discovery_scorer() {
+        let dir = TempDir::new("storage_dir").unwrap();
+        let mut storage = SimpleVectorStorage::open(dir.path(), 2, Distance::Dot).unwrap();
+
+        storage.put_vector(vec![1.0, 0.0]).unwrap();
+        storage.put_vector(vec![0.0, 1.0]).unwrap();
+        storage.put_vector(vec![0.5, 0.5]).unwrap();
+
+        let query = ExampleQuery::Discovery {
+            target: vec![1.0, 0.0],
+            context: vec![(vec![0.0, 1.0], vec![1.0, 0.0])],
+        };
+
+        let scorer = storage.raw_scorer_examples(&query);
+
+        // Point 1 is on the positive side of the context pair, despite being far from the target
+        assert!(scorer.score_point(1) > scorer.score_point(0));
+        // Points 0 and 2 are not on the positive side, so target similarity decides
+        assert!(scorer.score_point(0) > scorer.score_point(2));
+        assert!(scorer.score_point(2) < 0.0);
+    }
+}
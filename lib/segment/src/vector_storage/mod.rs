@@ -1,4 +1,6 @@
 pub mod vector_storage;
 pub mod simple_vector_storage;
 pub mod memmap_vector_storage;
+pub mod example_scorer;
+pub mod counting_scorer;
 mod mmap_vectors;
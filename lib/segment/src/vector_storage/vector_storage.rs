@@ -1,4 +1,5 @@
-use crate::types::{PointOffsetType, ScoreType, VectorElementType};
+use crate::types::{PointOffsetType, ScoreType, VectorElementType, ExampleQuery};
+use crate::vector_storage::example_scorer::{BestScoreRawScorer, DiscoveryRawScorer};
 use std::cmp::{Ordering};
 use ordered_float::OrderedFloat;
 use crate::entry::entry_point::OperationResult;
@@ -64,6 +65,22 @@ pub trait VectorStorage {
     /// Same as `raw_scorer` but uses internal vector for search, avoids double pre-processing
     fn raw_scorer_internal(&self, point_id: PointOffsetType) -> Box<dyn RawScorer + '_>;
 
+    /// Generate a RawScorer object, which scores points against multiple examples of the query
+    fn raw_scorer_examples(&self, query: &ExampleQuery) -> Box<dyn RawScorer + '_> {
+        match query {
+            ExampleQuery::BestScore { positive, negative } => Box::new(BestScoreRawScorer {
+                positive: positive.iter().map(|vector| self.raw_scorer(vector.clone())).collect(),
+                negative: negative.iter().map(|vector| self.raw_scorer(vector.clone())).collect(),
+            }),
+            ExampleQuery::Discovery { target, context } => Box::new(DiscoveryRawScorer {
+                target: self.raw_scorer(target.clone()),
+                context: context.iter()
+                    .map(|(positive, negative)| (self.raw_scorer(positive.clone()), self.raw_scorer(negative.clone())))
+                    .collect(),
+            }),
+        }
+    }
+
 
     fn score_points(
         &self,
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use tempdir::TempDir;
-    use segment::types::{StorageType, Distance, PayloadIndexType, Indexes, SegmentConfig, TheMap, PayloadKeyType, PayloadType, SeqNumberType, PointIdType, Condition, FieldCondition, Filter, Range, SearchParams, HnswConfig};
+    use segment::types::{StorageType, Distance, PayloadIndexType, Indexes, SegmentConfig, TheMap, PayloadKeyType, PayloadType, SeqNumberType, Condition, FieldCondition, Filter, Range, SearchParams, HnswConfig, Match, SearchPath};
     use segment::segment_constructor::segment_constructor::build_segment;
     use segment::fixtures::payload_fixtures::{random_vector, random_int_payload};
     use segment::entry::entry_point::SegmentEntry;
@@ -17,7 +17,7 @@ mod tests {
     fn test_filterable_hnsw() {
         let dim = 8;
         let m = 8;
-        let num_vectors: PointIdType = 5_000;
+        let num_vectors: u64 = 5_000;
         let ef = 32;
         let ef_construct = 16;
         let distance = Distance::Cosine;
@@ -35,6 +35,7 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance,
         };
 
@@ -46,8 +47,8 @@ mod tests {
             let mut payload: TheMap<PayloadKeyType, PayloadType> = Default::default();
             payload.insert(int_key.clone(), random_int_payload(&mut rnd, num_payload_values));
 
-            segment.upsert_point(idx as SeqNumberType, idx, &vector).unwrap();
-            segment.set_full_payload(idx as SeqNumberType, idx, payload.clone()).unwrap();
+            segment.upsert_point(idx as SeqNumberType, idx.into(), &vector).unwrap();
+            segment.set_full_payload(idx as SeqNumberType, idx.into(), payload.clone()).unwrap();
         }
         // let opnum = num_vectors + 1;
 
@@ -57,6 +58,7 @@ mod tests {
             segment.payload_storage.clone(),
             segment.id_mapper.clone(),
             payload_index_dir.path(),
+            false,
         ).unwrap();
 
         let payload_index_ptr = Arc::new(AtomicRefCell::new(payload_index));
@@ -127,4 +129,164 @@ mod tests {
         assert!(attempts - hits < 5);  // Not more than 5% failures
         eprintln!("hits = {:#?} out of {}", hits, attempts);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_hnsw_similar_pairs() {
+        let dim = 8;
+        let num_vectors: u64 = 1_000;
+        let num_duplicates: u64 = 20;
+
+        let mut rnd = thread_rng();
+
+        let dir = TempDir::new("segment_dir").unwrap();
+        let payload_index_dir = TempDir::new("payload_index_dir").unwrap();
+        let hnsw_dir = TempDir::new("hnsw_dir").unwrap();
+
+        let config = SegmentConfig {
+            vector_size: dim,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Plain),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+            distance: Distance::Cosine,
+        };
+
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        for idx in 0..num_vectors {
+            let vector = random_vector(&mut rnd, dim);
+            segment.upsert_point(idx as SeqNumberType, idx.into(), &vector).unwrap();
+        }
+
+        // Slightly changed copies of the first points
+        for idx in 0..num_duplicates {
+            let vector = segment.vector(idx.into()).unwrap()
+                .into_iter()
+                .map(|x| x + rnd.gen_range(-0.001..0.001))
+                .collect_vec();
+            segment.upsert_point((num_vectors + idx) as SeqNumberType, (num_vectors + idx).into(), &vector).unwrap();
+        }
+
+        let payload_index = StructPayloadIndex::open(
+            segment.condition_checker.clone(),
+            segment.vector_storage.clone(),
+            segment.payload_storage.clone(),
+            segment.id_mapper.clone(),
+            payload_index_dir.path(),
+            false,
+        ).unwrap();
+
+        let hnsw_config = HnswConfig {
+            m: 8,
+            ef_construct: 16,
+            full_scan_threshold: 100,
+        };
+
+        let mut hnsw_index = HNSWIndex::open(
+            hnsw_dir.path(),
+            segment.condition_checker.clone(),
+            segment.vector_storage.clone(),
+            Arc::new(AtomicRefCell::new(payload_index)),
+            hnsw_config,
+        ).unwrap();
+
+        hnsw_index.build_index().unwrap();
+
+        let threshold = 0.999;
+        let pairs = hnsw_index.similar_pairs(threshold, None, usize::MAX);
+
+        for idx in 0..num_duplicates {
+            let internal_id = segment.id_mapper.borrow().internal_id(idx.into()).unwrap();
+            let duplicate_internal_id = segment.id_mapper.borrow().internal_id((num_vectors + idx).into()).unwrap();
+            assert!(
+                pairs.iter().any(|(a, b, _)| *a == internal_id && *b == duplicate_internal_id),
+                "duplicate of {} is not found", idx
+            );
+        }
+
+        assert!(pairs.iter().all(|(a, b, score)| a < b && *score >= threshold));
+    }
+
+    #[test]
+    fn test_hnsw_search_explain() {
+        let dim = 8;
+        let num_vectors: u64 = 1_000;
+        let top = 5;
+
+        let mut rnd = thread_rng();
+
+        let dir = TempDir::new("segment_dir").unwrap();
+        let payload_index_dir = TempDir::new("payload_index_dir").unwrap();
+        let hnsw_dir = TempDir::new("hnsw_dir").unwrap();
+
+        let config = SegmentConfig {
+            vector_size: dim,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Plain),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+            distance: Distance::Cosine,
+        };
+
+        let int_key = "int".to_string();
+
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        for idx in 0..num_vectors {
+            let vector = random_vector(&mut rnd, dim);
+            let mut payload: TheMap<PayloadKeyType, PayloadType> = Default::default();
+            payload.insert(int_key.clone(), PayloadType::Integer(vec![(idx % 10) as i64]));
+
+            segment.upsert_point(idx as SeqNumberType, idx.into(), &vector).unwrap();
+            segment.set_full_payload(idx as SeqNumberType, idx.into(), payload).unwrap();
+        }
+
+        let mut payload_index = StructPayloadIndex::open(
+            segment.condition_checker.clone(),
+            segment.vector_storage.clone(),
+            segment.payload_storage.clone(),
+            segment.id_mapper.clone(),
+            payload_index_dir.path(),
+            false,
+        ).unwrap();
+        payload_index.set_indexed(&int_key).unwrap();
+
+        let hnsw_config = HnswConfig {
+            m: 8,
+            ef_construct: 16,
+            full_scan_threshold: 500,
+        };
+
+        let mut hnsw_index = HNSWIndex::open(
+            hnsw_dir.path(),
+            segment.condition_checker.clone(),
+            segment.vector_storage.clone(),
+            Arc::new(AtomicRefCell::new(payload_index)),
+            hnsw_config,
+        ).unwrap();
+
+        hnsw_index.build_index().unwrap();
+
+        let query = random_vector(&mut rnd, dim);
+
+        let (result, profile) = hnsw_index.search_explain(&query, None, top, None);
+        assert_eq!(result, hnsw_index.search(&query, None, top, None));
+        assert_eq!(profile.path, SearchPath::Graph);
+        assert!(profile.cardinality.is_none());
+        assert!(profile.scored_points >= top && profile.scored_points < num_vectors as usize);
+
+        // 100 points match the filter, which is below the full scan threshold
+        let filter = Filter::new_must(Condition::Field(FieldCondition {
+            key: int_key.clone(),
+            r#match: Some(Match { keyword: None, integer: Some(0) }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        }));
+
+        let (result, profile) = hnsw_index.search_explain(&query, Some(&filter), top, None);
+        assert_eq!(result, hnsw_index.search(&query, Some(&filter), top, None));
+        assert_eq!(profile.path, SearchPath::Plain);
+        assert!(!profile.sample_check);
+        assert_eq!(profile.cardinality.unwrap().max, 100);
+        assert_eq!(profile.scored_points, 100);
+    }
+}
@@ -1,9 +1,12 @@
 #[cfg(test)]
 mod tests {
-    use segment::fixtures::payload_fixtures::{random_vector, random_keyword_payload, random_int_payload, random_filter};
+    use segment::fixtures::payload_fixtures::{random_vector, random_keyword, random_keyword_payload, random_int_payload, random_filter};
     use tempdir::TempDir;
-    use segment::types::{SegmentConfig, Indexes, PayloadIndexType, StorageType, Distance, TheMap, PayloadKeyType, PayloadType, Filter, Condition, FieldCondition, Range};
+    use segment::types::{SegmentConfig, Indexes, PayloadIndexType, PayloadStorageType, StorageType, Distance, TheMap, PayloadKeyType, PayloadType, Filter, Condition, FieldCondition, Range};
     use segment::segment_constructor::segment_constructor::build_segment;
+    use segment::segment_constructor::segment_builder::SegmentBuilder;
+    use segment::segment::Segment;
+    use std::convert::TryInto;
     use segment::entry::entry_point::SegmentEntry;
     use itertools::Itertools;
 
@@ -20,6 +23,7 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Struct),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance: Distance::Dot,
         };
 
@@ -36,8 +40,8 @@ mod tests {
             payload.insert(str_key.clone(), random_keyword_payload(&mut rnd));
             payload.insert(int_key.clone(), random_int_payload(&mut rnd, 2));
 
-            struct_segment.upsert_point(opnum, idx, &vector).unwrap();
-            struct_segment.set_full_payload(opnum, idx, payload.clone()).unwrap();
+            struct_segment.upsert_point(opnum, idx.into(), &vector).unwrap();
+            struct_segment.set_full_payload(opnum, idx.into(), payload.clone()).unwrap();
 
             opnum += 1;
         }
@@ -93,6 +97,7 @@ mod tests {
             index: Indexes::Plain {},
             payload_index: Some(PayloadIndexType::Plain),
             storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
             distance: Distance::Dot,
         };
 
@@ -113,11 +118,11 @@ mod tests {
             payload.insert(str_key.clone(), random_keyword_payload(&mut rnd));
             payload.insert(int_key.clone(), random_int_payload(&mut rnd, num_int_values));
 
-            plain_segment.upsert_point(idx, idx, &vector).unwrap();
-            struct_segment.upsert_point(idx, idx, &vector).unwrap();
+            plain_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+            struct_segment.upsert_point(idx, idx.into(), &vector).unwrap();
 
-            plain_segment.set_full_payload(idx, idx, payload.clone()).unwrap();
-            struct_segment.set_full_payload(idx, idx, payload.clone()).unwrap();
+            plain_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
+            struct_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
 
             opnum += 1;
         }
@@ -148,4 +153,267 @@ mod tests {
                 });
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_facet_counts() {
+        // Compare facet counts and histograms of plain and struct indexes
+        let mut rnd = rand::thread_rng();
+
+        let dir1 = TempDir::new("segment1_dir").unwrap();
+        let dir2 = TempDir::new("segment2_dir").unwrap();
+
+        let dim = 5;
+
+        let mut config = SegmentConfig {
+            vector_size: dim,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Plain),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+            distance: Distance::Dot,
+        };
+
+        let mut plain_segment = build_segment(dir1.path(), &config).unwrap();
+        config.payload_index = Some(PayloadIndexType::Struct);
+        let mut struct_segment = build_segment(dir2.path(), &config).unwrap();
+
+        let str_key = "kvd".to_string();
+        let int_key = "int".to_string();
+
+        let num_points = 1000;
+
+        let mut opnum = 0;
+        for idx in 0..num_points {
+            let vector = random_vector(&mut rnd, dim);
+            let mut payload: TheMap<PayloadKeyType, PayloadType> = Default::default();
+            payload.insert(str_key.clone(), random_keyword_payload(&mut rnd));
+            payload.insert(int_key.clone(), random_int_payload(&mut rnd, 2));
+
+            plain_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+            struct_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+
+            plain_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
+            struct_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
+
+            opnum += 1;
+        }
+
+        struct_segment.create_field_index(opnum, &str_key).unwrap();
+        struct_segment.create_field_index(opnum, &int_key).unwrap();
+
+        let filter = Filter::new_must(Condition::Field(FieldCondition {
+            key: int_key.clone(),
+            r#match: None,
+            range: Some(Range {
+                lt: None,
+                gt: None,
+                gte: Some(20.),
+                lte: Some(80.),
+            }),
+            geo_bounding_box: None,
+            geo_radius: None,
+        }));
+
+        for query_filter in vec![None, Some(&filter)] {
+            let plain_facets = plain_segment.facet_counts(&str_key, query_filter);
+            let struct_facets = struct_segment.facet_counts(&str_key, query_filter);
+            assert!(!plain_facets.is_empty());
+            assert_eq!(plain_facets, struct_facets);
+
+            let plain_range = plain_segment.numeric_range(&int_key, query_filter).unwrap();
+            let struct_range = struct_segment.numeric_range(&int_key, query_filter).unwrap();
+            assert_eq!(plain_range, struct_range);
+
+            let (min, max) = plain_range;
+            let plain_histogram = plain_segment.numeric_histogram(&int_key, query_filter, min, max, 10);
+            let struct_histogram = struct_segment.numeric_histogram(&int_key, query_filter, min, max, 10);
+            assert_eq!(plain_histogram.len(), 10);
+            assert_eq!(plain_histogram, struct_histogram);
+        }
+    }
+
+    #[test]
+    fn test_on_disk_payload_storage() {
+        // Compare search with in-memory payload and on-disk payload checked with field indexes
+        let mut rnd = rand::thread_rng();
+
+        let dir1 = TempDir::new("segment1_dir").unwrap();
+        let dir2 = TempDir::new("segment2_dir").unwrap();
+
+        let dim = 5;
+
+        let mut config = SegmentConfig {
+            vector_size: dim,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Plain),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: PayloadStorageType::InMemory,
+            distance: Distance::Dot,
+        };
+
+        let mut plain_segment = build_segment(dir1.path(), &config).unwrap();
+        config.payload_index = Some(PayloadIndexType::Struct);
+        config.payload_storage_type = PayloadStorageType::OnDisk;
+        let mut on_disk_segment = build_segment(dir2.path(), &config).unwrap();
+
+        let str_key = "kvd".to_string();
+        let int_key = "int".to_string();
+
+        let num_points = 1000;
+
+        let mut opnum = 0;
+        for idx in 0..num_points {
+            let vector = random_vector(&mut rnd, dim);
+            let mut payload: TheMap<PayloadKeyType, PayloadType> = Default::default();
+            payload.insert(str_key.clone(), random_keyword_payload(&mut rnd));
+            payload.insert(int_key.clone(), random_int_payload(&mut rnd, 2));
+
+            plain_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+            on_disk_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+
+            plain_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
+            on_disk_segment.set_full_payload(idx, idx.into(), payload.clone()).unwrap();
+
+            opnum += 1;
+        }
+
+        on_disk_segment.create_field_index(opnum, &str_key).unwrap();
+        on_disk_segment.create_field_index(opnum, &int_key).unwrap();
+
+        assert_eq!(plain_segment.payload(10.into()).unwrap().len(), on_disk_segment.payload(10.into()).unwrap().len());
+
+        let attempts = 100;
+        for _i in 0..attempts {
+            let query_vector = random_vector(&mut rnd, dim);
+            let query_filter = random_filter(&mut rnd);
+
+            let plain_result = plain_segment.search(&query_vector, Some(&query_filter), 5, None).unwrap();
+            let on_disk_result = on_disk_segment.search(&query_vector, Some(&query_filter), 5, None).unwrap();
+
+            assert_eq!(plain_result.len(), on_disk_result.len());
+            plain_result
+                .iter()
+                .zip(on_disk_result.iter())
+                .for_each(|(r1, r2)| {
+                    assert_eq!(r1.id, r2.id);
+                    assert!((r1.score - r2.score) < 0.0001)
+                });
+        }
+
+        // Field indexes should follow payload changes, made after the indexes were built
+        for idx in 0..100 {
+            opnum += 1;
+            let point_id = idx.into();
+            match idx % 4 {
+                0 => {
+                    let keyword = random_keyword_payload(&mut rnd);
+                    plain_segment.set_payload(opnum, point_id, &str_key, keyword.clone()).unwrap();
+                    on_disk_segment.set_payload(opnum, point_id, &str_key, keyword).unwrap();
+                }
+                1 => {
+                    plain_segment.delete_payload(opnum, point_id, &str_key).unwrap();
+                    on_disk_segment.delete_payload(opnum, point_id, &str_key).unwrap();
+                }
+                2 => {
+                    plain_segment.clear_payload(opnum, point_id).unwrap();
+                    on_disk_segment.clear_payload(opnum, point_id).unwrap();
+                }
+                _ => {
+                    let mut patch: TheMap<PayloadKeyType, serde_json::Value> = TheMap::new();
+                    patch.insert(str_key.clone(), random_keyword(&mut rnd).into());
+                    let mut append: TheMap<PayloadKeyType, serde_json::Value> = TheMap::new();
+                    append.insert(int_key.clone(), vec![0, 1].into());
+                    plain_segment.update_payload(opnum, point_id, &patch, &append).unwrap();
+                    on_disk_segment.update_payload(opnum, point_id, &patch, &append).unwrap();
+                }
+            }
+        }
+
+        // Outdated indexes are not used until the segment is re-built
+        let mut outdated_fields = on_disk_segment.get_outdated_fields();
+        outdated_fields.sort();
+        assert_eq!(outdated_fields, vec![int_key.clone(), str_key.clone()]);
+
+        for _i in 0..attempts {
+            let query_filter = random_filter(&mut rnd);
+            let plain_result = plain_segment.read_filtered(0.into(), num_points as usize, Some(&query_filter));
+            let on_disk_result = on_disk_segment.read_filtered(0.into(), num_points as usize, Some(&query_filter));
+            assert_eq!(plain_result, on_disk_result);
+        }
+    }
+
+    #[test]
+    fn test_mmap_field_indexes() {
+        // Compare plain segment with the optimized one, which reads field indexes from mmap
+        let mut rnd = rand::thread_rng();
+
+        let dir1 = TempDir::new("segment1_dir").unwrap();
+        let dir2 = TempDir::new("segment2_dir").unwrap();
+        let temp_dir = TempDir::new("segment_temp_dir").unwrap();
+
+        let dim = 5;
+
+        let mut config = SegmentConfig {
+            vector_size: dim,
+            index: Indexes::Plain {},
+            payload_index: Some(PayloadIndexType::Plain),
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+            distance: Distance::Dot,
+        };
+
+        let mut plain_segment = build_segment(dir1.path(), &config).unwrap();
+
+        let str_key = "kvd".to_string();
+        let int_key = "int".to_string();
+
+        let num_points = 1000;
+
+        let mut opnum = 0;
+        for idx in 0..num_points {
+            let vector = random_vector(&mut rnd, dim);
+            let mut payload: TheMap<PayloadKeyType, PayloadType> = Default::default();
+            payload.insert(str_key.clone(), random_keyword_payload(&mut rnd));
+            payload.insert(int_key.clone(), random_int_payload(&mut rnd, 2));
+
+            plain_segment.upsert_point(idx, idx.into(), &vector).unwrap();
+            plain_segment.set_full_payload(idx, idx.into(), payload).unwrap();
+
+            opnum += 1;
+        }
+
+        plain_segment.create_field_index(opnum, &str_key).unwrap();
+        plain_segment.create_field_index(opnum, &int_key).unwrap();
+
+        config.payload_index = Some(PayloadIndexType::Struct);
+        config.storage_type = StorageType::Mmap;
+        let mut builder = SegmentBuilder::new(dir2.path(), temp_dir.path(), &config).unwrap();
+        builder.update_from(&plain_segment).unwrap();
+        let mmap_segment: Segment = builder.try_into().unwrap();
+
+        let fields_dir = mmap_segment.current_path.join("payload_index").join("fields");
+        assert!(fields_dir.join(format!("{}.mmap", str_key)).exists());
+        assert!(fields_dir.join(format!("{}.mmap", int_key)).exists());
+
+        let attempts = 100;
+        for _i in 0..attempts {
+            let query_vector = random_vector(&mut rnd, dim);
+            let query_filter = random_filter(&mut rnd);
+
+            let plain_result = plain_segment.search(&query_vector, Some(&query_filter), 5, None).unwrap();
+            let mmap_result = mmap_segment.search(&query_vector, Some(&query_filter), 5, None).unwrap();
+
+            assert_eq!(plain_result.len(), mmap_result.len());
+            plain_result
+                .iter()
+                .zip(mmap_result.iter())
+                .for_each(|(r1, r2)| {
+                    assert_eq!(r1.id, r2.id);
+                    assert!((r1.score - r2.score) < 0.0001)
+                });
+        }
+
+        assert_eq!(plain_segment.facet_counts(&str_key, None), mmap_segment.facet_counts(&str_key, None));
+        assert_eq!(plain_segment.numeric_range(&int_key, None), mmap_segment.numeric_range(&int_key, None));
+    }
+}
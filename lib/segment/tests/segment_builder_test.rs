@@ -8,6 +8,9 @@ mod tests {
     use segment::segment::Segment;
     use std::convert::TryInto;
     use segment::entry::entry_point::SegmentEntry;
+    use segment::segment_constructor::segment_constructor::{load_segment, ID_MAPPER_PATH, MMAP_ID_MAPPER_PATH};
+    use segment::types::{PointIdType, StorageType};
+    use itertools::Itertools;
 
     #[test]
     fn test_building_new_segment() {
@@ -50,4 +53,40 @@ mod tests {
         assert_eq!(merged_segment.vectors_count(), segment1.vectors_count() + segment2.vectors_count())
 
     }
+
+    #[test]
+    fn test_building_non_appendable_segment() {
+        let dir = TempDir::new("segment_dir").unwrap();
+        let temp_dir = TempDir::new("segment_temp_dir").unwrap();
+
+        let segment1 = build_segment_1(dir.path());
+        let segment2 = build_segment_2(dir.path());
+
+        let mut config = segment1.segment_config.clone();
+        config.storage_type = StorageType::Mmap;
+
+        let mut builder = SegmentBuilder::new(dir.path(), temp_dir.path(), &config).unwrap();
+        builder.update_from(&segment1).unwrap();
+        builder.update_from(&segment2).unwrap();
+
+        let mut merged_segment: Segment = builder.try_into().unwrap();
+        let segment_path = merged_segment.current_path.clone();
+
+        // Compact id mapper replaces the default one
+        assert!(segment_path.join(MMAP_ID_MAPPER_PATH).exists());
+        assert!(!segment_path.join(ID_MAPPER_PATH).exists());
+        assert_eq!(merged_segment.iter_points().sorted().collect_vec(),
+                   vec![1u64, 2, 3, 4, 5, 11, 12, 13, 14, 15].into_iter().map(PointIdType::from).collect_vec());
+        assert_eq!(merged_segment.payload(13.into()).unwrap().len(), 1);
+
+        assert!(merged_segment.delete_point(100, 3.into()).unwrap());
+        assert!(!merged_segment.has_point(3.into()));
+        merged_segment.flush().unwrap();
+        drop(merged_segment);
+
+        let loaded_segment = load_segment(&segment_path).unwrap();
+        assert!(!loaded_segment.has_point(3.into()));
+        assert!(loaded_segment.has_point(4.into()));
+        assert_eq!(loaded_segment.iter_points().count(), 9);
+    }
 }
\ No newline at end of file
@@ -6,8 +6,13 @@ mod tests {
     use crate::fixtures::segment::build_segment_1;
     use segment::entry::entry_point::SegmentEntry;
     use std::collections::HashSet;
-    use segment::types::{Filter, Condition};
+    use segment::types::{Filter, Condition, FieldCondition, Match, PointIdType};
     use tempdir::TempDir;
+    use itertools::Itertools;
+
+    fn point_ids(ids: &[u64]) -> Vec<PointIdType> {
+        ids.iter().map(|id| (*id).into()).collect()
+    }
 
     #[test]
     fn test_point_exclusion() {
@@ -16,17 +21,17 @@ mod tests {
 
         let segment = build_segment_1(dir.path());
 
-        assert!(segment.has_point(3));
+        assert!(segment.has_point(3.into()));
 
         let query_vector = vec![1.0, 1.0, 1.0, 1.0];
 
         let res = segment.search(&query_vector, None, 1, None).unwrap();
 
         let best_match = res.get(0).expect("Non-empty result");
-        assert_eq!(best_match.id, 3);
+        assert_eq!(best_match.id, 3.into());
 
 
-        let ids: HashSet<_> = vec![3].into_iter().collect();
+        let ids: HashSet<_> = point_ids(&[3]).into_iter().collect();
 
 
         let frt = Filter {
@@ -39,7 +44,7 @@ mod tests {
         let res = segment.search(&query_vector, Some(&frt), 1, None).unwrap();
 
         let best_match = res.get(0).expect("Non-empty result");
-        assert_ne!(best_match.id, 3);
+        assert_ne!(best_match.id, 3.into());
 
 
         let point_ids1: Vec<_> = segment.iter_points().collect();
@@ -50,4 +55,45 @@ mod tests {
 
         assert_eq!(&point_ids1, &point_ids2)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_read_filtered() {
+        let dir = TempDir::new("segment_dir").unwrap();
+
+        let segment = build_segment_1(dir.path());
+
+        let all_points = segment.read_filtered(0.into(), 100, None);
+        assert_eq!(all_points, point_ids(&[1, 2, 3, 4, 5]));
+
+        let page = segment.read_filtered(2.into(), 2, None);
+        assert_eq!(page, point_ids(&[2, 3]));
+
+        let blue_filter = Filter::new_must(Condition::Field(FieldCondition {
+            key: "color".to_string(),
+            r#match: Some(Match { keyword: Some("blue".to_string()), integer: None }),
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+        }));
+
+        let blue_points = segment.read_filtered(0.into(), 100, Some(&blue_filter));
+        assert_eq!(blue_points, point_ids(&[3, 4, 5]));
+
+        let blue_page = segment.read_filtered(4.into(), 100, Some(&blue_filter));
+        assert_eq!(blue_page, point_ids(&[4, 5]));
+    }
+
+    #[test]
+    fn test_similar_pairs() {
+        let dir = TempDir::new("segment_dir").unwrap();
+
+        let segment = build_segment_1(dir.path());
+
+        let pairs = segment.similar_pairs(3.0, None, usize::MAX).unwrap();
+        let pair_ids: Vec<_> = pairs.iter().map(|pair| (pair.a, pair.b)).sorted().collect();
+        assert_eq!(pair_ids, vec![(1.into(), 3.into()), (3.into(), 4.into())]);
+
+        let pairs = segment.similar_pairs(3.0, None, 1).unwrap();
+        assert_eq!(pairs.len(), 1);
+    }
+}
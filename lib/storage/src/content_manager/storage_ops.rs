@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use schemars::{JsonSchema};
-use segment::types::{Distance};
+use segment::types::{Distance, PayloadStorageType};
 use collection::operations::config_diff::{HnswConfigDiff, WalConfigDiff, OptimizersConfigDiff};
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -36,7 +36,9 @@ pub enum StorageOperations {
         /// Custom params for WAL. If none - values from service configuration file are used.
         wal_config: Option<WalConfigDiff>,
         /// Custom params for Optimizers.  If none - values from service configuration file are used.
-        optimizers_config: Option<OptimizersConfigDiff>
+        optimizers_config: Option<OptimizersConfigDiff>,
+        /// Type of payload storage. Default: in_memory
+        payload_storage_type: Option<PayloadStorageType>,
     },
     /// Update parameters of the existing collection
     UpdateCollection {
@@ -21,6 +21,8 @@ use crate::content_manager::storage_ops::{AliasOperations, StorageOperations};
 use crate::types::StorageConfig;
 use collection::config::CollectionParams;
 use collection::operations::config_diff::{DiffConfig};
+use collection::operations::types::{RecommendRequest, DiscoverRequest};
+use segment::types::ScoredPoint;
 
 /// Since sled is used for reading only during the initialization, large read cache is not required
 const SLED_CACHE_SIZE: u64 = 1 * 1024 * 1024; // 1 mb
@@ -148,6 +150,7 @@ impl TableOfContent {
                 hnsw_config: hnsw_config_diff,
                 wal_config: wal_config_diff,
                 optimizers_config: optimizers_config_diff,
+                payload_storage_type,
             } => {
                 self.validate_collection_not_exists(&collection_name)?;
                 let collection_path = self.create_collection_path(&collection_name)?;
@@ -155,6 +158,7 @@ impl TableOfContent {
                 let collection_params = CollectionParams {
                     vector_size,
                     distance,
+                    payload_storage_type: payload_storage_type.unwrap_or_default(),
                 };
                 let wal_config = match wal_config_diff {
                     None => self.storage_config.wal.clone(),
@@ -251,6 +255,34 @@ impl TableOfContent {
         Ok(read_collection.get(&real_collection_name).unwrap().clone())
     }
 
+    /// Runtime for long-running search tasks, which are not bound to a single request handler
+    pub fn search_runtime(&self) -> &Runtime {
+        &self.search_runtime
+    }
+
+    /// Recommend points of the collection.
+    /// If `lookup_from` is specified in request - ids of the examples are resolved in that collection
+    pub fn recommend(&self, collection_name: &str, request: RecommendRequest) -> Result<Vec<ScoredPoint>, StorageError> {
+        let collection = self.get_collection(collection_name)?;
+        let lookup_collection = self.get_lookup_collection(&collection, &request.lookup_from)?;
+        Ok(collection.recommend_by(Arc::new(request), &lookup_collection)?)
+    }
+
+    /// Discover points of the collection.
+    /// If `lookup_from` is specified in request - ids of the examples are resolved in that collection
+    pub fn discover(&self, collection_name: &str, request: DiscoverRequest) -> Result<Vec<ScoredPoint>, StorageError> {
+        let collection = self.get_collection(collection_name)?;
+        let lookup_collection = self.get_lookup_collection(&collection, &request.lookup_from)?;
+        Ok(collection.discover_by(Arc::new(request), &lookup_collection)?)
+    }
+
+    fn get_lookup_collection(&self, collection: &Arc<Collection>, lookup_from: &Option<String>) -> Result<Arc<Collection>, StorageError> {
+        match lookup_from {
+            None => Ok(collection.clone()),
+            Some(lookup_collection_name) => self.get_collection(lookup_collection_name),
+        }
+    }
+
     /// List of all collections
     pub fn all_collections(&self) -> Vec<String> {
         self.collections.read().keys().cloned().collect()
@@ -0,0 +1,25 @@
+use actix_web::{post, web, Responder};
+use storage::content_manager::toc::TableOfContent;
+use crate::common::helpers::process_response;
+use actix_web::rt::time::Instant;
+use std::sync::Arc;
+use collection::operations::types::ClusterRequest;
+
+#[post("/collections/{name}/points/cluster")]
+pub async fn cluster_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<ClusterRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .cluster(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
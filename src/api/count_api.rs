@@ -0,0 +1,25 @@
+use actix_web::{post, web, Responder};
+use storage::content_manager::toc::TableOfContent;
+use crate::common::helpers::process_response;
+use actix_web::rt::time::Instant;
+use std::sync::Arc;
+use collection::operations::types::CountRequest;
+
+#[post("/collections/{name}/points/count")]
+pub async fn count_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<CountRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .count(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
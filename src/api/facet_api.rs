@@ -0,0 +1,44 @@
+use actix_web::{post, web, Responder};
+use storage::content_manager::toc::TableOfContent;
+use crate::common::helpers::process_response;
+use actix_web::rt::time::Instant;
+use std::sync::Arc;
+use collection::operations::types::{FacetRequest, HistogramRequest};
+
+#[post("/collections/{name}/points/facet")]
+pub async fn facet_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<FacetRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .facet(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/histogram")]
+pub async fn histogram_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<HistogramRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .histogram(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
@@ -4,3 +4,6 @@ pub mod retrieve_api;
 pub mod update_api;
 pub mod search_api;
 pub mod recommend_api;
+pub mod count_api;
+pub mod cluster_api;
+pub mod facet_api;
\ No newline at end of file
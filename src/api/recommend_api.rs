@@ -2,8 +2,7 @@ use actix_web::{post, web, Responder};
 use storage::content_manager::toc::TableOfContent;
 use crate::common::helpers::process_response;
 use actix_web::rt::time::Instant;
-use std::sync::Arc;
-use collection::operations::types::RecommendRequest;
+use collection::operations::types::{RecommendRequest, DiscoverRequest};
 
 
 #[post("/collections/{name}/points/recommend")]
@@ -14,14 +13,20 @@ pub async fn recommend_points(
 ) -> impl Responder {
     let timing = Instant::now();
 
-    let response = {
-        toc.get_collection(&name)
-            .and_then(|collection| {
-                collection
-                    .recommend(Arc::new(request.0))
-                    .map_err(|err| err.into())
-            })
-    };
+    let response = toc.recommend(&name, request.0);
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/discover")]
+pub async fn discover_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<DiscoverRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = toc.discover(&name, request.0);
 
     process_response(response, timing)
 }
@@ -6,27 +6,34 @@ use segment::types::PointIdType;
 use serde::{Deserialize, Serialize};
 use schemars::{JsonSchema};
 use storage::content_manager::errors::StorageError;
+use collection::operations::types::{ScrollRequest, SampleRequest, OrderBy};
+use std::sync::Arc;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub struct PointRequest {
-    pub ids: Vec<PointIdType>
+    pub ids: Vec<PointIdType>,
+    /// Order points by numeric payload field
+    pub order_by: Option<OrderBy>,
 }
 
 #[get("/collections/{name}/points/{id}")]
 pub async fn get_point(
     toc: web::Data<TableOfContent>,
-    web::Path((name, point_id)): web::Path<(String, PointIdType)>,
+    web::Path((name, point_id)): web::Path<(String, String)>,
 ) -> impl Responder {
     let timing = Instant::now();
 
-    let response = {
-        toc.get_collection(&name)
+    // Path segments are always strings, so the id is parsed explicitly to accept both numbers and UUIDs
+    let response = point_id
+        .parse::<PointIdType>()
+        .map_err(|description| StorageError::BadRequest { description })
+        .and_then(|point_id| toc.get_collection(&name)
             .and_then(|collection| collection
                 .retrieve(&vec![point_id], true, true)
                 .map_err(|err| err.into())
                 .map(|points| points.into_iter().next())
             )
-    };
+        );
 
     let response = match response {
         Ok(record) => match record {
@@ -48,10 +55,49 @@ pub async fn get_vectors(
 ) -> impl Responder {
     let timing = Instant::now();
 
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| match &request.order_by {
+                None => collection.retrieve(&request.ids, true, true),
+                Some(order_by) => collection.retrieve_ordered(&request.ids, order_by, true, true),
+            }.map_err(|err| err.into()))
+    };
+
+    process_response(response, timing)
+}
+
+
+#[post("/collections/{name}/points/scroll")]
+pub async fn scroll_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<ScrollRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .scroll(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/sample")]
+pub async fn sample_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<SampleRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
     let response = {
         toc.get_collection(&name)
             .and_then(|collection| collection
-                .retrieve(&request.ids, true, true)
+                .sample(Arc::new(request.0))
                 .map_err(|err| err.into())
             )
     };
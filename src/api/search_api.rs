@@ -1,9 +1,14 @@
-use actix_web::{post, web, Responder};
+use actix_web::{post, web, error, Either, HttpResponse, Responder};
+use actix_web::web::Bytes;
 use storage::content_manager::toc::TableOfContent;
 use crate::common::helpers::process_response;
 use actix_web::rt::time::Instant;
 use std::sync::Arc;
-use collection::operations::types::SearchRequest;
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+use collection::operations::types::{SearchRequest, SearchRequestBatch, FusionSearchRequest, SearchGroupsRequest, SimilarPairsRequest, DistanceMatrixRequest, CollectionResult};
+use segment::types::SimilarPair;
 
 #[post("/collections/{name}/points/search")]
 pub async fn search_points(
@@ -13,6 +18,18 @@ pub async fn search_points(
 ) -> impl Responder {
     let timing = Instant::now();
 
+    if request.explain.unwrap_or(false) {
+        let response = {
+            toc.get_collection(&name)
+                .and_then(|collection| collection
+                    .search_explain(Arc::new(request.0))
+                    .map_err(|err| err.into())
+                )
+        };
+
+        return Either::A(process_response(response, timing));
+    }
+
     let response = {
         toc.get_collection(&name)
             .and_then(|collection| collection
@@ -21,5 +38,120 @@ pub async fn search_points(
             )
     };
 
+    Either::B(process_response(response, timing))
+}
+
+#[post("/collections/{name}/points/search/batch")]
+pub async fn search_batch_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<SearchRequestBatch>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .search_batch(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/search/groups")]
+pub async fn search_point_groups(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<SearchGroupsRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .search_groups(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/search/fusion")]
+pub async fn search_fusion_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<FusionSearchRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .search_fusion(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/similar_pairs")]
+pub async fn similar_point_pairs(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<SimilarPairsRequest>,
+) -> impl Responder {
+    /// Number of found pairs, buffered until they are sent to the client
+    const FOUND_PAIRS_BUFFER_SIZE: usize = 1024;
+
+    let timing = Instant::now();
+
+    let collection = match toc.get_collection(&name) {
+        Ok(collection) => collection,
+        Err(err) => return Either::A(process_response::<()>(Err(err), timing)),
+    };
+
+    // Search runs as a blocking task of the search runtime and is stopped as soon as the client disconnects.
+    // Pairs are sent as newline-delimited JSON, failed search breaks the response.
+    let (mut sender, receiver) = mpsc::channel(FOUND_PAIRS_BUFFER_SIZE);
+    let request = Arc::new(request.into_inner());
+    toc.search_runtime().spawn_blocking(move || {
+        let result = collection.similar_pairs(request, |pair| block_on(sender.send(Ok(pair))).is_ok());
+        if let Err(err) = result {
+            let _ = block_on(sender.send(Err(err)));
+        }
+    });
+
+    let body = receiver.map(|found: CollectionResult<SimilarPair>| found
+        .map(|pair| {
+            let mut line = serde_json::to_vec(&pair).unwrap();
+            line.push(b'\n');
+            Bytes::from(line)
+        })
+        .map_err(|err| error::ErrorInternalServerError(err.to_string()))
+    );
+
+    Either::B(HttpResponse::Ok().content_type("application/x-ndjson").streaming(body))
+}
+
+#[post("/collections/{name}/points/search/matrix")]
+pub async fn search_matrix_points(
+    toc: web::Data<TableOfContent>,
+    web::Path(name): web::Path<String>,
+    request: web::Json<DistanceMatrixRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+
+    let response = {
+        toc.get_collection(&name)
+            .and_then(|collection| collection
+                .distance_matrix(Arc::new(request.0))
+                .map_err(|err| err.into())
+            )
+    };
+
     process_response(response, timing)
 }
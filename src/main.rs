@@ -14,10 +14,13 @@ use env_logger;
 use storage::content_manager::toc::TableOfContent;
 use crate::api::collections_api::{get_collections, update_collections, get_collection};
 use crate::api::update_api::update_points;
-use crate::api::retrieve_api::{get_vectors, get_point};
-use crate::api::search_api::search_points;
+use crate::api::retrieve_api::{get_vectors, get_point, scroll_points, sample_points};
+use crate::api::search_api::{search_points, search_batch_points, search_fusion_points, search_point_groups, similar_point_pairs, search_matrix_points};
 use serde::{Deserialize, Serialize};
-use crate::api::recommend_api::recommend_points;
+use crate::api::recommend_api::{recommend_points, discover_points};
+use crate::api::count_api::count_points;
+use crate::api::cluster_api::cluster_points;
+use crate::api::facet_api::{facet_points, histogram_points};
 
 #[derive(Serialize, Deserialize)]
 pub struct VersionInfo {
@@ -76,7 +79,19 @@ async fn main() -> std::io::Result<()> {
             .service(get_point)
             .service(get_vectors)
             .service(search_points)
+            .service(search_batch_points)
+            .service(search_fusion_points)
+            .service(search_point_groups)
+            .service(similar_point_pairs)
+            .service(search_matrix_points)
             .service(recommend_points)
+            .service(discover_points)
+            .service(scroll_points)
+            .service(sample_points)
+            .service(count_points)
+            .service(facet_points)
+            .service(histogram_points)
+            .service(cluster_points)
             ;
 
         app
@@ -7,10 +7,10 @@ use serde_json;
 use crate::api::models::CollectionsResponse;
 use crate::api::retrieve_api::PointRequest;
 
-use collection::operations::types::{CollectionInfo, Record, SearchRequest, UpdateResult, RecommendRequest};
+use collection::operations::types::{CollectionInfo, Record, SearchRequest, SearchRequestBatch, FusionSearchRequest, SearchGroupsRequest, SimilarPairsRequest, SearchGroupsResult, UpdateResult, RecommendRequest, DiscoverRequest, ScrollRequest, ScrollResult, CountRequest, CountResult, FacetRequest, FacetResult, HistogramRequest, HistogramResult, ClusterRequest, ClusterResult, DistanceMatrixRequest, DistanceMatrixResult, SampleRequest, SearchExplainResult};
 use storage::content_manager::storage_ops::StorageOperations;
 use serde::{Deserialize, Serialize};
-use segment::types::ScoredPoint;
+use segment::types::{ScoredPoint, SimilarPair};
 use collection::operations::CollectionUpdateOperations;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -24,7 +24,28 @@ struct AllDefinitions {
     a7: ScoredPoint,
     a8: UpdateResult,
     a9: CollectionUpdateOperations,
-    aa: RecommendRequest
+    aa: RecommendRequest,
+    ab: ScrollRequest,
+    ac: ScrollResult,
+    ad: CountRequest,
+    ae: CountResult,
+    af: SearchRequestBatch,
+    ag: SearchGroupsRequest,
+    ah: SearchGroupsResult,
+    ai: DiscoverRequest,
+    aj: FacetRequest,
+    ak: FacetResult,
+    al: HistogramRequest,
+    am: HistogramResult,
+    an: FusionSearchRequest,
+    ao: SimilarPairsRequest,
+    ap: SimilarPair,
+    aq: ClusterRequest,
+    ar: ClusterResult,
+    at: DistanceMatrixRequest,
+    au: DistanceMatrixResult,
+    av: SampleRequest,
+    aw: SearchExplainResult,
 }
 
 